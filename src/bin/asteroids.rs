@@ -0,0 +1,265 @@
+use std::f32::consts::TAU;
+use glam::{Mat4, Quat, Vec3, Vec4};
+use glfw::{Action, Context, Key};
+use rand::Rng;
+use opengl_book_examples::camera::camera::Camera;
+use opengl_book_examples::common::common::{handle_window_event, process_input};
+use opengl_book_examples::flashlight::flashlight::FlashLight;
+use opengl_book_examples::frame_stats::frame_stats::FrameStats;
+use opengl_book_examples::lights::lights::DirectionalLight;
+use opengl_book_examples::mesh::mesh::Instance;
+use opengl_book_examples::shaders::shaders::ShaderProgram;
+use opengl_book_examples::shapes::shapes;
+
+const DEFAULT_COUNT: usize = 20_000;
+const RING_INNER_RADIUS: f32 = 12.0;
+const RING_OUTER_RADIUS: f32 = 24.0;
+
+/// How one asteroid moves, its matrix is rebuilt from this every frame.
+struct Asteroid {
+    radius: f32,
+    height: f32,
+    orbit_angle: f32,
+    /// Radians per second around the planet
+    orbit_speed: f32,
+    spin_axis: Vec3,
+    /// Radians per second around `spin_axis`
+    spin_speed: f32,
+    scale: Vec3,
+    color: Vec4,
+    shininess: f32
+}
+
+impl Asteroid {
+    fn model(&self, time: f32) -> Mat4 {
+        let angle = self.orbit_angle + self.orbit_speed * time;
+        let position = Vec3::new(angle.cos() * self.radius, self.height, angle.sin() * self.radius);
+        let spin = Quat::from_axis_angle(self.spin_axis, self.spin_speed * time);
+        Mat4::from_scale_rotation_translation(self.scale, spin, position)
+    }
+}
+
+/// A planet with a ring of rocks, by default 20000 of them, all rotating on their own and orbiting
+/// at their own speed. Pass another count as the first argument, e.g. `cargo run --release --bin
+/// asteroids -- 100000`. I switches between one instanced draw call for the whole ring and one
+/// draw call per rock, C toggles skipping the rocks outside the view frustum. The window title
+/// shows the frame rate of the current path and how many rocks were culled. Each second the
+/// frame, CPU and GPU times of the planet and of the rocks since the last switch are printed for
+/// comparisons. V-sync is off so the frame rate isn't capped by the display.
+fn main() {
+    let count: usize = match std::env::args().nth(1) {
+        Some(arg) => match arg.parse() {
+            Ok(count) => count,
+            Err(_) => {
+                eprintln!("Usage: asteroids [count]");
+                std::process::exit(1);
+            }
+        },
+        None => DEFAULT_COUNT
+    };
+
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+    glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
+    glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+    #[cfg(target_os = "macos")]
+    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+    let (mut window, events) =
+        glfw.create_window(800, 600, "Asteroids", glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window.");
+
+    window.make_current();
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+    glfw.set_swap_interval(glfw::SwapInterval::None);
+
+    // set the cursor at the middle of the screen
+    window.set_cursor_mode(glfw::CursorMode::Disabled);
+
+    // GLAD OpenGL function pointers
+    gl::load_with(|symbol| match window.get_proc_address(symbol) {
+        Some(f) => f as *const _,
+        None => std::ptr::null(),
+    });
+
+    // Shader setup
+    let shader = match ShaderProgram::from_files("src/shaders/vertex/instanced.vert", "src/shaders/fragment/instanced.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    let planet = shapes::uv_sphere(64, 32);
+    let mut rock = shapes::uv_sphere(8, 6);
+
+    let mut rng = rand::rng();
+    let asteroids: Vec<Asteroid> = (0..count)
+        .map(|_| {
+            // denser towards the middle of the ring
+            let t: f32 = (rng.random_range(0.0..1.0) + rng.random_range(0.0..1.0)) * 0.5;
+            let radius = RING_INNER_RADIUS + t * (RING_OUTER_RADIUS - RING_INNER_RADIUS);
+            let size = rng.random_range(0.05..0.25);
+            let grey = rng.random_range(0.35..0.6);
+            Asteroid {
+                radius,
+                height: rng.random_range(-0.8..0.8),
+                orbit_angle: rng.random_range(0.0..TAU),
+                // farther rocks are slower, as for a real orbit
+                orbit_speed: 0.6 / radius.sqrt(),
+                spin_axis: Vec3::new(
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0)
+                ).normalize_or(Vec3::Y),
+                spin_speed: rng.random_range(-2.0..2.0),
+                scale: Vec3::new(
+                    size * rng.random_range(0.7..1.3),
+                    size * rng.random_range(0.7..1.3),
+                    size * rng.random_range(0.7..1.3)
+                ),
+                color: Vec4::new(grey * 1.1, grey, grey * 0.9, 1.0),
+                shininess: rng.random_range(0.0..0.5)
+            }
+        })
+        .collect();
+    let mut instances: Vec<Instance> = Vec::with_capacity(count);
+
+    let mut delta_time: f32; // Time between the current frame and last frame
+    let mut last_x: f32 = 400.0;
+    let mut last_y: f32 = 300.0;
+    let mut first_mouse: bool = true;
+    let (mut width, mut height) = window.get_framebuffer_size();
+
+    // Camera setup
+    let mut camera: Camera = Camera::new(
+        Vec3::new(0.0, 8.0, 40.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        false
+    );
+    camera.set_orientation(-90.0, -12.0);
+    let mut flashlight: FlashLight = FlashLight::new(false);
+    let mut wireframe_mode: bool = false;
+    let mut instanced: bool = true;
+    let mut culling: bool = true;
+
+    let sun = DirectionalLight {
+        direction: Vec3::new(-1.0, -0.3, -0.5),
+        ambient: Vec3::splat(0.05),
+        diffuse: Vec3::splat(0.9),
+        specular: Vec3::splat(0.6)
+    };
+
+    // times since the last switch between paths
+    let mut frame_stats = FrameStats::default();
+
+    unsafe {
+        gl::Enable(gl::DEPTH_TEST);
+    }
+
+    // MAIN LOOP
+    while !window.should_close() {
+        // Input
+        let current_frame: f32 = glfw.get_time() as f32;
+        delta_time = frame_stats.begin_frame(current_frame);
+
+        for (_, event) in glfw::flush_messages(&events) {
+            match event {
+                glfw::WindowEvent::FramebufferSize(new_width, new_height) => {
+                    width = new_width;
+                    height = new_height;
+                }
+                glfw::WindowEvent::Key(Key::I, _, Action::Press, _) => {
+                    instanced = !instanced;
+                    frame_stats.reset();
+                }
+                glfw::WindowEvent::Key(Key::C, _, Action::Press, _) => {
+                    culling = !culling;
+                    frame_stats.reset();
+                }
+                _ => {}
+            }
+            handle_window_event(
+                &mut window,
+                event,
+                &mut first_mouse,
+                &mut last_x,
+                &mut last_y,
+                &mut camera,
+                &mut flashlight,
+                &mut wireframe_mode
+            );
+        }
+        // a minimised window has nothing to draw into, wait until it is restored
+        if width <= 0 || height <= 0 {
+            glfw.wait_events();
+            continue;
+        }
+        process_input(&window, &mut camera, delta_time);
+
+        // both paths build every matrix on the CPU, so they only differ in how they submit them
+        let aspect_ratio = width as f32 / height as f32;
+        let frustum = camera.frustum(aspect_ratio, 0.1, 200.0);
+        instances.clear();
+        instances.extend(asteroids.iter()
+            .map(|asteroid| Instance {
+                model: asteroid.model(current_frame),
+                color: asteroid.color,
+                data: Vec4::new(asteroid.shininess, 0.0, 0.0, 0.0)
+            })
+            .filter(|instance| !culling || frustum.intersects_sphere(&rock.bounding_sphere().transformed(&instance.model))));
+        let culled = count - instances.len();
+
+        // Render
+        frame_stats.begin_pass("planet");
+        let view = camera.get_view_matrix();
+        let projection = camera.get_projection_matrix(aspect_ratio, 0.1, 200.0);
+        unsafe {
+            gl::ClearColor(0.01, 0.01, 0.02, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        shader.use_program();
+        shader.set_mat4(c"view", &view);
+        shader.set_mat4(c"projection", &projection);
+        sun.set_uniforms(&shader, "dirLight", &view);
+
+        shader.set_bool(c"instanced", false);
+        shader.set_mat4(c"model", &(Mat4::from_rotation_y(current_frame * 0.05) * Mat4::from_scale(Vec3::splat(6.0))));
+        shader.set_vec4(c"color", &[0.8, 0.45, 0.25, 1.0]);
+        shader.set_vec4(c"data", &[0.1, 0.0, 0.0, 0.0]);
+        planet.draw(&shader);
+
+        frame_stats.begin_pass("rocks");
+        if instanced {
+            shader.set_bool(c"instanced", true);
+            rock.set_instances(&instances);
+            rock.draw_instanced(&shader, instances.len());
+        } else {
+            for instance in &instances {
+                shader.set_mat4(c"model", &instance.model);
+                shader.set_vec4(c"color", &instance.color.to_array());
+                shader.set_vec4(c"data", &instance.data.to_array());
+                rock.draw(&shader);
+            }
+        }
+
+        frame_stats.end_frame();
+        if let Some(report) = frame_stats.report(1.0) {
+            let path = if instanced { "instanced" } else { "one draw call each" };
+            let stats = format!(
+                "{count} asteroids, {culled} culled, {path}: {:.1} fps ({:.2} ms)",
+                frame_stats.frame_rate(),
+                frame_stats.frame_time().average()
+            );
+            window.set_title(&format!("Asteroids - {stats}"));
+            println!("{stats}\n{report}");
+        }
+
+        // Check call events and swap the buffers
+        glfw.poll_events();
+        window.swap_buffers();
+    }
+}
@@ -0,0 +1,281 @@
+use std::ffi::CString;
+use std::path::Path;
+use glam::{Mat4, Vec3};
+use glfw::{Action, Context, Key};
+use rand::Rng;
+use opengl_book_examples::camera::camera::Camera;
+use opengl_book_examples::common::common::{handle_window_event, process_input};
+use opengl_book_examples::deferred::deferred::DeferredRenderer;
+use opengl_book_examples::flashlight::flashlight::FlashLight;
+use opengl_book_examples::lights::lights::{DirectionalLight, PointLight, SpotLight};
+use opengl_book_examples::shaders::shaders::ShaderProgram;
+use opengl_book_examples::shapes::shapes;
+use opengl_book_examples::textures::textures::Texture;
+
+/// Same scene as `multiple_lights`, lit either with the forward shader, which only knows about the
+/// first 4 point lights, or with the deferred renderer, which takes any number of them.
+/// M switches between both paths, V cycles the G-buffer views, L adds 64 random lights and
+/// Backspace removes them. O toggles SSAO, K cycles its sample count and J its blur.
+fn main() {
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+    glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
+    glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+    #[cfg(target_os = "macos")]
+    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+    let (mut window, events) =
+        glfw.create_window(800, 600, "Deferred shading", glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window.");
+
+    window.make_current();
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+
+    // set the cursor at the middle of the screen
+    window.set_cursor_mode(glfw::CursorMode::Disabled);
+
+    // GLAD OpenGL function pointers
+    gl::load_with(|symbol| match window.get_proc_address(symbol) {
+        Some(f) => f as *const _,
+        None => std::ptr::null(),
+    });
+
+    // Shader setup
+    let forward = match ShaderProgram::from_files("src/shaders/vertex/lights.vert", "src/shaders/fragment/lights.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let lamp = match ShaderProgram::from_files("src/shaders/vertex/light_source.vert", "src/shaders/fragment/light_source.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let mut deferred = match DeferredRenderer::new(800, 600) {
+        Ok(renderer) => renderer,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    // Texture setup
+    let texture1 = match Texture::load_texture(Path::new("src/textures/wooden-container-with-metal-frame.png"), true) {
+        Ok(id) => id,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let texture2 = match Texture::load_texture(Path::new("src/textures/steel-frame.png"), true) {
+        Ok(id) => id,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    let cube = shapes::cube();
+    unsafe { gl::Enable(gl::DEPTH_TEST); }
+
+    let mut delta_time: f32; // Time between the current frame and last frame
+    let mut last_frame: f32 = 0.0; // Time of last frame
+    let mut last_x: f32 = 400.0;
+    let mut last_y: f32 = 300.0;
+    let mut first_mouse: bool = true;
+    let (mut width, mut height) = (800, 600);
+
+    // Camera setup
+    let mut camera: Camera = Camera::new(
+        Vec3::new(0.0, 1.0, 3.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        true
+    );
+    let mut flashlight: FlashLight = FlashLight::new(true);
+    let mut wireframe_mode: bool = false;
+    let mut use_deferred: bool = true;
+
+    forward.use_program();
+    forward.set_int(c"material.diffuse", 0);
+    forward.set_int(c"material.specular", 1);
+
+    let cube_positions: [Vec3; 10] = [
+        Vec3::new( 0.0,  0.0,  0.0),
+        Vec3::new( 2.0,  5.0, -15.0),
+        Vec3::new(-1.5, -2.2, -2.5),
+        Vec3::new(-3.8, -2.0, -12.3),
+        Vec3::new( 2.4, -0.4, -3.5),
+        Vec3::new(-1.7,  3.0, -7.5),
+        Vec3::new( 1.3, -2.0, -2.5),
+        Vec3::new( 1.5,  2.0, -2.5),
+        Vec3::new( 1.5,  0.2, -1.5),
+        Vec3::new(-1.3,  1.0, -1.5)
+    ];
+
+    let directional_light = DirectionalLight {
+        direction: Vec3::new(-0.2, -1.0, -0.3),
+        ambient: Vec3::splat(0.2),
+        diffuse: Vec3::splat(0.5),
+        specular: Vec3::splat(1.0)
+    };
+    let mut point_lights: Vec<PointLight> = vec![
+        PointLight::new(Vec3::new(0.7, 0.2, 1.0), Vec3::splat(0.1), Vec3::new(1.0, 0.65, 0.0), Vec3::ONE),
+        PointLight::new(Vec3::new(2.3, -1.3, -4.0), Vec3::splat(0.1), Vec3::new(0.0, 1.0, 0.0), Vec3::ONE),
+        PointLight::new(Vec3::new(-4.0, 2.0, -9.0), Vec3::splat(0.1), Vec3::new(1.0, 0.0, 0.0), Vec3::ONE),
+        PointLight::new(Vec3::new(0.0, 0.0, -3.0), Vec3::splat(0.1), Vec3::new(0.0, 0.0, 1.0), Vec3::ONE)
+    ];
+    let mut rng = rand::rng();
+
+    // MAIN LOOP
+    while !window.should_close() {
+        // Input
+        let current_frame: f32 = glfw.get_time() as f32;
+        delta_time = current_frame - last_frame;
+        last_frame = current_frame;
+
+        for (_, event) in glfw::flush_messages(&events) {
+            match event {
+                glfw::WindowEvent::FramebufferSize(new_width, new_height) => {
+                    width = new_width;
+                    height = new_height;
+                    if let Err(e) = deferred.resize(width, height) {
+                        panic!("{}", e.to_string())
+                    }
+                }
+                glfw::WindowEvent::Key(Key::M, _, Action::Press, _) => {
+                    use_deferred = !use_deferred;
+                    println!("Deferred shading: {}", use_deferred);
+                }
+                glfw::WindowEvent::Key(Key::V, _, Action::Press, _) => {
+                    deferred.view = deferred.view.next();
+                    println!("G-buffer view: {:?}", deferred.view);
+                }
+                glfw::WindowEvent::Key(Key::O, _, Action::Press, _) => {
+                    deferred.ssao.enabled = !deferred.ssao.enabled;
+                    println!("SSAO: {}", deferred.ssao.enabled);
+                }
+                glfw::WindowEvent::Key(Key::K, _, Action::Press, _) => {
+                    let sample_count = match deferred.ssao.sample_count() {
+                        8 => 16,
+                        16 => 32,
+                        32 => 64,
+                        _ => 8
+                    };
+                    deferred.ssao.set_sample_count(sample_count);
+                    println!("SSAO samples: {}", sample_count);
+                }
+                glfw::WindowEvent::Key(Key::J, _, Action::Press, _) => {
+                    deferred.ssao.blur = !deferred.ssao.blur;
+                    println!("SSAO blur: {}", deferred.ssao.blur);
+                }
+                glfw::WindowEvent::Key(Key::L, _, Action::Press, _) => {
+                    // dimmer lights with a stronger falloff so their volumes stay small
+                    for _ in 0..64 {
+                        let position = Vec3::new(
+                            rng.random_range(-6.0..6.0),
+                            rng.random_range(-4.0..6.0),
+                            rng.random_range(-16.0..2.0)
+                        );
+                        let color = Vec3::new(rng.random(), rng.random(), rng.random()) * 0.5 + Vec3::splat(0.1);
+                        let mut light = PointLight::new(position, Vec3::ZERO, color, color);
+                        light.linear = 0.7;
+                        light.quadratic = 1.8;
+                        point_lights.push(light);
+                    }
+                    println!("Point lights: {}", point_lights.len());
+                }
+                glfw::WindowEvent::Key(Key::Backspace, _, Action::Press, _) => {
+                    point_lights.truncate(4);
+                    println!("Point lights: {}", point_lights.len());
+                }
+                _ => {}
+            }
+            handle_window_event(
+                &mut window,
+                event,
+                &mut first_mouse,
+                &mut last_x,
+                &mut last_y,
+                &mut camera,
+                &mut flashlight,
+                &mut wireframe_mode
+            );
+        }
+        // a minimised window has nothing to draw into, wait until it is restored
+        if width <= 0 || height <= 0 {
+            glfw.wait_events();
+            continue;
+        }
+        process_input(&window, &mut camera, delta_time);
+
+        let view_matrix = camera.get_view_matrix();
+        let projection_matrix: Mat4 = Mat4::perspective_rh_gl(camera.zoom.to_radians(), width as f32 / height as f32, 0.1, 100.0);
+        let light = flashlight.get_light();
+        let spot_light = SpotLight::flashlight(camera.position, camera.front(), Vec3::new(light[0], light[1], light[2]));
+
+        // Rendering
+        let program = if use_deferred {
+            deferred.begin_geometry_pass(&view_matrix, &projection_matrix)
+        } else {
+            unsafe {
+                gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            }
+            forward.use_program();
+            forward.set_float(c"material.shininess", 32.0);
+            // Phong in gamma space like the deferred lighting shaders, so both paths can be compared
+            forward.set_bool(c"blinn", false);
+            forward.set_bool(c"gammaCorrection", false);
+            forward.set_mat4(c"view", &view_matrix);
+            forward.set_mat4(c"projection", &projection_matrix);
+            directional_light.set_uniforms(&forward, "dirLight", &view_matrix);
+            spot_light.set_uniforms(&forward, "spotLight", &view_matrix);
+            for (i, point_light) in point_lights.iter().take(4).enumerate() {
+                point_light.set_uniforms(&forward, &format!("pointLights[{i}]"), &view_matrix);
+                let c_str = CString::new(format!("lightPositions[{i}]")).unwrap();
+                forward.set_vec3(&c_str, &view_matrix.transform_point3(point_light.position).to_array());
+            }
+            &forward
+        };
+
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, texture1);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, texture2);
+        }
+        for (i, v) in cube_positions.iter().enumerate() {
+            let angle = 20.0 * i as f32;
+            let model_matrix = Mat4::from_translation(*v) * Mat4::from_axis_angle(Vec3::new(1.0, 0.3, 0.5).normalize(), angle);
+            program.set_mat4(c"model", &model_matrix);
+            cube.draw(program);
+        }
+
+        if use_deferred {
+            deferred.render_lighting(
+                &view_matrix,
+                &projection_matrix,
+                &directional_light,
+                &spot_light,
+                &point_lights,
+                None
+            );
+        }
+
+        // lamps are drawn forward on top of either path
+        lamp.use_program();
+        lamp.set_mat4(c"view", &view_matrix);
+        lamp.set_mat4(c"projection", &projection_matrix);
+        for point_light in &point_lights {
+            lamp.set_vec3(c"lightColorSource", &point_light.diffuse.to_array());
+            let model_matrix = Mat4::from_translation(point_light.position) * Mat4::from_scale(Vec3::splat(0.1));
+            lamp.set_mat4(c"model", &model_matrix);
+            cube.draw(&lamp);
+        }
+
+        // Check call events and swap the buffers
+        glfw.poll_events();
+        window.swap_buffers();
+    }
+}
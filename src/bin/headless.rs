@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use opengl_book_examples::headless::headless::{HeadlessContext, HeadlessRenderer, Scene};
+use opengl_book_examples::scene_file::scene_file::LoadedScene;
+use opengl_book_examples::scenes::scenes;
+
+const USAGE: &str = "Usage: headless <scene | scene.toml> [--frames N] [--time-step SECONDS] [--size WIDTHxHEIGHT] [--samples N] [--output FILE.png]";
+
+/// Renders one of the example scenes without a window and writes the last frame as a PNG, e.g.
+/// `LIBGL_ALWAYS_SOFTWARE=1 cargo run --bin headless -- multiple_lights --frames 60 --output out.png`
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(scene_name) = args.next() else {
+        eprintln!("{USAGE}\nScenes: {}", scenes::NAMES.join(", "));
+        return ExitCode::FAILURE;
+    };
+    let mut frames: u32 = 1;
+    let mut time_step: f32 = 1.0 / 60.0;
+    let (mut width, mut height) = (800, 600);
+    let mut samples = 1;
+    let stem = Path::new(&scene_name).file_stem().map_or(scene_name.clone(), |stem| stem.to_string_lossy().into_owned());
+    let mut output = PathBuf::from(format!("{stem}.png"));
+
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        let parsed = match (arg.as_str(), value.as_deref()) {
+            ("--frames", Some(value)) => value.parse().map(|value| frames = value).is_ok(),
+            ("--time-step", Some(value)) => value.parse().map(|value| time_step = value).is_ok(),
+            ("--samples", Some(value)) => value.parse().map(|value| samples = value).is_ok(),
+            ("--size", Some(value)) => match value.split_once('x') {
+                Some((w, h)) => match (w.parse(), h.parse()) {
+                    (Ok(w), Ok(h)) => {
+                        (width, height) = (w, h);
+                        true
+                    }
+                    _ => false
+                },
+                None => false
+            },
+            ("--output", Some(value)) => {
+                output = PathBuf::from(value);
+                true
+            }
+            _ => false
+        };
+        if !parsed {
+            eprintln!("Invalid argument {arg}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let _context = match HeadlessContext::new() {
+        Ok(context) => context,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let loaded = if scene_name.ends_with(".toml") {
+        Some(LoadedScene::load(&scene_name).map(|scene| Box::new(scene) as Box<dyn Scene>))
+    } else {
+        scenes::by_name(&scene_name)
+    };
+    let mut scene = match loaded {
+        Some(Ok(scene)) => scene,
+        Some(Err(e)) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+        None => {
+            eprintln!("Unknown scene {scene_name}, expected one of: {}", scenes::NAMES.join(", "));
+            return ExitCode::FAILURE;
+        }
+    };
+    let renderer = match HeadlessRenderer::new(width, height, samples) {
+        Ok(renderer) => renderer,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = renderer.render_to_png(scene.as_mut(), frames, time_step, &output) {
+        eprintln!("{e}");
+        return ExitCode::FAILURE;
+    }
+    println!("Rendered {frames} frame(s) of {scene_name} to {}", output.display());
+    ExitCode::SUCCESS
+}
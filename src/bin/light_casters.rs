@@ -2,13 +2,16 @@ use std::ffi::CStr;
 use std::path::Path;
 use std::ptr;
 use glam::{Mat4, Vec3, Vec4};
-use glfw::{Context};
+use glfw::{Action, Context, Key};
 use opengl_book_examples::camera::camera::Camera;
 use opengl_book_examples::common::common::{handle_window_event, process_input};
 use opengl_book_examples::flashlight::flashlight::FlashLight;
 use opengl_book_examples::shaders::shaders::{Shader, ShaderProgram, ShaderType};
 use opengl_book_examples::textures::textures::Texture;
 
+/// A flashlight lighting the crates. P switches between Phong and Blinn-Phong specular, L between
+/// lighting in gamma space and in linear space, with the diffuse map decoded from sRGB and the
+/// result gamma corrected by the shader.
 fn main() {
     let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
     glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
@@ -83,6 +86,13 @@ fn main() {
             panic!("{}", e.to_string())
         }
     };
+    // same diffuse map decoded from sRGB, for linear space lighting
+    let texture1_srgb = match Texture::load_srgb_texture(Path::new("src/textures/wooden-container-with-metal-frame.png"), true) {
+        Ok(id) => id,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
     let texture2 = match Texture::load_texture(Path::new("src/textures/steel-frame.png"), true) {
         Ok(id) => id,
         Err(e) => {
@@ -222,6 +232,9 @@ fn main() {
     let mut lamp_color = Vec3::new(1.0, 1.0, 1.0);
 
     let mut wireframe_mode = false;
+    // P switches between Phong and Blinn-Phong, L between gamma space and linear space lighting
+    let mut blinn: bool = false;
+    let mut linear_lighting: bool = false;
     cube_object.use_program();
     cube_object.set_int(c"material.diffuse", 0);
     cube_object.set_int(c"material.specular", 1);
@@ -247,6 +260,17 @@ fn main() {
         last_frame = current_frame;
 
         for (_, event) in glfw::flush_messages(&events) {
+            match event {
+                glfw::WindowEvent::Key(Key::P, _, Action::Press, _) => {
+                    blinn = !blinn;
+                    println!("Specular: {}", if blinn { "Blinn-Phong" } else { "Phong" });
+                }
+                glfw::WindowEvent::Key(Key::L, _, Action::Press, _) => {
+                    linear_lighting = !linear_lighting;
+                    println!("Linear space lighting: {}", linear_lighting);
+                }
+                _ => {}
+            }
             handle_window_event(
                 &mut window,
                 event,
@@ -270,13 +294,15 @@ fn main() {
             gl::Clear(gl::COLOR_BUFFER_BIT);
 
             gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, texture1);
+            gl::BindTexture(gl::TEXTURE_2D, if linear_lighting { texture1_srgb } else { texture1 });
             gl::ActiveTexture(gl::TEXTURE1);
             gl::BindTexture(gl::TEXTURE_2D, texture2);
 
             cube_object.use_program();
             // cube_object.set_vec3(c"lightPos", &light_position.to_array());
             cube_object.set_float(c"material.shininess", 32.0);
+            cube_object.set_bool(c"blinn", blinn);
+            cube_object.set_bool(c"gammaCorrection", linear_lighting);
 
             // let light_direction = Vec3::new(-0.2, -1.0, -0.3);
             // let light_direction = view_matrix * Vec4::new(-0.2, -1.0, -0.3, 0.0);
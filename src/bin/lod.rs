@@ -0,0 +1,269 @@
+use glam::{Mat4, Vec3};
+use glfw::{Action, Context, Key};
+use opengl_book_examples::camera::camera::Camera;
+use opengl_book_examples::common::common::{handle_window_event, process_input};
+use opengl_book_examples::flashlight::flashlight::FlashLight;
+use opengl_book_examples::lights::lights::{DirectionalLight, PointLight, SpotLight};
+use opengl_book_examples::lod::lod::{self, LodMesh, LodSelector};
+use opengl_book_examples::material::material::Material;
+use opengl_book_examples::model::model::Model;
+use opengl_book_examples::renderer::renderer::{DrawItem, RenderQueue};
+use opengl_book_examples::shaders::shaders::ShaderProgram;
+use opengl_book_examples::shapes::shapes;
+
+const COLUMNS: usize = 7;
+const ROWS: usize = 24;
+const SPACING: f32 = 4.0;
+/// Triangles kept by each generated level, as a ratio of the full detail mesh, and the screen
+/// size below which it is used
+const LEVELS: [(f32, f32); 3] = [(0.25, 0.3), (0.06, 0.12), (0.015, 0.05)];
+
+/// What the field is made of, the same object everywhere.
+enum Object {
+    Sphere(LodMesh),
+    Model(Model)
+}
+
+impl Object {
+    fn selector(&self) -> &LodSelector {
+        match self {
+            Object::Sphere(sphere) => &sphere.selector,
+            Object::Model(model) => &model.lod_selector
+        }
+    }
+
+    fn selector_mut(&mut self) -> &mut LodSelector {
+        match self {
+            Object::Sphere(sphere) => &mut sphere.selector,
+            Object::Model(model) => &mut model.lod_selector
+        }
+    }
+
+    /// Triangles drawn for one copy at the level.
+    fn triangles(&self, level: usize) -> usize {
+        match self {
+            Object::Sphere(sphere) => sphere.mesh(level).indices.len() / 3,
+            Object::Model(model) => model.lod(level).iter().map(|mesh| mesh.indices.len() / 3).sum()
+        }
+    }
+}
+
+/// A field of detailed spheres stretching away from the camera, each drawn with the level of
+/// detail its screen size calls for. The coarser levels are generated by the quadric simplifier.
+/// Passing the path of a model file, in any format Assimp reads, fills the field with it instead.
+/// C tints every object with the colour of its level, green being the full detail, H toggles the
+/// hysteresis that keeps objects at a threshold from switching back and forth. The window title
+/// shows the triangles drawn and the objects at each level.
+fn main() {
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+    glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
+    glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+    #[cfg(target_os = "macos")]
+    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+    let (mut window, events) =
+        glfw.create_window(800, 600, "Levels of detail", glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window.");
+
+    window.make_current();
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+
+    // set the cursor at the middle of the screen
+    window.set_cursor_mode(glfw::CursorMode::Disabled);
+
+    // GLAD OpenGL function pointers
+    gl::load_with(|symbol| match window.get_proc_address(symbol) {
+        Some(f) => f as *const _,
+        None => std::ptr::null(),
+    });
+
+    // Shader setup
+    let lit = match ShaderProgram::from_files("src/shaders/vertex/lights.vert", "src/shaders/fragment/material.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    let mut object = match std::env::args().nth(1) {
+        Some(path) => {
+            let mut model = match Model::load(&path) {
+                Ok(model) => model,
+                Err(e) => {
+                    panic!("{}", e.to_string())
+                }
+            };
+            for (ratio, threshold) in LEVELS {
+                if let Err(e) = model.generate_lod(ratio, threshold) {
+                    panic!("{}", e.to_string())
+                }
+            }
+            Object::Model(model)
+        }
+        None => {
+            let (vertices, indices) = shapes::uv_sphere_geometry(96, 48);
+            match LodMesh::generate(vertices, indices, &LEVELS) {
+                Ok(sphere) => Object::Sphere(sphere),
+                Err(e) => {
+                    panic!("{}", e.to_string())
+                }
+            }
+        }
+    };
+    for level in 0..object.selector().levels() {
+        println!("level {level}: {} triangles", object.triangles(level));
+    }
+
+    // scale the object so it fits in a unit sphere whatever it is
+    let bounds = match &object {
+        Object::Sphere(sphere) => *sphere.mesh(0).bounding_sphere(),
+        Object::Model(model) => model.bounding_sphere()
+    };
+    let normalize = Mat4::from_scale(Vec3::splat(1.0 / bounds.radius)) * Mat4::from_translation(-bounds.center);
+    let placements: Vec<Mat4> = (0..ROWS)
+        .flat_map(|row| (0..COLUMNS).map(move |column| {
+            let x = (column as f32 - (COLUMNS - 1) as f32 * 0.5) * SPACING;
+            Mat4::from_translation(Vec3::new(x, 0.0, -(row as f32) * SPACING)) * normalize
+        }))
+        .collect();
+    // level each copy was drawn at last frame, for the hysteresis
+    let mut levels = vec![0; placements.len()];
+
+    let plain = Material {
+        specular_color: Vec3::splat(0.5),
+        shininess: 32.0,
+        ..Material::default()
+    };
+    let tinted: Vec<Material> = lod::LEVEL_COLORS.iter()
+        .map(|color| Material { diffuse_color: *color, ..plain })
+        .collect();
+
+    let mut delta_time: f32; // Time between the current frame and last frame
+    let mut last_frame: f32 = 0.0; // Time of last frame
+    let mut last_x: f32 = 400.0;
+    let mut last_y: f32 = 300.0;
+    let mut first_mouse: bool = true;
+    let (mut width, mut height) = window.get_framebuffer_size();
+
+    // Camera setup
+    let mut camera: Camera = Camera::new(
+        Vec3::new(0.0, 3.0, 6.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        false
+    );
+    camera.set_orientation(-90.0, -10.0);
+    let mut flashlight: FlashLight = FlashLight::new(false);
+    let mut wireframe_mode: bool = false;
+    let mut show_levels: bool = false;
+
+    let directional_light = DirectionalLight {
+        direction: Vec3::new(-0.4, -1.0, -0.5),
+        ambient: Vec3::splat(0.1),
+        diffuse: Vec3::splat(0.8),
+        specular: Vec3::splat(0.5)
+    };
+    let dark_light = PointLight::dark();
+
+    let mut title_time: f32 = 0.0;
+
+    unsafe {
+        gl::Enable(gl::DEPTH_TEST);
+    }
+
+    // MAIN LOOP
+    while !window.should_close() {
+        // Input
+        let current_frame: f32 = glfw.get_time() as f32;
+        delta_time = current_frame - last_frame;
+        last_frame = current_frame;
+
+        for (_, event) in glfw::flush_messages(&events) {
+            match event {
+                glfw::WindowEvent::FramebufferSize(new_width, new_height) => {
+                    width = new_width;
+                    height = new_height;
+                }
+                glfw::WindowEvent::Key(Key::C, _, Action::Press, _) => {
+                    show_levels = !show_levels;
+                }
+                glfw::WindowEvent::Key(Key::H, _, Action::Press, _) => {
+                    let selector = object.selector_mut();
+                    selector.hysteresis = if selector.hysteresis > 0.0 { 0.0 } else { LodSelector::DEFAULT_HYSTERESIS };
+                    println!("hysteresis {}", selector.hysteresis);
+                }
+                _ => {}
+            }
+            handle_window_event(
+                &mut window,
+                event,
+                &mut first_mouse,
+                &mut last_x,
+                &mut last_y,
+                &mut camera,
+                &mut flashlight,
+                &mut wireframe_mode
+            );
+        }
+        // a minimised window has nothing to draw into, wait until it is restored
+        if width <= 0 || height <= 0 {
+            glfw.wait_events();
+            continue;
+        }
+        process_input(&window, &mut camera, delta_time);
+
+        let flashlight_color = if flashlight.is_on() { Vec3::ONE } else { Vec3::ZERO };
+        let spot_light = SpotLight::flashlight(camera.position, camera.front(), flashlight_color);
+
+        // Render
+        let view = camera.get_view_matrix();
+        let aspect_ratio = width as f32 / height as f32;
+        let projection = camera.get_projection_matrix(aspect_ratio, 0.1, 200.0);
+        unsafe {
+            gl::ClearColor(0.05, 0.05, 0.08, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        let mut queue = RenderQueue::with_frustum(camera.frustum(aspect_ratio, 0.1, 200.0));
+        let mut per_level = vec![0; object.selector().levels()];
+        let mut triangles = 0;
+        for (model, level) in placements.iter().zip(levels.iter_mut()) {
+            let size = lod::screen_size(&bounds.transformed(model), &camera);
+            *level = object.selector().select(*level, size);
+            let material = if show_levels { &tinted[(*level).min(tinted.len() - 1)] } else { &plain };
+            let before = queue.len();
+            match &object {
+                Object::Sphere(sphere) => queue.push(DrawItem::new(sphere.mesh(*level), material, &lit, *model)),
+                Object::Model(lod_model) => lod_model.queue(&mut queue, *level, material, &lit, model)
+            }
+            // only count what made it past culling
+            if queue.len() > before {
+                per_level[*level] += 1;
+                triangles += object.triangles(*level);
+            }
+        }
+        queue.render(camera.position, current_frame, |program| {
+            program.set_mat4(c"view", &view);
+            program.set_mat4(c"projection", &projection);
+            directional_light.set_uniforms(program, "dirLight", &view);
+            spot_light.set_uniforms(program, "spotLight", &view);
+            for i in 0..4 {
+                dark_light.set_uniforms(program, &format!("pointLights[{i}]"), &view);
+            }
+        });
+
+        if current_frame - title_time >= 1.0 {
+            let counts: Vec<String> = per_level.iter().enumerate()
+                .map(|(level, count)| format!("L{level}: {count}"))
+                .collect();
+            window.set_title(&format!("Levels of detail - {triangles} triangles, {}", counts.join(", ")));
+            title_time = current_frame;
+        }
+
+        // Check call events and swap the buffers
+        glfw.poll_events();
+        window.swap_buffers();
+    }
+}
@@ -0,0 +1,201 @@
+use std::path::Path;
+use glam::{Mat4, Vec2, Vec3};
+use glfw::Context;
+use opengl_book_examples::camera::camera::Camera;
+use opengl_book_examples::common::common::{handle_window_event, process_input};
+use opengl_book_examples::flashlight::flashlight::FlashLight;
+use opengl_book_examples::lights::lights::{DirectionalLight, PointLight, SpotLight};
+use opengl_book_examples::material::material::{BlendMode, Emission, Material, SpecularMap};
+use opengl_book_examples::shaders::shaders::ShaderProgram;
+use opengl_book_examples::shapes::shapes;
+use opengl_book_examples::textures::textures::Texture;
+
+/// A row of cubes showing what the material system can do, from left to right: a specular
+/// intensity map with a pulsing emission map, a specular colour map, a scrolling emission map on
+/// an otherwise plain material and an opacity map cutting holes with alpha testing.
+fn main() {
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+    glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
+    glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+    #[cfg(target_os = "macos")]
+    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+    let (mut window, events) =
+        glfw.create_window(800, 600, "Materials", glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window.");
+
+    window.make_current();
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+
+    // set the cursor at the middle of the screen
+    window.set_cursor_mode(glfw::CursorMode::Disabled);
+
+    // GLAD OpenGL function pointers
+    gl::load_with(|symbol| match window.get_proc_address(symbol) {
+        Some(f) => f as *const _,
+        None => std::ptr::null(),
+    });
+
+    // Shader setup
+    let program = match ShaderProgram::from_files("src/shaders/vertex/lights.vert", "src/shaders/fragment/material.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let lamp = match ShaderProgram::from_files("src/shaders/vertex/light_source.vert", "src/shaders/fragment/light_source.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    // Texture setup
+    let load = |path: &str, is_png: bool| match Texture::load_texture(Path::new(path), is_png) {
+        Ok(id) => id,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let container = load("src/textures/wooden-container-with-metal-frame.png", true);
+    let steel_frame = load("src/textures/steel-frame.png", true);
+    let colored_steel_frame = load("src/textures/colored-steel-frame.png", true);
+    let emission = load("src/textures/emission2.png", true);
+    let matrix = load("src/textures/matrix.jpg", false);
+    let awesome_face = load("src/textures/awesomeface.png", true);
+
+    let materials: [Material; 4] = [
+        Material {
+            emission: Some(Emission {
+                pulse_frequency: 0.5,
+                ..Emission::new(emission)
+            }),
+            ..Material::new(container, steel_frame)
+        },
+        Material {
+            specular: Some(SpecularMap::Color(colored_steel_frame)),
+            ..Material::new(container, steel_frame)
+        },
+        Material {
+            diffuse_color: Vec3::splat(0.1),
+            emission: Some(Emission {
+                scroll: Vec2::new(0.0, 0.5),
+                ..Emission::new(matrix)
+            }),
+            ..Material::default()
+        },
+        Material {
+            diffuse: Some(awesome_face),
+            opacity: Some(awesome_face),
+            blend: BlendMode::Cutout,
+            ..Material::default()
+        }
+    ];
+
+    let cube = shapes::cube();
+    unsafe { gl::Enable(gl::DEPTH_TEST); }
+
+    let mut delta_time: f32; // Time between the current frame and last frame
+    let mut last_frame: f32 = 0.0; // Time of last frame
+    let mut last_x: f32 = 400.0;
+    let mut last_y: f32 = 300.0;
+    let mut first_mouse: bool = true;
+    let (mut width, mut height) = (800, 600);
+
+    // Camera setup
+    let mut camera: Camera = Camera::new(
+        Vec3::new(0.0, 0.5, 5.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        true
+    );
+    let mut flashlight: FlashLight = FlashLight::new(false);
+    let mut wireframe_mode: bool = false;
+
+    let directional_light = DirectionalLight {
+        direction: Vec3::new(-0.2, -1.0, -0.3),
+        ambient: Vec3::splat(0.2),
+        diffuse: Vec3::splat(0.5),
+        specular: Vec3::ONE
+    };
+    let point_lights: [PointLight; 4] = [
+        PointLight::new(Vec3::new(-3.0, 1.0, 1.5), Vec3::splat(0.05), Vec3::new(1.0, 0.65, 0.0), Vec3::ONE),
+        PointLight::new(Vec3::new(-1.0, 1.0, 1.5), Vec3::splat(0.05), Vec3::ONE, Vec3::ONE),
+        PointLight::new(Vec3::new(1.0, 1.0, 1.5), Vec3::splat(0.05), Vec3::ONE, Vec3::ONE),
+        PointLight::new(Vec3::new(3.0, 1.0, 1.5), Vec3::splat(0.05), Vec3::new(0.0, 0.5, 1.0), Vec3::ONE)
+    ];
+
+    // MAIN LOOP
+    while !window.should_close() {
+        // Input
+        let current_frame: f32 = glfw.get_time() as f32;
+        delta_time = current_frame - last_frame;
+        last_frame = current_frame;
+
+        for (_, event) in glfw::flush_messages(&events) {
+            if let glfw::WindowEvent::FramebufferSize(new_width, new_height) = event {
+                width = new_width;
+                height = new_height;
+            }
+            handle_window_event(
+                &mut window,
+                event,
+                &mut first_mouse,
+                &mut last_x,
+                &mut last_y,
+                &mut camera,
+                &mut flashlight,
+                &mut wireframe_mode
+            );
+        }
+        // a minimised window has nothing to draw into, wait until it is restored
+        if width <= 0 || height <= 0 {
+            glfw.wait_events();
+            continue;
+        }
+        process_input(&window, &mut camera, delta_time);
+
+        let view_matrix = camera.get_view_matrix();
+        let projection_matrix: Mat4 = Mat4::perspective_rh_gl(camera.zoom.to_radians(), width as f32 / height as f32, 0.1, 100.0);
+        let light = flashlight.get_light();
+        let spot_light = SpotLight::flashlight(camera.position, camera.front(), Vec3::new(light[0], light[1], light[2]));
+
+        // Rendering
+        unsafe {
+            gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        program.use_program();
+        program.set_mat4(c"view", &view_matrix);
+        program.set_mat4(c"projection", &projection_matrix);
+        directional_light.set_uniforms(&program, "dirLight", &view_matrix);
+        spot_light.set_uniforms(&program, "spotLight", &view_matrix);
+        for (i, point_light) in point_lights.iter().enumerate() {
+            point_light.set_uniforms(&program, &format!("pointLights[{i}]"), &view_matrix);
+        }
+        for (i, material) in materials.iter().enumerate() {
+            let angle = 0.3 * current_frame + i as f32;
+            let model_matrix = Mat4::from_translation(Vec3::new(i as f32 * 2.0 - 3.0, 0.0, 0.0)) * Mat4::from_rotation_y(angle);
+            program.set_mat4(c"model", &model_matrix);
+            material.bind(&program, current_frame);
+            cube.draw(&program);
+        }
+
+        lamp.use_program();
+        lamp.set_mat4(c"view", &view_matrix);
+        lamp.set_mat4(c"projection", &projection_matrix);
+        for point_light in &point_lights {
+            lamp.set_vec3(c"lightColorSource", &point_light.diffuse.to_array());
+            let model_matrix = Mat4::from_translation(point_light.position) * Mat4::from_scale(Vec3::splat(0.1));
+            lamp.set_mat4(c"model", &model_matrix);
+            cube.draw(&lamp);
+        }
+
+        // Check call events and swap the buffers
+        glfw.poll_events();
+        window.swap_buffers();
+    }
+}
@@ -2,15 +2,43 @@ use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::path::Path;
 use std::ptr;
-use glam::{Mat4, Vec3, Vec4};
-use glfw::{Context};
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use glfw::{Action, Context, Key};
 use opengl_book_examples::camera::camera::Camera;
+use opengl_book_examples::capture::capture::{self, Recorder, RecordingOutput};
 use opengl_book_examples::common::common::{handle_window_event, process_input};
+use opengl_book_examples::debug_draw::debug_draw::{DebugDraw, DebugPass};
 use opengl_book_examples::flashlight::flashlight::FlashLight;
+use opengl_book_examples::frame_stats::frame_stats::FrameStats;
+use opengl_book_examples::framebuffer::framebuffer::ColorFormat;
+use opengl_book_examples::hdr::hdr::{BloomBlur, HdrRenderer};
+use opengl_book_examples::lights::lights::{DirectionalLight, PointLight, SpotLight};
+use opengl_book_examples::picking::picking::IdBuffer;
+use opengl_book_examples::postprocessing::postprocessing::{Effect, Kernel, PostProcessingStack};
 use opengl_book_examples::shaders::shaders::{Shader, ShaderProgram, ShaderType};
+use opengl_book_examples::shapes::shapes;
+use opengl_book_examples::text::text::{TextRenderer, DEFAULT_FONT};
 use opengl_book_examples::textures::textures::Texture;
 
+/// F12 saves a screenshot in `screenshots/`, F11 starts and stops recording. Recordings are
+/// numbered PNGs in `recordings/<timestamp>/`, or are piped to the command given with
+/// `--encoder "<command>"`, see `RecordingOutput::Command`.
+///
+/// Clicking picks the cube under the crosshair in the middle of the screen, or under the cursor
+/// once M frees it, and prints which one it is. I switches between casting a ray against the
+/// triangles of the cubes and reading the cube id back from a GPU id buffer.
+///
+/// V cycles the debug view between off, lines hidden by the cubes and lines on top: a grid, the
+/// world axes, the box of every cube with the picked one in white, the reach of the lamps and the
+/// direction of the sun. N adds the normals of the cubes, C leaves the frustum and flashlight cone
+/// of the camera where they are to look at them from elsewhere.
+///
+/// The top left corner shows the camera position, the state of every toggle and the frame, CPU
+/// and GPU times of each pass over the last frames, H hides it. O prints the times every two
+/// seconds as well.
 fn main() {
+    let encoder = std::env::args().skip_while(|arg| arg != "--encoder").nth(1);
+
     let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
     glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
     glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
@@ -26,6 +54,7 @@ fn main() {
     window.set_key_polling(true);
     window.set_cursor_pos_polling(true);
     window.set_scroll_polling(true);
+    window.set_mouse_button_polling(true);
 
     // set the cursor at the middle of the screen
     window.set_cursor_mode(glfw::CursorMode::Disabled);
@@ -83,6 +112,13 @@ fn main() {
             panic!("{}", e.to_string())
         }
     };
+    // same diffuse map decoded from sRGB, for linear space lighting
+    let texture1_srgb = match Texture::load_srgb_texture(Path::new("src/textures/wooden-container-with-metal-frame.png"), true) {
+        Ok(id) => id,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
     let texture2 = match Texture::load_texture(Path::new("src/textures/steel-frame.png"), true) {
         Ok(id) => id,
         Err(e) => {
@@ -204,6 +240,63 @@ fn main() {
     };
     unsafe { gl::Enable(gl::DEPTH_TEST); }
 
+    // HDR setup, the scene is lit into a floating point target and tone mapped afterwards
+    let mut hdr = match HdrRenderer::new(800, 600, 4) {
+        Ok(renderer) => renderer,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    // lamps are drawn brighter than 1.0 so they bloom
+    let lamp_intensity: f32 = 4.0;
+
+    // Post-processing setup, the number keys toggle the effects in the order they are pressed
+    let mut post_processing = match PostProcessingStack::new(800, 600, 1, ColorFormat::RGBA8) {
+        Ok(stack) => stack,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    // Picking setup, the cubes as a mesh only serve to test rays against and to draw the ids
+    let cube_mesh = shapes::cube();
+    let (mut width, mut height) = window.get_framebuffer_size();
+    let mut id_buffer = match IdBuffer::new(width, height) {
+        Ok(id_buffer) => id_buffer,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let mut gpu_picking: bool = false;
+    let mut cursor_free: bool = false;
+    // where the last click was, in framebuffer pixels, until it is handled
+    let mut click: Option<Vec2> = None;
+    let mut picked: Option<usize> = None;
+
+    // Debug drawing setup
+    let debug_pass = match DebugPass::new() {
+        Ok(pass) => pass,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let mut debug = DebugDraw::new();
+    let mut show_debug: bool = false;
+    let mut show_normals: bool = false;
+    // view projection and flashlight of the camera when C was pressed
+    let mut frozen_camera: Option<(Mat4, SpotLight)> = None;
+
+    // HUD setup
+    let mut text = match TextRenderer::new(DEFAULT_FONT, 16.0) {
+        Ok(renderer) => renderer,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let mut show_hud: bool = true;
+    let mut frame_stats = FrameStats::default();
+    let mut log_stats: bool = false;
+
     let mut delta_time: f32 = 0.0; // Time between the current frame and last frame
     let mut last_frame: f32 = 0.0; // Time of last frame
     let mut last_x: f32 = 400.0;
@@ -219,6 +312,11 @@ fn main() {
     let mut flashlight: FlashLight = FlashLight::new(true);
 
     let mut wireframe_mode: Box<bool> = Box::new(false);
+    // P switches between Phong and Blinn-Phong, L between gamma space and linear space lighting
+    let mut blinn: bool = true;
+    let mut linear_lighting: bool = true;
+    let mut take_screenshot: bool = false;
+    let mut recorder: Option<Recorder> = None;
     cube.use_program();
     cube.set_int(c"material.diffuse", 0);
     cube.set_int(c"material.specular", 1);
@@ -253,15 +351,197 @@ fn main() {
     point_light_properties.insert("linear", vec![vec![0.09]; 4]);
     point_light_properties.insert("quadratic", vec![vec![0.032]; 4]);
 
+    let cube_model = |i: usize| {
+        let angle = 20.0 * i as f32;
+        Mat4::from_translation(cube_positions[i]) * Mat4::from_axis_angle(Vec3::new(1.0, 0.3, 0.5).normalize(), angle)
+    };
+
     // MAIN LOOP
     while !window.should_close() {
         // println!("Camera position: {}", camera.position);
         // Input
-        let current_frame: f32 = glfw.get_time() as f32;
-        delta_time = current_frame - last_frame;
+        let mut current_frame: f32 = glfw.get_time() as f32;
+        if recorder.is_some() {
+            // fixed steps while recording so the footage plays back at the real speed
+            current_frame = last_frame + 1.0 / capture::FRAME_RATE as f32;
+            glfw.set_time(current_frame as f64);
+        }
+        delta_time = frame_stats.begin_frame(current_frame);
         last_frame = current_frame;
 
         for (_, event) in glfw::flush_messages(&events) {
+            let effect = match event {
+                glfw::WindowEvent::FramebufferSize(new_width, new_height) => {
+                    width = new_width;
+                    height = new_height;
+                    if let Err(e) = hdr.resize(width, height) {
+                        panic!("{}", e.to_string())
+                    }
+                    if let Err(e) = post_processing.resize(width, height) {
+                        panic!("{}", e.to_string())
+                    }
+                    if let Err(e) = id_buffer.resize(width, height) {
+                        panic!("{}", e.to_string())
+                    }
+                    None
+                }
+                glfw::WindowEvent::MouseButton(glfw::MouseButtonLeft, Action::Press, _) => {
+                    click = Some(if cursor_free {
+                        // the cursor is in window coordinates, which differ from pixels on high
+                        // density displays
+                        let (x, y) = window.get_cursor_pos();
+                        let (window_width, window_height) = window.get_size();
+                        Vec2::new(x as f32 * width as f32 / window_width as f32, y as f32 * height as f32 / window_height as f32)
+                    } else {
+                        Vec2::new(width as f32 * 0.5, height as f32 * 0.5)
+                    });
+                    None
+                }
+                glfw::WindowEvent::Key(Key::M, _, Action::Press, _) => {
+                    cursor_free = !cursor_free;
+                    window.set_cursor_mode(if cursor_free { glfw::CursorMode::Normal } else { glfw::CursorMode::Disabled });
+                    // the camera would jump by however far the cursor moved meanwhile
+                    first_mouse = true;
+                    None
+                }
+                glfw::WindowEvent::Key(Key::H, _, Action::Press, _) => {
+                    show_hud = !show_hud;
+                    None
+                }
+                glfw::WindowEvent::Key(Key::O, _, Action::Press, _) => {
+                    log_stats = !log_stats;
+                    None
+                }
+                glfw::WindowEvent::Key(Key::V, _, Action::Press, _) => {
+                    if !show_debug {
+                        show_debug = true;
+                        debug.depth_test = true;
+                    } else if debug.depth_test {
+                        debug.depth_test = false;
+                    } else {
+                        show_debug = false;
+                    }
+                    None
+                }
+                glfw::WindowEvent::Key(Key::N, _, Action::Press, _) => {
+                    show_normals = !show_normals;
+                    None
+                }
+                glfw::WindowEvent::Key(Key::C, _, Action::Press, _) => {
+                    frozen_camera = match frozen_camera {
+                        Some(_) => None,
+                        None => {
+                            let aspect_ratio = width as f32 / height as f32;
+                            // a shorter far plane than the real one keeps the frustum in sight
+                            let view_projection = camera.get_projection_matrix(aspect_ratio, 0.1, 10.0) * camera.get_view_matrix();
+                            Some((view_projection, SpotLight::flashlight(camera.position, camera.front(), Vec3::new(1.0, 1.0, 0.6))))
+                        }
+                    };
+                    None
+                }
+                glfw::WindowEvent::Key(Key::I, _, Action::Press, _) => {
+                    gpu_picking = !gpu_picking;
+                    println!("Picking: {}", if gpu_picking { "GPU id buffer" } else { "ray casting" });
+                    None
+                }
+                glfw::WindowEvent::Key(Key::T, _, Action::Press, _) => {
+                    hdr.tone_mapping = hdr.tone_mapping.next();
+                    println!("Tone mapping: {:?}", hdr.tone_mapping);
+                    None
+                }
+                glfw::WindowEvent::Key(Key::B, _, Action::Press, _) => {
+                    hdr.bloom.enabled = !hdr.bloom.enabled;
+                    println!("Bloom: {}", hdr.bloom.enabled);
+                    None
+                }
+                glfw::WindowEvent::Key(Key::G, _, Action::Press, _) => {
+                    hdr.bloom.blur = match hdr.bloom.blur {
+                        BloomBlur::Gaussian => BloomBlur::DualKawase,
+                        BloomBlur::DualKawase => BloomBlur::Gaussian
+                    };
+                    println!("Bloom blur: {:?}", hdr.bloom.blur);
+                    None
+                }
+                glfw::WindowEvent::Key(Key::X, _, Action::Press, _) => {
+                    hdr.auto_exposure.enabled = !hdr.auto_exposure.enabled;
+                    println!("Auto exposure: {}", hdr.auto_exposure.enabled);
+                    None
+                }
+                glfw::WindowEvent::Key(Key::P, _, Action::Press, _) => {
+                    blinn = !blinn;
+                    println!("Specular: {}", if blinn { "Blinn-Phong" } else { "Phong" });
+                    None
+                }
+                glfw::WindowEvent::Key(Key::L, _, Action::Press, _) => {
+                    linear_lighting = !linear_lighting;
+                    // the tone mapping pass encodes the output, so it only applies the gamma
+                    // when the textures were decoded to linear space
+                    hdr.gamma = if linear_lighting { 2.2 } else { 1.0 };
+                    println!("Linear space lighting: {}", linear_lighting);
+                    None
+                }
+                glfw::WindowEvent::Key(Key::Up, _, Action::Press | Action::Repeat, _) => {
+                    hdr.exposure *= 1.25;
+                    println!("Exposure: {}", hdr.exposure);
+                    None
+                }
+                glfw::WindowEvent::Key(Key::Down, _, Action::Press | Action::Repeat, _) => {
+                    hdr.exposure /= 1.25;
+                    println!("Exposure: {}", hdr.exposure);
+                    None
+                }
+                glfw::WindowEvent::Key(Key::Num1, _, Action::Press, _) => Some(Effect::Grayscale),
+                glfw::WindowEvent::Key(Key::Num2, _, Action::Press, _) => Some(Effect::Inversion),
+                glfw::WindowEvent::Key(Key::Num3, _, Action::Press, _) => Some(Effect::Kernel(Kernel::Sharpen)),
+                glfw::WindowEvent::Key(Key::Num4, _, Action::Press, _) => Some(Effect::Kernel(Kernel::Blur)),
+                glfw::WindowEvent::Key(Key::Num5, _, Action::Press, _) => Some(Effect::Kernel(Kernel::EdgeDetection)),
+                glfw::WindowEvent::Key(Key::Num6, _, Action::Press, _) => Some(Effect::vignette()),
+                glfw::WindowEvent::Key(Key::Num7, _, Action::Press, _) => Some(Effect::chromatic_aberration()),
+                glfw::WindowEvent::Key(Key::Num8, _, Action::Press, _) => Some(Effect::film_grain()),
+                glfw::WindowEvent::Key(Key::Num9, _, Action::Press, _) => Some(Effect::fxaa()),
+                glfw::WindowEvent::Key(Key::F12, _, Action::Press, _) => {
+                    take_screenshot = true;
+                    None
+                }
+                glfw::WindowEvent::Key(Key::F11, _, Action::Press, _) => {
+                    match recorder.take() {
+                        Some(recording) => match recording.finish() {
+                            Ok(frames) => println!("Recording stopped, {frames} frames"),
+                            Err(e) => eprintln!("{e}")
+                        },
+                        None => {
+                            let output = match &encoder {
+                                Some(command) => RecordingOutput::Command(command.clone()),
+                                None => RecordingOutput::Png(format!("recordings/{}", capture::timestamp()).into())
+                            };
+                            let (width, height) = window.get_framebuffer_size();
+                            match Recorder::start(output.clone(), width, height) {
+                                Ok(recording) => {
+                                    println!("Recording to {:?}", output);
+                                    recorder = Some(recording);
+                                }
+                                Err(e) => eprintln!("{e}")
+                            }
+                        }
+                    }
+                    None
+                }
+                glfw::WindowEvent::Key(Key::Num0, _, Action::Press, _) => {
+                    post_processing.clear();
+                    println!("Post-processing: none");
+                    None
+                }
+                _ => None
+            };
+            if let Some(effect) = effect {
+                post_processing.toggle(effect);
+                let stack: Vec<&str> = post_processing.passes.iter().map(|pass| pass.effect.shader_name()).collect();
+                println!("Post-processing: {:?}", stack);
+            }
+            // a free cursor is for clicking, not for looking around
+            if cursor_free && matches!(event, glfw::WindowEvent::CursorPos(..)) {
+                continue;
+            }
             handle_window_event(
                 &mut window,
                 event,
@@ -273,23 +553,60 @@ fn main() {
                 &mut wireframe_mode
             );
         }
+        // a minimised window has nothing to draw into, wait until it is restored
+        if width <= 0 || height <= 0 {
+            glfw.wait_events();
+            continue;
+        }
         process_input(&window, &mut camera, delta_time);
 
         let view_matrix = camera.get_view_matrix();
-        let projection_matrix: Mat4 = Mat4::perspective_rh_gl(camera.zoom.to_radians(), 800.0 / 600.0, 0.1, 100.0);
+        let projection_matrix: Mat4 = camera.get_projection_matrix(width as f32 / height as f32, 0.1, 100.0);
+
+        if let Some(point) = click.take() {
+            picked = if gpu_picking {
+                frame_stats.begin_pass("picking");
+                id_buffer.render(&view_matrix, &projection_matrix, (0..cube_positions.len()).map(|i| (i as u32 + 1, &cube_mesh, cube_model(i))));
+                match id_buffer.id_at(point.x as i32, point.y as i32) {
+                    Ok(id) => id.map(|id| id as usize - 1),
+                    Err(e) => {
+                        eprintln!("{e}");
+                        None
+                    }
+                }
+            } else {
+                let ray = camera.screen_point_to_ray(point.x, point.y, Vec4::new(0.0, 0.0, width as f32, height as f32), &projection_matrix);
+                (0..cube_positions.len())
+                    .filter_map(|i| ray.intersect_mesh(&cube_mesh, &cube_model(i)).map(|distance| (i, distance)))
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(i, distance)| {
+                        println!("Ray hit at {:.2} units", distance);
+                        i
+                    })
+            };
+            match picked {
+                Some(i) => println!("Picked cube {i} at {}", cube_positions[i]),
+                None => println!("Picked nothing")
+            }
+        }
 
         // Rendering
+        frame_stats.begin_pass("scene");
+        hdr.begin_scene();
         unsafe {
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
             gl::ClearColor(0.1, 0.1, 0.1, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT);
 
             gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, texture1);
+            gl::BindTexture(gl::TEXTURE_2D, if linear_lighting { texture1_srgb } else { texture1 });
             gl::ActiveTexture(gl::TEXTURE1);
             gl::BindTexture(gl::TEXTURE_2D, texture2);
 
             cube.use_program();
+            cube.set_bool(c"blinn", blinn);
+            // the HDR renderer gamma corrects after tone mapping
+            cube.set_bool(c"gammaCorrection", false);
             // uniform material setup
             cube.set_float(c"material.shininess", 32.0);
 
@@ -348,9 +665,8 @@ fn main() {
             gl::UniformMatrix4fv(model_location, 1, gl::FALSE, &projection_matrix.to_cols_array()[0]);
 
             gl::BindVertexArray(cube_vao);
-            for (i, v) in cube_positions.iter().enumerate() {
-                let angle = 20.0 * i as f32;
-                let model_matrix = Mat4::IDENTITY * Mat4::from_translation(*v) * Mat4::from_axis_angle(Vec3::new(1.0, 0.3, 0.5).normalize(), angle);
+            for i in 0..cube_positions.len() {
+                let model_matrix = cube_model(i);
 
                 let model_cstr: &CStr = c"model";
                 let model_location = gl::GetUniformLocation(cube.shader_program_id, model_cstr.as_ptr());
@@ -371,7 +687,8 @@ fn main() {
             gl::BindVertexArray(light_vao);
             for (i, v) in light_positions.iter().enumerate() {
                 let light = point_light_properties.get("diffuse").unwrap();
-                lamp.set_vec3(c"lightColorSource", &light[i]);
+                let light: Vec<f32> = light[i].iter().map(|channel| channel * lamp_intensity).collect();
+                lamp.set_vec3(c"lightColorSource", &light);
                 let model_matrix: Mat4 = Mat4::IDENTITY * Mat4::from_translation(*v) * Mat4::from_scale(Vec3::new(0.2, 0.2, 0.2));
 
                 let model_cstr: &CStr = c"model";
@@ -383,8 +700,95 @@ fn main() {
             gl::BindVertexArray(0);
         }
 
+        if show_debug || show_normals || frozen_camera.is_some() {
+            if show_debug {
+                debug.grid(10, 1.0, Vec3::splat(0.3));
+                debug.axes(&Mat4::IDENTITY, 1.0);
+                for i in 0..cube_positions.len() {
+                    let color = if picked == Some(i) { Vec3::ONE } else { Vec3::new(1.0, 0.8, 0.2) };
+                    debug.aabb(cube_mesh.aabb(), &cube_model(i), color);
+                }
+                let sun = DirectionalLight {
+                    direction: Vec3::new(-0.2, -1.0, -0.3),
+                    ambient: Vec3::splat(0.2),
+                    diffuse: Vec3::splat(0.5),
+                    specular: Vec3::ONE
+                };
+                debug.directional_light(&sun, Vec3::new(0.0, 3.0, 0.0), 1.5);
+                let diffuse = point_light_properties.get("diffuse").unwrap();
+                for (i, position) in light_positions.iter().enumerate() {
+                    let color = Vec3::from_slice(&diffuse[i]);
+                    debug.point_light(&PointLight::new(*position, Vec3::splat(0.1), color, Vec3::ONE));
+                }
+            }
+            if show_normals {
+                for i in 0..cube_positions.len() {
+                    debug.normals(&cube_mesh.vertices, &cube_model(i), 0.25, Vec3::new(0.3, 1.0, 1.0));
+                }
+            }
+            if let Some((view_projection, flashlight)) = &frozen_camera {
+                debug.frustum(view_projection, Vec3::new(1.0, 0.3, 1.0));
+                debug.spot_light(flashlight, 3.0);
+            }
+            // still in the scene target, whose depth buffer holds the cubes
+            frame_stats.begin_pass("debug lines");
+            debug_pass.flush(&mut debug, &view_matrix, &projection_matrix);
+        }
+        frame_stats.begin_pass("hdr");
+        hdr.render(delta_time, Some(post_processing.scene_target()));
+        frame_stats.begin_pass("post-processing");
+        post_processing.render(current_frame);
+
+        if show_hud {
+            frame_stats.begin_pass("hud");
+            let on_off = |on: bool| if on { "on" } else { "off" };
+            let effects: Vec<&str> = post_processing.passes.iter().map(|pass| pass.effect.shader_name()).collect();
+            let mut hud = vec![
+                format!("Camera {:.2}, {:.2}, {:.2}", camera.position.x, camera.position.y, camera.position.z),
+                format!("{}, {} space lighting, flashlight {}", if blinn { "Blinn-Phong" } else { "Phong" }, if linear_lighting { "linear" } else { "gamma" }, on_off(flashlight.is_on())),
+                format!("Tone mapping {:?}, bloom {} ({:?}), auto exposure {}", hdr.tone_mapping, on_off(hdr.bloom.enabled), hdr.bloom.blur, on_off(hdr.auto_exposure.enabled)),
+                format!("Post-processing: {}", if effects.is_empty() { "none".to_string() } else { effects.join(", ") }),
+                format!("Picking: {}, picked {}", if gpu_picking { "GPU id buffer" } else { "ray casting" }, picked.map_or("nothing".to_string(), |i| format!("cube {i}"))),
+                format!(
+                    "Debug view {}, normals {}, frozen camera {}",
+                    if !show_debug { "off" } else if debug.depth_test { "depth tested" } else { "on top" },
+                    on_off(show_normals),
+                    on_off(frozen_camera.is_some())
+                )
+            ];
+            if recorder.is_some() {
+                hud.push("Recording".to_string());
+            }
+            hud.extend(frame_stats.lines());
+            text.queue_with_shadow(&hud.join("\n"), Vec2::new(10.0, 8.0), Vec3::ONE);
+            text.flush(width, height);
+        }
+
+        frame_stats.end_frame();
+        if log_stats && let Some(report) = frame_stats.report(2.0) {
+            println!("{report}");
+        }
+
+        if take_screenshot {
+            take_screenshot = false;
+            let (width, height) = window.get_framebuffer_size();
+            match capture::screenshot(width, height, "screenshots") {
+                Ok(path) => println!("Screenshot saved to {}", path.display()),
+                Err(e) => eprintln!("{e}")
+            }
+        }
+        if let Some(recording) = &mut recorder
+            && let Err(e) = recording.capture() {
+            eprintln!("{e}");
+            recorder = None;
+        }
+
         // Check call events and swap the buffers
         glfw.poll_events();
         window.swap_buffers();
     }
+    if let Some(recording) = recorder
+        && let Err(e) = recording.finish() {
+        eprintln!("{e}");
+    }
 }
@@ -0,0 +1,239 @@
+use std::path::Path;
+use glam::{Mat4, Vec3};
+use glfw::{Action, Context, Key};
+use opengl_book_examples::camera::camera::Camera;
+use opengl_book_examples::common::common::{handle_window_event, process_input};
+use opengl_book_examples::flashlight::flashlight::FlashLight;
+use opengl_book_examples::hdr::hdr::HdrRenderer;
+use opengl_book_examples::ibl::ibl::Environment;
+use opengl_book_examples::lights::lights::{DirectionalLight, PointLight, SpotLight};
+use opengl_book_examples::pbr::pbr::{self, PbrMaterial};
+use opengl_book_examples::shaders::shaders::ShaderProgram;
+use opengl_book_examples::shapes::shapes;
+
+const ROWS: usize = 7;
+const COLUMNS: usize = 7;
+const SPACING: f32 = 2.5;
+
+/// Grid of spheres lit with the Cook-Torrance shader: the metallic value goes up from the bottom
+/// row to the top one and the roughness from the left column to the right one. The lit scene is
+/// tone mapped by the HDR renderer, T cycles the tone mapping operator and Up/Down the exposure.
+/// Passing the path of an equirectangular `.hdr` image lights the spheres with it and draws it as
+/// the sky, I toggles image based lighting. The precomputed maps are cached in `target/ibl-cache`.
+fn main() {
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+    glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
+    glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+    #[cfg(target_os = "macos")]
+    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+    let (mut window, events) =
+        glfw.create_window(800, 600, "PBR", glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window.");
+
+    window.make_current();
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+
+    // set the cursor at the middle of the screen
+    window.set_cursor_mode(glfw::CursorMode::Disabled);
+
+    // GLAD OpenGL function pointers
+    gl::load_with(|symbol| match window.get_proc_address(symbol) {
+        Some(f) => f as *const _,
+        None => std::ptr::null(),
+    });
+
+    // Shader setup
+    let pbr = match pbr::program() {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let lamp = match ShaderProgram::from_files("src/shaders/vertex/light_source.vert", "src/shaders/fragment/light_source.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let mut hdr = match HdrRenderer::new(800, 600, 4) {
+        Ok(renderer) => renderer,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    let environment = std::env::args().nth(1).map(|hdr_path| {
+        match Environment::from_equirectangular(&hdr_path, Some(Path::new("target/ibl-cache"))) {
+            Ok(environment) => environment,
+            Err(e) => {
+                panic!("{}", e.to_string())
+            }
+        }
+    });
+    let mut use_ibl = environment.is_some();
+
+    let sphere = shapes::uv_sphere(64, 32);
+    let cube = shapes::cube();
+    unsafe { gl::Enable(gl::DEPTH_TEST); }
+
+    let mut delta_time: f32; // Time between the current frame and last frame
+    let mut last_frame: f32 = 0.0; // Time of last frame
+    let mut last_x: f32 = 400.0;
+    let mut last_y: f32 = 300.0;
+    let mut first_mouse: bool = true;
+    let (mut width, mut height) = (800, 600);
+
+    // Camera setup
+    let mut camera: Camera = Camera::new(
+        Vec3::new(0.0, 0.0, 20.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        false
+    );
+    let mut flashlight: FlashLight = FlashLight::new(false);
+    let mut wireframe_mode: bool = false;
+
+    let directional_light = DirectionalLight {
+        direction: Vec3::new(-0.2, -1.0, -0.3),
+        ambient: Vec3::splat(0.03),
+        diffuse: Vec3::splat(0.5),
+        specular: Vec3::ZERO
+    };
+    // physically based lights fall off with the inverse square of the distance
+    let point_lights: Vec<PointLight> = [
+        Vec3::new(-10.0, 10.0, 10.0),
+        Vec3::new(10.0, 10.0, 10.0),
+        Vec3::new(-10.0, -10.0, 10.0),
+        Vec3::new(10.0, -10.0, 10.0)
+    ]
+        .into_iter()
+        .map(|position| {
+            let mut light = PointLight::new(position, Vec3::ZERO, Vec3::splat(300.0), Vec3::ZERO);
+            light.constant = 0.0;
+            light.linear = 0.0;
+            light.quadratic = 1.0;
+            light
+        })
+        .collect();
+
+    let mut materials = Vec::with_capacity(ROWS * COLUMNS);
+    for row in 0..ROWS {
+        for column in 0..COLUMNS {
+            let metallic = row as f32 / (ROWS - 1) as f32;
+            let roughness = column as f32 / (COLUMNS - 1) as f32;
+            let model_matrix = Mat4::from_translation(Vec3::new(
+                (column as f32 - (COLUMNS - 1) as f32 / 2.0) * SPACING,
+                (row as f32 - (ROWS - 1) as f32 / 2.0) * SPACING,
+                0.0
+            ));
+            materials.push((model_matrix, PbrMaterial::new(Vec3::new(0.5, 0.0, 0.0), metallic, roughness)));
+        }
+    }
+
+    // MAIN LOOP
+    while !window.should_close() {
+        // Input
+        let current_frame: f32 = glfw.get_time() as f32;
+        delta_time = current_frame - last_frame;
+        last_frame = current_frame;
+
+        for (_, event) in glfw::flush_messages(&events) {
+            match event {
+                glfw::WindowEvent::FramebufferSize(new_width, new_height) => {
+                    width = new_width;
+                    height = new_height;
+                    if let Err(e) = hdr.resize(width, height) {
+                        panic!("{}", e.to_string())
+                    }
+                }
+                glfw::WindowEvent::Key(Key::I, _, Action::Press, _) if environment.is_some() => {
+                    use_ibl = !use_ibl;
+                    println!("Image based lighting: {}", use_ibl);
+                }
+                glfw::WindowEvent::Key(Key::T, _, Action::Press, _) => {
+                    hdr.tone_mapping = hdr.tone_mapping.next();
+                    println!("Tone mapping: {:?}", hdr.tone_mapping);
+                }
+                glfw::WindowEvent::Key(Key::Up, _, Action::Press | Action::Repeat, _) => {
+                    hdr.exposure += 0.1;
+                    println!("Exposure: {:.1}", hdr.exposure);
+                }
+                glfw::WindowEvent::Key(Key::Down, _, Action::Press | Action::Repeat, _) => {
+                    hdr.exposure = (hdr.exposure - 0.1).max(0.1);
+                    println!("Exposure: {:.1}", hdr.exposure);
+                }
+                _ => {}
+            }
+            handle_window_event(
+                &mut window,
+                event,
+                &mut first_mouse,
+                &mut last_x,
+                &mut last_y,
+                &mut camera,
+                &mut flashlight,
+                &mut wireframe_mode
+            );
+        }
+        // a minimised window has nothing to draw into, wait until it is restored
+        if width <= 0 || height <= 0 {
+            glfw.wait_events();
+            continue;
+        }
+        process_input(&window, &mut camera, delta_time);
+
+        let view_matrix = camera.get_view_matrix();
+        let projection_matrix: Mat4 = Mat4::perspective_rh_gl(camera.zoom.to_radians(), width as f32 / height as f32, 0.1, 100.0);
+        let light = flashlight.get_light();
+        let spot_light = SpotLight::flashlight(camera.position, camera.front(), Vec3::new(light[0], light[1], light[2]) * 10.0);
+
+        // Rendering
+        hdr.begin_scene();
+        unsafe {
+            gl::ClearColor(0.05, 0.05, 0.05, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        pbr.use_program();
+        pbr.set_mat4(c"view", &view_matrix);
+        pbr.set_mat4(c"projection", &projection_matrix);
+        directional_light.set_uniforms(&pbr, "dirLight", &view_matrix);
+        spot_light.set_uniforms(&pbr, "spotLight", &view_matrix);
+        match &environment {
+            Some(environment) if use_ibl => environment.set_uniforms(&pbr, &view_matrix),
+            _ => pbr.set_bool(c"useIbl", false)
+        }
+        pbr.set_int(c"pointLightCount", point_lights.len() as u32);
+        for (i, point_light) in point_lights.iter().enumerate() {
+            point_light.set_uniforms(&pbr, &format!("pointLights[{i}]"), &view_matrix);
+        }
+        for (model_matrix, material) in &materials {
+            material.set_uniforms(&pbr);
+            pbr.set_mat4(c"model", model_matrix);
+            sphere.draw(&pbr);
+        }
+
+        lamp.use_program();
+        lamp.set_mat4(c"view", &view_matrix);
+        lamp.set_mat4(c"projection", &projection_matrix);
+        for point_light in &point_lights {
+            lamp.set_vec3(c"lightColorSource", &Vec3::splat(10.0).to_array());
+            let model_matrix = Mat4::from_translation(point_light.position) * Mat4::from_scale(Vec3::splat(0.5));
+            lamp.set_mat4(c"model", &model_matrix);
+            cube.draw(&lamp);
+        }
+
+        if let Some(environment) = environment.as_ref().filter(|_| use_ibl) {
+            environment.draw_skybox(&view_matrix, &projection_matrix);
+        }
+
+        hdr.render(delta_time, None);
+
+        // Check call events and swap the buffers
+        glfw.poll_events();
+        window.swap_buffers();
+    }
+}
@@ -0,0 +1,245 @@
+use std::path::Path;
+use glam::{Quat, Vec3};
+use glfw::Context;
+use opengl_book_examples::camera::camera::Camera;
+use opengl_book_examples::common::common::{handle_window_event, process_input};
+use opengl_book_examples::flashlight::flashlight::FlashLight;
+use opengl_book_examples::lights::lights::{DirectionalLight, PointLight, SpotLight};
+use opengl_book_examples::material::material::Material;
+use opengl_book_examples::model::model::Model;
+use opengl_book_examples::renderer::renderer::{DrawItem, RenderQueue};
+use opengl_book_examples::scene_graph::scene_graph::{SceneGraph, Transform};
+use opengl_book_examples::shaders::shaders::ShaderProgram;
+use opengl_book_examples::shapes::shapes;
+use opengl_book_examples::textures::textures::Texture;
+
+const LAMP_COLOR: Vec3 = Vec3::new(1.0, 0.85, 0.6);
+
+/// What a node of the demo draws with its world matrix.
+enum Part {
+    /// Only moves its children around
+    Pivot,
+    Solid(Material),
+    /// Small cube giving off the light of the lamp
+    Lamp,
+    /// Where the model given on the command line hangs
+    Model
+}
+
+/// A crate riding a turntable, with a two jointed arm on top of it holding a lamp. Every part only
+/// knows its transform relative to the part it's attached to, the scene graph works out where the
+/// lamp ends up and the point light follows it. Passing the path of a model file, in any format
+/// Assimp reads, puts it next to the turntable with the node hierarchy of the file.
+fn main() {
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+    glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
+    glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+    #[cfg(target_os = "macos")]
+    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+    let (mut window, events) =
+        glfw.create_window(800, 600, "Scene graph", glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window.");
+
+    window.make_current();
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+
+    // set the cursor at the middle of the screen
+    window.set_cursor_mode(glfw::CursorMode::Disabled);
+
+    // GLAD OpenGL function pointers
+    gl::load_with(|symbol| match window.get_proc_address(symbol) {
+        Some(f) => f as *const _,
+        None => std::ptr::null(),
+    });
+
+    // Shader setup
+    let lit = match ShaderProgram::from_files("src/shaders/vertex/lights.vert", "src/shaders/fragment/material.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let lamp = match ShaderProgram::from_files("src/shaders/vertex/light_source.vert", "src/shaders/fragment/light_source.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    // Texture setup
+    let crate_diffuse = match Texture::load_texture(Path::new("src/textures/wooden-container-with-metal-frame.png"), true) {
+        Ok(id) => id,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let crate_specular = match Texture::load_texture(Path::new("src/textures/steel-frame.png"), true) {
+        Ok(id) => id,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let floor_diffuse = match Texture::load_texture(Path::new("src/textures/gravel-concrete.jpg"), false) {
+        Ok(id) => id,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    let model = std::env::args().nth(1).map(|path| match Model::load(&path) {
+        Ok(model) => model,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    });
+
+    let cube = shapes::cube();
+    let metal = Material {
+        diffuse_color: Vec3::splat(0.3),
+        specular_color: Vec3::splat(0.8),
+        shininess: 64.0,
+        ..Material::default()
+    };
+    let model_material = Material::default();
+
+    // Scene graph setup, segments are leaves scaled on their own so the joints below them keep a
+    // uniform scale
+    let mut graph = SceneGraph::new();
+    let floor = Material {
+        diffuse: Some(floor_diffuse),
+        specular_color: Vec3::splat(0.1),
+        ..Material::default()
+    };
+    graph.add(None, "floor", Transform::from_translation(Vec3::new(0.0, -0.55, 0.0)).with_scale(Vec3::new(12.0, 0.1, 12.0)), Part::Solid(floor));
+    let turntable = graph.add(None, "turntable", Transform::IDENTITY, Part::Pivot);
+    let crate_node = graph.add(Some(turntable), "crate", Transform::from_translation(Vec3::new(2.5, 0.0, 0.0)), Part::Solid(Material::new(crate_diffuse, crate_specular)));
+    let shoulder = graph.add(Some(crate_node), "shoulder", Transform::from_translation(Vec3::new(0.0, 0.5, 0.0)), Part::Pivot);
+    graph.add(Some(shoulder), "upper arm", Transform::from_translation(Vec3::new(0.0, 0.5, 0.0)).with_scale(Vec3::new(0.1, 1.0, 0.1)), Part::Solid(metal));
+    let elbow = graph.add(Some(shoulder), "elbow", Transform::from_translation(Vec3::new(0.0, 1.0, 0.0)), Part::Pivot);
+    graph.add(Some(elbow), "forearm", Transform::from_translation(Vec3::new(0.0, 0.4, 0.0)).with_scale(Vec3::new(0.08, 0.8, 0.08)), Part::Solid(metal));
+    let lamp_node = graph.add(Some(elbow), "lamp", Transform::from_translation(Vec3::new(0.0, 0.8, 0.0)).with_scale(Vec3::splat(0.2)), Part::Lamp);
+    if model.is_some() {
+        graph.add(None, "model", Transform::from_translation(Vec3::new(-2.5, 0.0, 0.0)), Part::Model);
+    }
+
+    let mut delta_time: f32; // Time between the current frame and last frame
+    let mut last_frame: f32 = 0.0; // Time of last frame
+    let mut last_x: f32 = 400.0;
+    let mut last_y: f32 = 300.0;
+    let mut first_mouse: bool = true;
+    let (mut width, mut height) = window.get_framebuffer_size();
+
+    // Camera setup
+    let mut camera: Camera = Camera::new(
+        Vec3::new(0.0, 2.0, 7.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        true
+    );
+    camera.set_orientation(-90.0, -15.0);
+    let mut flashlight: FlashLight = FlashLight::new(false);
+    let mut wireframe_mode: bool = false;
+
+    let directional_light = DirectionalLight {
+        direction: Vec3::new(-0.2, -1.0, -0.3),
+        ambient: Vec3::splat(0.05),
+        diffuse: Vec3::splat(0.2),
+        specular: Vec3::splat(0.2)
+    };
+    let dark_light = PointLight::dark();
+
+    unsafe {
+        gl::Enable(gl::DEPTH_TEST);
+    }
+
+    // MAIN LOOP
+    while !window.should_close() {
+        // Input
+        let current_frame: f32 = glfw.get_time() as f32;
+        delta_time = current_frame - last_frame;
+        last_frame = current_frame;
+
+        for (_, event) in glfw::flush_messages(&events) {
+            if let glfw::WindowEvent::FramebufferSize(new_width, new_height) = event {
+                width = new_width;
+                height = new_height;
+            }
+            handle_window_event(
+                &mut window,
+                event,
+                &mut first_mouse,
+                &mut last_x,
+                &mut last_y,
+                &mut camera,
+                &mut flashlight,
+                &mut wireframe_mode
+            );
+        }
+        // a minimised window has nothing to draw into, wait until it is restored
+        if width <= 0 || height <= 0 {
+            glfw.wait_events();
+            continue;
+        }
+        process_input(&window, &mut camera, delta_time);
+
+        // Animation, only the joints move and everything attached to them follows
+        graph.update_local(turntable, |t| t.rotation = Quat::from_rotation_y((current_frame * 20.0).to_radians()));
+        graph.update_local(crate_node, |t| t.rotation = Quat::from_rotation_y((current_frame * -45.0).to_radians()));
+        graph.update_local(shoulder, |t| t.rotation = Quat::from_rotation_z((current_frame.sin() * 40.0).to_radians()));
+        graph.update_local(elbow, |t| t.rotation = Quat::from_rotation_z((60.0 + (current_frame * 1.3).sin() * 30.0).to_radians()));
+        graph.update();
+
+        let lamp_position = graph.world(lamp_node).transform_point3(Vec3::ZERO);
+        let point_light = PointLight::new(lamp_position, LAMP_COLOR * 0.05, LAMP_COLOR, LAMP_COLOR);
+        let flashlight_color = if flashlight.is_on() { Vec3::ONE } else { Vec3::ZERO };
+        let spot_light = SpotLight::flashlight(camera.position, camera.front(), flashlight_color);
+
+        // Render
+        let view = camera.get_view_matrix();
+        let aspect_ratio = width as f32 / height as f32;
+        let projection = camera.get_projection_matrix(aspect_ratio, 0.1, 100.0);
+        unsafe {
+            gl::ClearColor(0.05, 0.05, 0.08, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        let mut queue = RenderQueue::with_frustum(camera.frustum(aspect_ratio, 0.1, 100.0));
+        graph.traverse(|_, node| match &node.data {
+            Part::Solid(material) => queue.push(DrawItem::new(&cube, material, &lit, *node.world())),
+            Part::Model => {
+                if let Some(model) = &model {
+                    model.queue(&mut queue, 0, &model_material, &lit, node.world());
+                }
+            }
+            Part::Pivot | Part::Lamp => {}
+        });
+        queue.render(camera.position, current_frame, |program| {
+            program.set_mat4(c"view", &view);
+            program.set_mat4(c"projection", &projection);
+            directional_light.set_uniforms(program, "dirLight", &view);
+            spot_light.set_uniforms(program, "spotLight", &view);
+            point_light.set_uniforms(program, "pointLights[0]", &view);
+            for i in 1..4 {
+                dark_light.set_uniforms(program, &format!("pointLights[{i}]"), &view);
+            }
+        });
+
+        lamp.use_program();
+        lamp.set_mat4(c"view", &view);
+        lamp.set_mat4(c"projection", &projection);
+        lamp.set_vec3(c"lightColorSource", &LAMP_COLOR.to_array());
+        lamp.set_mat4(c"model", &graph.world(lamp_node));
+        cube.draw(&lamp);
+
+        // Check call events and swap the buffers
+        glfw.poll_events();
+        window.swap_buffers();
+    }
+
+    unsafe {
+        gl::DeleteTextures(3, [crate_diffuse, crate_specular, floor_diffuse].as_ptr());
+    }
+}
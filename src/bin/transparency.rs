@@ -0,0 +1,271 @@
+use std::path::Path;
+use glam::{Mat4, Quat, Vec3};
+use glfw::{Action, Context, Key};
+use opengl_book_examples::camera::camera::Camera;
+use opengl_book_examples::common::common::{handle_window_event, process_input};
+use opengl_book_examples::flashlight::flashlight::FlashLight;
+use opengl_book_examples::lights::lights::{DirectionalLight, PointLight, SpotLight};
+use opengl_book_examples::material::material::{BlendMode, Material};
+use opengl_book_examples::oit::oit::WeightedBlended;
+use opengl_book_examples::renderer::renderer::{DrawItem, RenderQueue};
+use opengl_book_examples::shaders::shaders::ShaderProgram;
+use opengl_book_examples::shapes::shapes;
+use opengl_book_examples::textures::textures::Texture;
+
+/// Colours of the glowing orbs, which also light the scene.
+const GLOW_COLORS: [Vec3; 3] = [
+    Vec3::new(1.0, 0.4, 0.1),
+    Vec3::new(0.2, 0.6, 1.0),
+    Vec3::new(0.4, 1.0, 0.3)
+];
+
+/// Every blend mode side by side: opaque crates, a cutout face with its transparent background
+/// discarded, blended panels using the alpha of their textures, three glass spheres going through
+/// each other and additive orbs circling them. O switches the blended surfaces between sorting
+/// them back to front and weighted blended order-independent transparency. Sorting draws whole
+/// objects in order, so where the spheres intersect one of them is always wrongly on top, which
+/// the order-independent path gets right. The window title shows the mode and what the frame
+/// cost.
+fn main() {
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+    glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
+    glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+    #[cfg(target_os = "macos")]
+    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+    let (mut window, events) =
+        glfw.create_window(800, 600, "Transparency", glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window.");
+
+    window.make_current();
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+
+    // set the cursor at the middle of the screen
+    window.set_cursor_mode(glfw::CursorMode::Disabled);
+
+    // GLAD OpenGL function pointers
+    gl::load_with(|symbol| match window.get_proc_address(symbol) {
+        Some(f) => f as *const _,
+        None => std::ptr::null(),
+    });
+
+    // Shader setup
+    let lit = match ShaderProgram::from_files("src/shaders/vertex/lights.vert", "src/shaders/fragment/material.frag") {
+        Ok(program) => program,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    // Texture setup
+    let load = |path: &str, is_png: bool| match Texture::load_texture(Path::new(path), is_png) {
+        Ok(id) => id,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+    let container = load("src/textures/wooden-container-with-metal-frame.png", true);
+    let steel_frame = load("src/textures/steel-frame.png", true);
+    let awesome_face = load("src/textures/awesomeface.png", true);
+    let gravel = load("src/textures/gravel-concrete.jpg", false);
+
+    let (mut width, mut height) = window.get_framebuffer_size();
+    let mut weighted_blended = match WeightedBlended::new(width, height) {
+        Ok(targets) => targets,
+        Err(e) => {
+            panic!("{}", e.to_string())
+        }
+    };
+
+    let cube = shapes::cube();
+    let sphere = shapes::uv_sphere(48, 24);
+
+    let floor = Material {
+        diffuse: Some(gravel),
+        specular_color: Vec3::splat(0.1),
+        ..Material::default()
+    };
+    let crate_material = Material::new(container, steel_frame);
+    let face = Material {
+        diffuse: Some(awesome_face),
+        blend: BlendMode::Cutout,
+        ..Material::default()
+    };
+    let frame_panel = Material {
+        diffuse: Some(steel_frame),
+        alpha: 0.9,
+        blend: BlendMode::Blended,
+        ..Material::default()
+    };
+    let face_panel = Material {
+        diffuse: Some(awesome_face),
+        alpha: 0.6,
+        blend: BlendMode::Blended,
+        ..Material::default()
+    };
+    let glass: [Material; 3] = [Vec3::new(1.0, 0.2, 0.2), Vec3::new(0.2, 1.0, 0.3), Vec3::new(0.3, 0.4, 1.0)]
+        .map(|color| Material {
+            diffuse_color: color,
+            specular_color: Vec3::ONE,
+            shininess: 64.0,
+            alpha: 0.4,
+            blend: BlendMode::Blended,
+            ..Material::default()
+        });
+    let glows: [Material; 3] = GLOW_COLORS.map(|color| Material {
+        diffuse_color: color,
+        specular_color: Vec3::ZERO,
+        alpha: 0.8,
+        blend: BlendMode::Additive,
+        ..Material::default()
+    });
+
+    let mut delta_time: f32; // Time between the current frame and last frame
+    let mut last_frame: f32 = 0.0; // Time of last frame
+    let mut last_x: f32 = 400.0;
+    let mut last_y: f32 = 300.0;
+    let mut first_mouse: bool = true;
+
+    // Camera setup
+    let mut camera: Camera = Camera::new(
+        Vec3::new(0.0, 1.5, 6.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        true
+    );
+    camera.set_orientation(-90.0, -10.0);
+    let mut flashlight: FlashLight = FlashLight::new(false);
+    let mut wireframe_mode: bool = false;
+    let mut order_independent: bool = true;
+
+    let directional_light = DirectionalLight {
+        direction: Vec3::new(-0.3, -1.0, -0.4),
+        ambient: Vec3::splat(0.2),
+        diffuse: Vec3::splat(0.6),
+        specular: Vec3::splat(0.5)
+    };
+    let mut title_time: f32 = 0.0;
+
+    unsafe {
+        gl::Enable(gl::DEPTH_TEST);
+    }
+
+    // MAIN LOOP
+    while !window.should_close() {
+        // Input
+        let current_frame: f32 = glfw.get_time() as f32;
+        delta_time = current_frame - last_frame;
+        last_frame = current_frame;
+
+        for (_, event) in glfw::flush_messages(&events) {
+            match event {
+                glfw::WindowEvent::FramebufferSize(new_width, new_height) => {
+                    width = new_width;
+                    height = new_height;
+                    if let Err(e) = weighted_blended.resize(width, height) {
+                        panic!("{}", e.to_string())
+                    }
+                }
+                glfw::WindowEvent::Key(Key::O, _, Action::Press, _) => {
+                    order_independent = !order_independent;
+                }
+                _ => {}
+            }
+            handle_window_event(
+                &mut window,
+                event,
+                &mut first_mouse,
+                &mut last_x,
+                &mut last_y,
+                &mut camera,
+                &mut flashlight,
+                &mut wireframe_mode
+            );
+        }
+        // a minimised window has nothing to draw into, wait until it is restored
+        if width <= 0 || height <= 0 {
+            glfw.wait_events();
+            continue;
+        }
+        process_input(&window, &mut camera, delta_time);
+
+        // the orbs circle the glass spheres, each one lighting the scene with its colour
+        let glow_positions: Vec<Vec3> = (0..GLOW_COLORS.len())
+            .map(|i| {
+                let angle = current_frame * 0.8 + i as f32 * std::f32::consts::TAU / GLOW_COLORS.len() as f32;
+                Vec3::new(angle.cos() * 1.8, 1.0 + (current_frame * 1.5 + i as f32).sin() * 0.3, angle.sin() * 1.8)
+            })
+            .collect();
+        let point_lights: Vec<PointLight> = glow_positions.iter().zip(GLOW_COLORS)
+            .map(|(position, color)| PointLight::new(*position, color * 0.02, color * 0.6, color))
+            .collect();
+        let dark_light = PointLight::new(Vec3::ZERO, Vec3::ZERO, Vec3::ZERO, Vec3::ZERO);
+        let flashlight_color = if flashlight.is_on() { Vec3::ONE } else { Vec3::ZERO };
+        let spot_light = SpotLight::flashlight(camera.position, camera.front(), flashlight_color);
+
+        // Render
+        let view = camera.get_view_matrix();
+        let aspect_ratio = width as f32 / height as f32;
+        let projection = camera.get_projection_matrix(aspect_ratio, 0.1, 100.0);
+        unsafe {
+            gl::ClearColor(0.05, 0.05, 0.08, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        let mut queue = RenderQueue::with_frustum(camera.frustum(aspect_ratio, 0.1, 100.0));
+        if order_independent {
+            queue = queue.order_independent(&weighted_blended);
+        }
+        queue.push(DrawItem::new(&cube, &floor, &lit, Mat4::from_scale_rotation_translation(
+            Vec3::new(12.0, 0.1, 12.0), Quat::IDENTITY, Vec3::new(0.0, -0.55, 0.0)
+        )));
+        queue.push(DrawItem::new(&cube, &crate_material, &lit, Mat4::from_translation(Vec3::new(-3.0, 0.0, -1.0))));
+        queue.push(DrawItem::new(&cube, &crate_material, &lit, Mat4::from_translation(Vec3::new(3.0, 0.0, -1.5))));
+        queue.push(DrawItem::new(&cube, &face, &lit, Mat4::from_translation(Vec3::new(-3.0, 1.0, -1.0))));
+        // thin panels in front of the crates, each one seeing the other through it
+        for (x, z, material) in [(-2.6, 0.8, &frame_panel), (-3.4, 1.4, &face_panel), (2.6, 0.6, &face_panel), (3.4, 1.2, &frame_panel)] {
+            queue.push(DrawItem::new(&cube, material, &lit, Mat4::from_scale_rotation_translation(
+                Vec3::new(0.9, 0.9, 0.02), Quat::IDENTITY, Vec3::new(x, 0.0, z)
+            )));
+        }
+        // the spheres overlap, so no draw order can show them right
+        for (i, material) in glass.iter().enumerate() {
+            let angle = current_frame * 0.3 + i as f32 * std::f32::consts::TAU / 3.0;
+            let position = Vec3::new(angle.cos() * 0.45, 1.0, angle.sin() * 0.45);
+            queue.push(DrawItem::new(&sphere, material, &lit, Mat4::from_scale_rotation_translation(
+                Vec3::splat(0.7), Quat::IDENTITY, position
+            )));
+        }
+        for (position, material) in glow_positions.iter().zip(&glows) {
+            queue.push(DrawItem::new(&sphere, material, &lit, Mat4::from_scale_rotation_translation(
+                Vec3::splat(0.15), Quat::IDENTITY, *position
+            )));
+        }
+        let stats = queue.render(camera.position, current_frame, |program| {
+            program.set_mat4(c"view", &view);
+            program.set_mat4(c"projection", &projection);
+            directional_light.set_uniforms(program, "dirLight", &view);
+            spot_light.set_uniforms(program, "spotLight", &view);
+            for i in 0..4 {
+                let light = point_lights.get(i).unwrap_or(&dark_light);
+                light.set_uniforms(program, &format!("pointLights[{i}]"), &view);
+            }
+        });
+
+        if current_frame - title_time >= 1.0 {
+            let mode = if order_independent { "weighted blended" } else { "sorted" };
+            window.set_title(&format!("Transparency - {mode} - {stats}"));
+            title_time = current_frame;
+        }
+
+        // Check call events and swap the buffers
+        glfw.poll_events();
+        window.swap_buffers();
+    }
+
+    unsafe {
+        gl::DeleteTextures(4, [container, steel_frame, awesome_face, gravel].as_ptr());
+    }
+}
@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+use glfw::{Action, Context, Key};
+use opengl_book_examples::camera::camera::Camera;
+use opengl_book_examples::common::common::{handle_window_event, process_input};
+use opengl_book_examples::flashlight::flashlight::FlashLight;
+use opengl_book_examples::scene_file::scene_file::LoadedScene;
+
+/// Renders any scene file, e.g. `cargo run --bin viewer -- scenes/multiple_lights.toml`. R reloads
+/// the file from disk and puts the camera back at its start pose, keeping the current scene when
+/// the new one has errors. Tab selects the next object, outlining it, and goes back to no
+/// selection after the last one. V cycles through the debug views: wireframe over the shaded
+/// scene, normals as colours, vertex normals as lines, a UV checkerboard, linear depth and each
+/// light on its own. The window title shows the view, the draw calls and state changes of the
+/// last frame, refreshed every second.
+fn main() {
+    let Some(path) = std::env::args().nth(1).map(PathBuf::from) else {
+        eprintln!("Usage: viewer <scene.toml>");
+        std::process::exit(1);
+    };
+
+    let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
+    glfw.window_hint(glfw::WindowHint::ContextVersion(3,3));
+    glfw.window_hint(glfw::WindowHint::OpenGlProfile(glfw::OpenGlProfileHint::Core));
+    #[cfg(target_os = "macos")]
+    glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+
+    let title = format!("Viewer - {}", path.display());
+    let (mut window, events) =
+        glfw.create_window(800, 600, &title, glfw::WindowMode::Windowed)
+            .expect("Failed to create GLFW window.");
+
+    window.make_current();
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+
+    // set the cursor at the middle of the screen
+    window.set_cursor_mode(glfw::CursorMode::Disabled);
+
+    // GLAD OpenGL function pointers
+    gl::load_with(|symbol| match window.get_proc_address(symbol) {
+        Some(f) => f as *const _,
+        None => std::ptr::null(),
+    });
+
+    let mut scene = match LoadedScene::load(&path) {
+        Ok(scene) => scene,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut delta_time: f32; // Time between the current frame and last frame
+    let mut last_frame: f32 = 0.0; // Time of last frame
+    let mut last_x: f32 = 400.0;
+    let mut last_y: f32 = 300.0;
+    let mut first_mouse: bool = true;
+    let (mut width, mut height) = window.get_framebuffer_size();
+
+    // the scene keeps its start pose, the window moves a copy of it around
+    let mut camera: Camera = scene.camera.clone();
+    let mut flashlight: FlashLight = FlashLight::new(scene.flashlight_on);
+    let mut wireframe_mode: bool = false;
+    let mut last_title: f32 = 0.0;
+
+    // MAIN LOOP
+    while !window.should_close() {
+        // Input
+        let current_frame: f32 = glfw.get_time() as f32;
+        delta_time = current_frame - last_frame;
+        last_frame = current_frame;
+
+        for (_, event) in glfw::flush_messages(&events) {
+            match event {
+                glfw::WindowEvent::FramebufferSize(new_width, new_height) => {
+                    width = new_width;
+                    height = new_height;
+                }
+                glfw::WindowEvent::Key(Key::R, _, Action::Press, _) => {
+                    match LoadedScene::load(&path) {
+                        Ok(reloaded) => {
+                            let debug_view = scene.debug_view;
+                            scene = reloaded;
+                            scene.debug_view = debug_view;
+                            camera = scene.camera.clone();
+                            flashlight = FlashLight::new(scene.flashlight_on);
+                            println!("Reloaded {}", path.display());
+                        }
+                        Err(e) => eprintln!("{e}")
+                    }
+                }
+                glfw::WindowEvent::Key(Key::V, _, Action::Press, _) => {
+                    scene.debug_view = scene.debug_view.next(scene.point_light_count());
+                    println!("Debug view: {}", scene.debug_view);
+                }
+                glfw::WindowEvent::Key(Key::Tab, _, Action::Press, _) => {
+                    scene.selected = match scene.selected {
+                        None if scene.object_count() > 0 => Some(0),
+                        Some(index) if index + 1 < scene.object_count() => Some(index + 1),
+                        _ => None
+                    };
+                    match scene.selected {
+                        Some(index) => println!("Selected object {index} {}", scene.object_name(index).unwrap_or("")),
+                        None => println!("Nothing selected")
+                    }
+                }
+                _ => {}
+            }
+            handle_window_event(
+                &mut window,
+                event,
+                &mut first_mouse,
+                &mut last_x,
+                &mut last_y,
+                &mut camera,
+                &mut flashlight,
+                &mut wireframe_mode
+            );
+        }
+        // a minimised window has nothing to draw into, wait until it is restored
+        if width <= 0 || height <= 0 {
+            glfw.wait_events();
+            continue;
+        }
+        process_input(&window, &mut camera, delta_time);
+
+        scene.flashlight_on = flashlight.is_on();
+        let stats = scene.draw(&camera, width, height, current_frame);
+        if current_frame - last_title >= 1.0 {
+            window.set_title(&format!("{title} - {} - {stats}", scene.debug_view));
+            last_title = current_frame;
+        }
+
+        // Check call events and swap the buffers
+        glfw.poll_events();
+        window.swap_buffers();
+    }
+}
@@ -0,0 +1,292 @@
+/// Bounding volumes and view frustums, to skip what the camera can't see before drawing it
+pub mod bounds {
+    use glam::{Mat4, Vec3, Vec4};
+
+    /// Axis aligned box, empty when any `min` component is above the matching `max` one.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Aabb {
+        pub min: Vec3,
+        pub max: Vec3
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct BoundingSphere {
+        pub center: Vec3,
+        pub radius: f32
+    }
+
+    /// Points `p` with `normal.dot(p) + distance >= 0` are in front of the plane. The normal is
+    /// unit length, so that value is the signed distance to the plane.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Plane {
+        pub normal: Vec3,
+        pub distance: f32
+    }
+
+    /// Six planes facing inwards, in the order left, right, bottom, top, near, far.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Frustum {
+        pub planes: [Plane; 6]
+    }
+
+    impl Aabb {
+        pub const EMPTY: Self = Aabb {
+            min: Vec3::splat(f32::INFINITY),
+            max: Vec3::splat(f32::NEG_INFINITY)
+        };
+
+        pub fn new(min: Vec3, max: Vec3) -> Self {
+            Aabb { min, max }
+        }
+
+        /// Smallest box holding every point, `EMPTY` without points.
+        pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+            points.into_iter().fold(Self::EMPTY, |aabb, point| Aabb {
+                min: aabb.min.min(point),
+                max: aabb.max.max(point)
+            })
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.min.cmpgt(self.max).any()
+        }
+
+        pub fn center(&self) -> Vec3 {
+            (self.min + self.max) * 0.5
+        }
+
+        /// Half the size along each axis.
+        pub fn extents(&self) -> Vec3 {
+            (self.max - self.min) * 0.5
+        }
+
+        /// Smallest box holding both.
+        pub fn union(&self, other: &Aabb) -> Self {
+            Aabb {
+                min: self.min.min(other.min),
+                max: self.max.max(other.max)
+            }
+        }
+
+        /// Box holding this one once transformed, which is larger than the transformed box itself
+        /// when `matrix` rotates. Each axis of the new box gathers what every column of the matrix
+        /// adds to it, as done in Graphics Gems' "Transforming Axis-Aligned Bounding Boxes".
+        pub fn transformed(&self, matrix: &Mat4) -> Self {
+            if self.is_empty() {
+                return *self;
+            }
+            let center = matrix.transform_point3(self.center());
+            let extents = self.extents();
+            let half_size = matrix.x_axis.truncate().abs() * extents.x
+                + matrix.y_axis.truncate().abs() * extents.y
+                + matrix.z_axis.truncate().abs() * extents.z;
+            Aabb {
+                min: center - half_size,
+                max: center + half_size
+            }
+        }
+    }
+
+    impl BoundingSphere {
+        pub fn new(center: Vec3, radius: f32) -> Self {
+            BoundingSphere { center, radius }
+        }
+
+        /// Sphere around the center of the points' box, reaching the farthest point. Not the
+        /// smallest sphere, but close and found in two passes.
+        pub fn from_points(points: &[Vec3]) -> Self {
+            if points.is_empty() {
+                return BoundingSphere::new(Vec3::ZERO, 0.0);
+            }
+            let center = Aabb::from_points(points.iter().copied()).center();
+            let radius = points.iter()
+                .map(|point| point.distance_squared(center))
+                .fold(0.0, f32::max)
+                .sqrt();
+            BoundingSphere { center, radius }
+        }
+
+        /// Sphere holding this one once transformed, scaled by the largest scale of `matrix`.
+        pub fn transformed(&self, matrix: &Mat4) -> Self {
+            let scale = matrix.x_axis.truncate().length_squared()
+                .max(matrix.y_axis.truncate().length_squared())
+                .max(matrix.z_axis.truncate().length_squared())
+                .sqrt();
+            BoundingSphere {
+                center: matrix.transform_point3(self.center),
+                radius: self.radius * scale
+            }
+        }
+    }
+
+    impl Plane {
+        /// Plane from the `(a, b, c, d)` coefficients of `ax + by + cz + d = 0`, normalized.
+        pub fn from_coefficients(coefficients: Vec4) -> Self {
+            let length = coefficients.truncate().length();
+            Plane {
+                normal: coefficients.truncate() / length,
+                distance: coefficients.w / length
+            }
+        }
+
+        pub fn signed_distance(&self, point: Vec3) -> f32 {
+            self.normal.dot(point) + self.distance
+        }
+    }
+
+    impl Frustum {
+        /// Planes of the volume a projection, or a projection times a view matrix, maps to the
+        /// OpenGL clip cube, following Gribb and Hartmann's "Fast Extraction of Viewing Frustum
+        /// Planes from the World-View-Projection Matrix". With a view matrix in the product the
+        /// planes are in world space, otherwise in view space.
+        pub fn from_matrix(matrix: &Mat4) -> Self {
+            // a point is inside when -w <= x, y, z <= w in clip space, each side of those
+            // inequalities is a combination of the matrix rows
+            let row = |i: usize| matrix.row(i);
+            let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+            Frustum {
+                planes: [
+                    Plane::from_coefficients(w + x),
+                    Plane::from_coefficients(w - x),
+                    Plane::from_coefficients(w + y),
+                    Plane::from_coefficients(w - y),
+                    Plane::from_coefficients(w + z),
+                    Plane::from_coefficients(w - z)
+                ]
+            }
+        }
+
+        pub fn contains_point(&self, point: Vec3) -> bool {
+            self.planes.iter().all(|plane| plane.signed_distance(point) >= 0.0)
+        }
+
+        /// False only when the sphere is entirely behind one of the planes. Spheres next to a
+        /// corner of the frustum may pass without touching it, which only costs a draw.
+        pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+            self.planes.iter().all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+        }
+
+        /// False only when the box is entirely behind one of the planes, tested with the corner
+        /// of the box farthest along the plane normal. Same leniency near corners as spheres.
+        pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+            if aabb.is_empty() {
+                return false;
+            }
+            self.planes.iter().all(|plane| {
+                let farthest = Vec3::select(plane.normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+                plane.signed_distance(farthest) >= 0.0
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const EPSILON: f32 = 1e-5;
+
+        fn perspective() -> Mat4 {
+            Mat4::perspective_rh_gl(90.0_f32.to_radians(), 1.0, 1.0, 10.0)
+        }
+
+        #[test]
+        fn planes_of_an_orthographic_projection() {
+            let frustum = Frustum::from_matrix(&Mat4::orthographic_rh_gl(-2.0, 2.0, -1.0, 1.0, 1.0, 10.0));
+            let expected = [
+                (Vec3::X, 2.0),
+                (Vec3::NEG_X, 2.0),
+                (Vec3::Y, 1.0),
+                (Vec3::NEG_Y, 1.0),
+                // the camera looks down -Z, so the near plane is at z = -1 and the far one at -10
+                (Vec3::NEG_Z, -1.0),
+                (Vec3::Z, 10.0)
+            ];
+            for (plane, (normal, distance)) in frustum.planes.iter().zip(expected) {
+                assert!(plane.normal.abs_diff_eq(normal, EPSILON), "{plane:?} should face {normal}");
+                assert!((plane.distance - distance).abs() < EPSILON, "{plane:?} should be {distance} away");
+            }
+        }
+
+        #[test]
+        fn planes_of_a_perspective_projection_are_normalized() {
+            let frustum = Frustum::from_matrix(&perspective());
+            for plane in frustum.planes {
+                assert!((plane.normal.length() - 1.0).abs() < EPSILON);
+            }
+            // with a 90 degree field of view the side planes are at 45 degrees
+            let left = frustum.planes[0];
+            assert!(left.normal.abs_diff_eq(Vec3::new(1.0, 0.0, -1.0).normalize(), EPSILON));
+            assert!(left.distance.abs() < EPSILON);
+        }
+
+        #[test]
+        fn planes_follow_the_view_matrix() {
+            let view = Mat4::look_at_rh(Vec3::new(5.0, 0.0, 0.0), Vec3::new(5.0, 0.0, -1.0), Vec3::Y);
+            let frustum = Frustum::from_matrix(&(perspective() * view));
+            assert!(frustum.contains_point(Vec3::new(5.0, 0.0, -5.0)));
+            assert!(!frustum.contains_point(Vec3::new(-1.0, 0.0, -5.0)));
+        }
+
+        #[test]
+        fn points_inside_and_outside() {
+            let frustum = Frustum::from_matrix(&perspective());
+            assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -5.0)));
+            assert!(frustum.contains_point(Vec3::new(4.9, -4.9, -5.0)));
+            assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -0.5)), "in front of the near plane");
+            assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -11.0)), "beyond the far plane");
+            assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 5.0)), "behind the camera");
+            assert!(!frustum.contains_point(Vec3::new(5.1, 0.0, -5.0)), "right of the right plane");
+        }
+
+        #[test]
+        fn spheres_inside_straddling_and_outside() {
+            let frustum = Frustum::from_matrix(&perspective());
+            assert!(frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(0.0, 0.0, -5.0), 1.0)));
+            assert!(frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(0.0, 0.0, -11.0), 2.0)));
+            assert!(frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(0.0, 0.0, 0.0), 1.5)));
+            assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(0.0, 0.0, -12.0), 1.0)));
+            assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(0.0, 0.0, 3.0), 1.0)));
+            assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(-8.0, 0.0, -5.0), 1.0)));
+        }
+
+        #[test]
+        fn boxes_inside_straddling_and_outside() {
+            let frustum = Frustum::from_matrix(&perspective());
+            let unit_box_at = |center: Vec3| Aabb::new(center - Vec3::splat(0.5), center + Vec3::splat(0.5));
+            assert!(frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, 0.0, -5.0))));
+            assert!(frustum.intersects_aabb(&unit_box_at(Vec3::new(5.2, 0.0, -5.0))), "straddles the right plane");
+            assert!(frustum.intersects_aabb(&Aabb::new(Vec3::splat(-20.0), Vec3::splat(20.0))), "holds the frustum");
+            assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(6.5, 0.0, -5.0))));
+            assert!(!frustum.intersects_aabb(&unit_box_at(Vec3::new(0.0, 0.0, 2.0))));
+            assert!(!frustum.intersects_aabb(&Aabb::EMPTY));
+        }
+
+        #[test]
+        fn transformed_bounds() {
+            let aabb = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+            let moved = aabb.transformed(&(Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0)) * Mat4::from_scale(Vec3::new(2.0, 1.0, 1.0))));
+            assert!(moved.min.abs_diff_eq(Vec3::new(8.0, -1.0, -1.0), EPSILON));
+            assert!(moved.max.abs_diff_eq(Vec3::new(12.0, 1.0, 1.0), EPSILON));
+
+            // a box turned by 45 degrees around Y reaches sqrt(2) along X and Z
+            let turned = aabb.transformed(&Mat4::from_rotation_y(45.0_f32.to_radians()));
+            assert!(turned.max.abs_diff_eq(Vec3::new(2.0_f32.sqrt(), 1.0, 2.0_f32.sqrt()), EPSILON));
+
+            let sphere = BoundingSphere::new(Vec3::X, 1.0)
+                .transformed(&(Mat4::from_translation(Vec3::Y) * Mat4::from_scale(Vec3::new(1.0, 3.0, 2.0))));
+            assert!(sphere.center.abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), EPSILON));
+            assert!((sphere.radius - 3.0).abs() < EPSILON);
+        }
+
+        #[test]
+        fn bounds_of_points() {
+            let points = [Vec3::new(-1.0, 0.0, 0.0), Vec3::new(3.0, 2.0, 0.0), Vec3::new(1.0, -2.0, 1.0)];
+            let aabb = Aabb::from_points(points);
+            assert_eq!(aabb, Aabb::new(Vec3::new(-1.0, -2.0, 0.0), Vec3::new(3.0, 2.0, 1.0)));
+            let sphere = BoundingSphere::from_points(&points);
+            assert!(sphere.center.abs_diff_eq(Vec3::new(1.0, 0.0, 0.5), EPSILON));
+            assert!(points.iter().all(|point| point.distance(sphere.center) <= sphere.radius + EPSILON));
+            assert!(Aabb::from_points([]).is_empty());
+        }
+    }
+}
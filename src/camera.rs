@@ -1,6 +1,8 @@
 /// Structure for all camera setup and camera movement
 pub mod camera {
-    use glam::{Mat4, Vec3};
+    use glam::{Mat4, Vec3, Vec4};
+    use crate::bounds::bounds::Frustum;
+    use crate::ray::ray::Ray;
 
     #[derive(Debug)]
     pub enum CameraMovement {
@@ -12,6 +14,7 @@ pub mod camera {
         DOWN
     }
 
+    #[derive(Debug, Clone)]
     pub struct Camera {
         // camera Attributes
         pub position: Vec3,
@@ -131,6 +134,44 @@ pub mod camera {
             Mat4::look_at_rh(self.position, self.position + self.front, self.up)
         }
 
+        /// Points the camera in the direction given by the euler angles, in degrees. A yaw of -90
+        /// looks down the negative Z axis.
+        pub fn set_orientation(&mut self, yaw: f32, pitch: f32) {
+            self.yaw = yaw;
+            self.pitch = pitch.clamp(-89.0, 89.0);
+            self.front = Self::get_front_vector(self.yaw, self.pitch);
+            self.right = Self::get_right_vector(self.front, self.world_up);
+            self.up = Self::get_up_vector(self.right, self.front);
+        }
+
+        /// Direction the camera is looking at
+        pub fn front(&self) -> Vec3 {
+            self.front
+        }
+
+        /// Perspective projection with the camera zoom as vertical field of view.
+        pub fn get_projection_matrix(&self, aspect_ratio: f32, near: f32, far: f32) -> Mat4 {
+            Mat4::perspective_rh_gl(self.zoom.to_radians(), aspect_ratio, near, far)
+        }
+
+        /// World space planes of what `get_projection_matrix` with the same arguments shows.
+        pub fn frustum(&self, aspect_ratio: f32, near: f32, far: f32) -> Frustum {
+            Frustum::from_matrix(&(self.get_projection_matrix(aspect_ratio, near, far) * self.get_view_matrix()))
+        }
+
+        /// Ray from the near plane through the point of the screen, such as the cursor position.
+        /// `viewport` is the `x, y, width, height` of the area drawn into, in the same units as
+        /// the point and with y going down, like window coordinates. `projection` is the one the
+        /// scene is drawn with, so the ray goes through what is shown under the point.
+        pub fn screen_point_to_ray(&self, x: f32, y: f32, viewport: Vec4, projection: &Mat4) -> Ray {
+            let ndc_x = 2.0 * (x - viewport.x) / viewport.z - 1.0;
+            let ndc_y = 1.0 - 2.0 * (y - viewport.y) / viewport.w;
+            let inverse = (*projection * self.get_view_matrix()).inverse();
+            let near = inverse.project_point3(Vec3::new(ndc_x, ndc_y, -1.0));
+            let far = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 1.0));
+            Ray::new(near, far - near)
+        }
+
         fn get_front_vector(yaw: f32, pitch: f32) -> Vec3 {
             Vec3::new(
                 yaw.to_radians().cos() * pitch.to_radians().cos(),
@@ -0,0 +1,248 @@
+/// Reads back what the window shows, to save screenshots or record footage frame by frame
+pub mod capture {
+    use std::io::{Error, Write};
+    use std::path::{Path, PathBuf};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::mpsc::{self, Receiver, SyncSender};
+    use std::thread::JoinHandle;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use image::RgbaImage;
+    use crate::framebuffer::framebuffer::Framebuffer;
+
+    /// Frames per second recordings are meant to be played back at. Examples advance their clock
+    /// by `1 / FRAME_RATE` per recorded frame, so the footage plays at the right speed however
+    /// long each frame took to render and write.
+    pub const FRAME_RATE: u32 = 60;
+
+    /// Number of pixel buffers frames go through. A frame is copied into a buffer by the GPU while
+    /// the CPU maps the one filled `PIXEL_BUFFERS - 1` frames earlier, which is long done by then.
+    const PIXEL_BUFFERS: usize = 3;
+    /// Frames read back but not written yet before `capture` waits for the writer.
+    const QUEUED_FRAMES: usize = 8;
+
+    /// Where a `Recorder` sends its frames.
+    #[derive(Debug, Clone)]
+    pub enum RecordingOutput {
+        /// `frame-00000.png`, `frame-00001.png`, ... in the directory, created if needed.
+        Png(PathBuf),
+        /// Shell command reading raw RGBA frames, top row first, from its standard input. `{width}`,
+        /// `{height}` and `{fps}` are replaced, e.g. `ffmpeg -f rawvideo -pix_fmt rgba -s
+        /// {width}x{height} -r {fps} -i - -pix_fmt yuv420p demo.mp4`.
+        Command(String)
+    }
+
+    enum Sink {
+        Png(PathBuf),
+        Encoder(Child)
+    }
+
+    /// Records the default framebuffer every time `capture` is called. Frames are read back
+    /// asynchronously through pixel buffer objects and written on a separate thread, so recording
+    /// doesn't stall the render loop on the GPU nor on PNG encoding.
+    pub struct Recorder {
+        pixel_buffers: [u32; PIXEL_BUFFERS],
+        width: i32,
+        height: i32,
+        next: usize,
+        pending: usize,
+        frames: u32,
+        sender: Option<SyncSender<Vec<u8>>>,
+        writer: Option<JoinHandle<Result<(), Error>>>
+    }
+
+    /// Saves what the window currently shows as `screenshot-<milliseconds since epoch>.png` in
+    /// `directory`, and returns the path written. Call it after rendering and before swapping the
+    /// buffers.
+    pub fn screenshot<P: AsRef<Path>>(width: i32, height: i32, directory: P) -> Result<PathBuf, Error> {
+        let image = Framebuffer::read_default_pixels(width, height);
+        std::fs::create_dir_all(directory.as_ref())?;
+        let path = directory.as_ref().join(format!("screenshot-{}.png", timestamp()));
+        image.save(&path).map_err(Error::other)?;
+        Ok(path)
+    }
+
+    impl Recorder {
+        /// Starts recording `width` x `height` frames from the bottom left corner of the window.
+        /// The size is fixed for the whole recording, encoders can't change it halfway.
+        pub fn start(output: RecordingOutput, width: i32, height: i32) -> Result<Self, Error> {
+            let sink = match output {
+                RecordingOutput::Png(directory) => {
+                    std::fs::create_dir_all(&directory)?;
+                    Sink::Png(directory)
+                }
+                RecordingOutput::Command(command) => {
+                    let command = command
+                        .replace("{width}", &width.to_string())
+                        .replace("{height}", &height.to_string())
+                        .replace("{fps}", &FRAME_RATE.to_string());
+                    let child = shell(&command)
+                        .stdin(Stdio::piped())
+                        .spawn()
+                        .map_err(|e| Error::other(format!("ERROR::CAPTURE::ENCODER_FAILED_TO_START {command}\n{e}")))?;
+                    Sink::Encoder(child)
+                }
+            };
+
+            let mut pixel_buffers = [0; PIXEL_BUFFERS];
+            let size = (width * height * 4) as isize;
+            unsafe {
+                gl::GenBuffers(PIXEL_BUFFERS as i32, pixel_buffers.as_mut_ptr());
+                for buffer in pixel_buffers {
+                    gl::BindBuffer(gl::PIXEL_PACK_BUFFER, buffer);
+                    gl::BufferData(gl::PIXEL_PACK_BUFFER, size, std::ptr::null(), gl::STREAM_READ);
+                }
+                gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+            }
+
+            let (sender, receiver) = mpsc::sync_channel(QUEUED_FRAMES);
+            let (frame_width, frame_height) = (width as u32, height as u32);
+            let writer = std::thread::spawn(move || write_frames(sink, frame_width, frame_height, receiver));
+            Ok(Recorder {
+                pixel_buffers,
+                width,
+                height,
+                next: 0,
+                pending: 0,
+                frames: 0,
+                sender: Some(sender),
+                writer: Some(writer)
+            })
+        }
+
+        /// Queues the read back of the default framebuffer and hands the oldest frame that is ready
+        /// to the writer. Call it after rendering and before swapping the buffers.
+        pub fn capture(&mut self) -> Result<(), Error> {
+            if self.pending == PIXEL_BUFFERS {
+                self.send_oldest()?;
+            }
+            unsafe {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+                gl::BindBuffer(gl::PIXEL_PACK_BUFFER, self.pixel_buffers[self.next]);
+                // with a pack buffer bound the last argument is an offset into it, and the call
+                // returns without waiting for the GPU. RGBA8 rows are 4 byte aligned, as the
+                // default PACK_ALIGNMENT expects.
+                gl::ReadPixels(0, 0, self.width, self.height, gl::RGBA, gl::UNSIGNED_BYTE, std::ptr::null_mut());
+                gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+            }
+            self.next = (self.next + 1) % PIXEL_BUFFERS;
+            self.pending += 1;
+            Ok(())
+        }
+
+        /// Frames captured so far, including the ones still being read back.
+        pub fn frames(&self) -> u32 {
+            self.frames + self.pending as u32
+        }
+
+        /// Writes the frames still in flight, waits for the writer to finish and returns the number
+        /// of frames recorded.
+        pub fn finish(mut self) -> Result<u32, Error> {
+            while self.pending > 0 {
+                self.send_oldest()?;
+            }
+            self.join_writer()?;
+            Ok(self.frames)
+        }
+
+        fn send_oldest(&mut self) -> Result<(), Error> {
+            let oldest = (self.next + PIXEL_BUFFERS - self.pending) % PIXEL_BUFFERS;
+            let pixels = self.map(self.pixel_buffers[oldest]);
+            self.pending -= 1;
+            self.frames += 1;
+            let sent = match &self.sender {
+                Some(sender) => sender.send(pixels).is_ok(),
+                None => false
+            };
+            if !sent {
+                // the writer only hangs up when it failed, report why
+                self.join_writer()?;
+                return Err(Error::other("ERROR::CAPTURE::WRITER_STOPPED"));
+            }
+            Ok(())
+        }
+
+        /// Copies the frame out of a pixel buffer, flipped so the top row comes first.
+        fn map(&self, buffer: u32) -> Vec<u8> {
+            let row = self.width as usize * 4;
+            let mut pixels = vec![0; row * self.height as usize];
+            unsafe {
+                gl::BindBuffer(gl::PIXEL_PACK_BUFFER, buffer);
+                let data = gl::MapBuffer(gl::PIXEL_PACK_BUFFER, gl::READ_ONLY) as *const u8;
+                if !data.is_null() {
+                    let data = std::slice::from_raw_parts(data, pixels.len());
+                    // OpenGL starts at the bottom left corner while images start at the top left
+                    for (target, source) in pixels.chunks_exact_mut(row).zip(data.chunks_exact(row).rev()) {
+                        target.copy_from_slice(source);
+                    }
+                    gl::UnmapBuffer(gl::PIXEL_PACK_BUFFER);
+                }
+                gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+            }
+            pixels
+        }
+
+        fn join_writer(&mut self) -> Result<(), Error> {
+            // closing the channel ends the writer loop
+            self.sender = None;
+            match self.writer.take() {
+                Some(writer) => writer.join()
+                    .map_err(|_| Error::other("ERROR::CAPTURE::WRITER_PANICKED"))?,
+                None => Ok(())
+            }
+        }
+    }
+
+    impl Drop for Recorder {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteBuffers(PIXEL_BUFFERS as i32, self.pixel_buffers.as_ptr());
+            }
+        }
+    }
+
+    fn write_frames(sink: Sink, width: u32, height: u32, frames: Receiver<Vec<u8>>) -> Result<(), Error> {
+        match sink {
+            Sink::Png(directory) => {
+                for (i, pixels) in frames.iter().enumerate() {
+                    let image = RgbaImage::from_raw(width, height, pixels)
+                        .ok_or_else(|| Error::other("ERROR::CAPTURE::FRAME_SIZE"))?;
+                    image.save(directory.join(format!("frame-{i:05}.png"))).map_err(Error::other)?;
+                }
+                Ok(())
+            }
+            Sink::Encoder(mut child) => {
+                let mut stdin = child.stdin.take()
+                    .ok_or_else(|| Error::other("ERROR::CAPTURE::ENCODER_STDIN"))?;
+                for pixels in frames.iter() {
+                    stdin.write_all(&pixels)?;
+                }
+                // the encoder finishes the file once its input is closed
+                drop(stdin);
+                let status = child.wait()?;
+                if !status.success() {
+                    return Err(Error::other(format!("ERROR::CAPTURE::ENCODER_FAILED\nThe encoder exited with {status}")));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    fn shell(command: &str) -> Command {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    }
+
+    #[cfg(not(windows))]
+    fn shell(command: &str) -> Command {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+
+    /// Milliseconds since the Unix epoch, unique enough to name the files of one session.
+    pub fn timestamp() -> u128 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_millis()).unwrap_or(0)
+    }
+}
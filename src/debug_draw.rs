@@ -0,0 +1,347 @@
+/// Immediate mode debug drawing: lines and simple shapes gathered during a frame and drawn at once
+pub mod debug_draw {
+    use std::io::Error;
+    use std::mem::offset_of;
+    use glam::{Mat4, Vec3};
+    use crate::bounds::bounds::Aabb;
+    use crate::lights::lights::{DirectionalLight, PointLight, SpotLight};
+    use crate::mesh::mesh::Vertex;
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// Segments of the circles, and of the spheres and cones made of them.
+    const SEGMENTS: usize = 32;
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct LineVertex {
+        position: Vec3,
+        color: Vec3
+    }
+
+    /// Lines in world space waiting to be drawn. Everything is added again every frame, so the
+    /// code that knows about a box or a light draws it right where it is used, and nothing has to
+    /// be kept in sync. `DebugPass::flush` draws the whole batch with one draw call and empties it.
+    #[derive(Debug, Clone)]
+    pub struct DebugDraw {
+        vertices: Vec<LineVertex>,
+        /// Whether the scene hides the lines behind it, or they are drawn on top of everything.
+        pub depth_test: bool
+    }
+
+    /// Program and buffer the batches are drawn with.
+    pub struct DebugPass {
+        program: ShaderProgram,
+        vao: u32,
+        vbo: u32
+    }
+
+    impl DebugDraw {
+        pub fn new() -> Self {
+            DebugDraw { vertices: Vec::new(), depth_test: true }
+        }
+
+        pub fn line(&mut self, from: Vec3, to: Vec3, color: Vec3) {
+            self.vertices.push(LineVertex { position: from, color });
+            self.vertices.push(LineVertex { position: to, color });
+        }
+
+        /// Number of lines waiting to be drawn.
+        pub fn line_count(&self) -> usize {
+            self.vertices.len() / 2
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.vertices.is_empty()
+        }
+
+        pub fn clear(&mut self) {
+            self.vertices.clear();
+        }
+
+        /// The X, Y and Z axes of `model` in red, green and blue, `length` units long before the
+        /// scale of the matrix.
+        pub fn axes(&mut self, model: &Mat4, length: f32) {
+            let origin = model.transform_point3(Vec3::ZERO);
+            for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+                self.line(origin, model.transform_point3(axis * length), axis);
+            }
+        }
+
+        /// Grid on the XZ plane around the origin, `cells` cells of `spacing` units from the
+        /// middle to each side.
+        pub fn grid(&mut self, cells: u32, spacing: f32, color: Vec3) {
+            let extent = cells as f32 * spacing;
+            for i in -(cells as i32)..=cells as i32 {
+                let offset = i as f32 * spacing;
+                self.line(Vec3::new(offset, 0.0, -extent), Vec3::new(offset, 0.0, extent), color);
+                self.line(Vec3::new(-extent, 0.0, offset), Vec3::new(extent, 0.0, offset), color);
+            }
+        }
+
+        /// Edges of the box placed by `model`, which stays a box rotated along with the object
+        /// rather than the larger axis aligned one `Aabb::transformed` would give.
+        pub fn aabb(&mut self, aabb: &Aabb, model: &Mat4, color: Vec3) {
+            if aabb.is_empty() {
+                return;
+            }
+            // corner i takes max on the axes whose bit is set in i
+            let corners: [Vec3; 8] = std::array::from_fn(|i| model.transform_point3(Vec3::new(
+                if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                if i & 4 == 0 { aabb.min.z } else { aabb.max.z }
+            )));
+            self.box_edges(&corners, color);
+        }
+
+        /// Edges of the volume a view projection matrix sees, such as the one of another camera.
+        pub fn frustum(&mut self, view_projection: &Mat4, color: Vec3) {
+            let inverse = view_projection.inverse();
+            let corners: [Vec3; 8] = std::array::from_fn(|i| inverse.project_point3(Vec3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 }
+            )));
+            self.box_edges(&corners, color);
+        }
+
+        pub fn circle(&mut self, center: Vec3, normal: Vec3, radius: f32, color: Vec3) {
+            let (u, v) = normal.normalize_or(Vec3::Y).any_orthonormal_pair();
+            let point = |i: usize| {
+                let angle = i as f32 * std::f32::consts::TAU / SEGMENTS as f32;
+                center + (u * angle.cos() + v * angle.sin()) * radius
+            };
+            for i in 0..SEGMENTS {
+                self.line(point(i), point(i + 1), color);
+            }
+        }
+
+        /// Three circles around the axes.
+        pub fn sphere(&mut self, center: Vec3, radius: f32, color: Vec3) {
+            for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+                self.circle(center, axis, radius, color);
+            }
+        }
+
+        /// Line with a head of four short lines at `to`.
+        pub fn arrow(&mut self, from: Vec3, to: Vec3, color: Vec3) {
+            self.line(from, to, color);
+            let direction = to - from;
+            let length = direction.length();
+            if length <= f32::EPSILON {
+                return;
+            }
+            let (u, v) = (direction / length).any_orthonormal_pair();
+            let base = to - direction * 0.2;
+            for side in [u, -u, v, -v] {
+                self.line(to, base + side * length * 0.08, color);
+            }
+        }
+
+        /// Arrow pointing where the light shines, starting at `origin` since the light itself is
+        /// everywhere.
+        pub fn directional_light(&mut self, light: &DirectionalLight, origin: Vec3, length: f32) {
+            let direction = light.direction.normalize_or_zero();
+            self.arrow(origin, origin + direction * length, gizmo_color(light.diffuse));
+        }
+
+        /// Small cross at the light and a sphere at the distance it stops lighting, see
+        /// `PointLight::radius`.
+        pub fn point_light(&mut self, light: &PointLight) {
+            let color = gizmo_color(light.diffuse);
+            for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+                self.line(light.position - axis * 0.1, light.position + axis * 0.1, color);
+            }
+            let radius = light.radius();
+            if radius.is_finite() {
+                self.sphere(light.position, radius, color);
+            }
+        }
+
+        /// Cone of the outer cut off, `length` units long, with the inner cut off as a circle
+        /// at its base.
+        pub fn spot_light(&mut self, light: &SpotLight, length: f32) {
+            let color = gizmo_color(light.diffuse);
+            let direction = light.direction.normalize_or_zero();
+            if direction == Vec3::ZERO {
+                return;
+            }
+            let base = light.position + direction * length;
+            let outer = length * light.outer_cut_off.to_radians().tan();
+            let inner = length * light.cut_off.to_radians().tan();
+            self.circle(base, direction, outer, color);
+            self.circle(base, direction, inner, color);
+            let (u, v) = direction.any_orthonormal_pair();
+            for side in [u, -u, v, -v] {
+                self.line(light.position, base + side * outer, color);
+            }
+        }
+
+        /// A line along the normal of every vertex placed by `model`, such as `mesh.vertices`.
+        pub fn normals(&mut self, vertices: &[Vertex], model: &Mat4, length: f32, color: Vec3) {
+            let normal_matrix = model.inverse().transpose();
+            for vertex in vertices {
+                let position = model.transform_point3(vertex.position());
+                let normal = normal_matrix.transform_vector3(vertex.normal()).normalize_or_zero();
+                self.line(position, position + normal * length, color);
+            }
+        }
+
+        /// Edges between corners numbered like `aabb` numbers them.
+        fn box_edges(&mut self, corners: &[Vec3; 8], color: Vec3) {
+            for i in 0..8 {
+                for bit in [1, 2, 4] {
+                    // each edge once, from the corner without the bit to the one with it
+                    if i & bit == 0 {
+                        self.line(corners[i], corners[i | bit], color);
+                    }
+                }
+            }
+        }
+    }
+
+    impl Default for DebugDraw {
+        fn default() -> Self {
+            DebugDraw::new()
+        }
+    }
+
+    /// Light colour at full brightness, so dim lights still show.
+    fn gizmo_color(color: Vec3) -> Vec3 {
+        let max = color.max_element();
+        if max > 0.0 { color / max } else { Vec3::ONE }
+    }
+
+    impl DebugPass {
+        pub fn new() -> Result<Self, Error> {
+            let program = ShaderProgram::from_files(
+                "src/shaders/vertex/debug_lines.vert",
+                "src/shaders/fragment/debug_lines.frag"
+            )?;
+            let (mut vao, mut vbo) = (0, 0);
+            unsafe {
+                gl::GenVertexArrays(1, &mut vao);
+                gl::GenBuffers(1, &mut vbo);
+                gl::BindVertexArray(vao);
+                gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+                let stride = size_of::<LineVertex>() as i32;
+                gl::EnableVertexAttribArray(0);
+                gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, offset_of!(LineVertex, position) as *const _);
+                gl::EnableVertexAttribArray(1);
+                gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride, offset_of!(LineVertex, color) as *const _);
+                gl::BindVertexArray(0);
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            }
+            Ok(DebugPass { program, vao, vbo })
+        }
+
+        /// Draws the lines of the batch in one draw call into the bound framebuffer and empties
+        /// it. Returns how many lines were drawn. Depth testing is left as it was found.
+        pub fn flush(&self, debug: &mut DebugDraw, view: &Mat4, projection: &Mat4) -> usize {
+            let lines = debug.line_count();
+            if lines == 0 {
+                return 0;
+            }
+            unsafe {
+                let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+                if debug.depth_test {
+                    gl::Enable(gl::DEPTH_TEST);
+                } else {
+                    gl::Disable(gl::DEPTH_TEST);
+                }
+
+                // a new store every frame, so the previous frame may still draw from the old one
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    size_of_val(debug.vertices.as_slice()) as isize,
+                    debug.vertices.as_ptr().cast(),
+                    gl::STREAM_DRAW
+                );
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+                self.program.use_program();
+                self.program.set_mat4(c"view", view);
+                self.program.set_mat4(c"projection", projection);
+                gl::BindVertexArray(self.vao);
+                gl::DrawArrays(gl::LINES, 0, debug.vertices.len() as i32);
+                gl::BindVertexArray(0);
+
+                if depth_test {
+                    gl::Enable(gl::DEPTH_TEST);
+                } else {
+                    gl::Disable(gl::DEPTH_TEST);
+                }
+            }
+            debug.clear();
+            lines
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const EPSILON: f32 = 1e-4;
+
+        fn points(debug: &DebugDraw) -> Vec<Vec3> {
+            debug.vertices.iter().map(|vertex| vertex.position).collect()
+        }
+
+        #[test]
+        fn boxes_have_twelve_edges_placed_by_the_model() {
+            let mut debug = DebugDraw::new();
+            let model = Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)) * Mat4::from_scale(Vec3::splat(2.0));
+            debug.aabb(&Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0)), &model, Vec3::ONE);
+            assert_eq!(debug.line_count(), 12);
+            let points = points(&debug);
+            assert!(points.iter().all(|point| (point - Vec3::new(5.0, 0.0, 0.0)).abs().abs_diff_eq(Vec3::splat(2.0), EPSILON)));
+            // every edge runs along one axis and is as long as the box
+            for edge in points.chunks_exact(2) {
+                assert!(((edge[1] - edge[0]).length() - 4.0).abs() < EPSILON);
+            }
+
+            debug.clear();
+            debug.aabb(&Aabb::from_points([]), &model, Vec3::ONE);
+            assert!(debug.is_empty());
+        }
+
+        #[test]
+        fn frusta_end_at_the_near_and_far_planes() {
+            let mut debug = DebugDraw::new();
+            let projection = Mat4::perspective_rh_gl(90.0_f32.to_radians(), 1.0, 1.0, 10.0);
+            let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+            debug.frustum(&(projection * view), Vec3::ONE);
+            assert_eq!(debug.line_count(), 12);
+            for point in points(&debug) {
+                let depth = 5.0 - point.z;
+                assert!((depth - 1.0).abs() < EPSILON || (depth - 10.0).abs() < 1e-3);
+                // a 90 degree field of view is as wide as it is far
+                assert!((point.x.abs() - depth).abs() < 1e-3 && (point.y.abs() - depth).abs() < 1e-3);
+            }
+        }
+
+        #[test]
+        fn circles_lie_on_their_plane_at_the_radius() {
+            let mut debug = DebugDraw::new();
+            let (center, normal) = (Vec3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 1.0, 0.0));
+            debug.circle(center, normal, 2.0, Vec3::ONE);
+            assert_eq!(debug.line_count(), SEGMENTS);
+            for point in points(&debug) {
+                assert!(((point - center).length() - 2.0).abs() < EPSILON);
+                assert!((point - center).dot(normal).abs() < EPSILON);
+            }
+        }
+
+        #[test]
+        fn normals_follow_the_model() {
+            let vertices = [Vertex::new(Vec3::ZERO, Vec3::Y, glam::Vec2::ZERO)];
+            let mut debug = DebugDraw::new();
+            // the normal of a surface squashed along X then turned to face X stays unit length
+            let model = Mat4::from_rotation_z(-std::f32::consts::FRAC_PI_2) * Mat4::from_scale(Vec3::new(0.5, 2.0, 1.0));
+            debug.normals(&vertices, &model, 0.5, Vec3::ONE);
+            let points = points(&debug);
+            assert!(points[0].abs_diff_eq(Vec3::ZERO, EPSILON));
+            assert!(points[1].abs_diff_eq(Vec3::new(0.5, 0.0, 0.0), EPSILON));
+        }
+    }
+}
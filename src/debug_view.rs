@@ -0,0 +1,190 @@
+/// Debug views of the render queue: wireframes, normals, UVs, depth and lights one at a time
+pub mod debug_view {
+    use std::fmt::{Display, Formatter};
+    use std::io::Error;
+    use glam::Vec3;
+    use crate::lights::lights::MAX_POINT_LIGHTS;
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// One of the lights of `material.frag`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum LightSource {
+        Directional,
+        Point(usize),
+        Spot
+    }
+
+    /// How `RenderQueue` draws its items, see `RenderQueue::debug_view`. `Normals`, `UvChecker`
+    /// and `Depth` draw every item with one flat program instead of its own, transparent ones
+    /// included. `Wireframe` and `VertexNormals` draw over the shaded scene, and `Light` keeps the
+    /// usual programs but lets a single light through, which needs the `isolateLight` uniforms of
+    /// `material.frag`.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub enum DebugView {
+        #[default]
+        Shaded,
+        Wireframe,
+        Normals,
+        VertexNormals,
+        UvChecker,
+        Depth,
+        Light(LightSource)
+    }
+
+    /// Programs of the debug views and their settings.
+    pub struct DebugViews {
+        pub(crate) surface: ShaderProgram,
+        pub(crate) wireframe: ShaderProgram,
+        pub(crate) vertex_normals: ShaderProgram,
+        pub wire_color: Vec3,
+        pub normal_color: Vec3,
+        /// Length of the vertex normals in world units, whatever the scale of the objects.
+        pub normal_length: f32,
+        /// Checker squares along each texture coordinate from 0 to 1.
+        pub checker_cells: f32,
+        /// Distance from the camera shown black in the depth view.
+        pub depth_range: f32
+    }
+
+    impl LightSource {
+        /// Value of `isolatedLight` in `material.frag`.
+        pub(crate) fn index(&self) -> u32 {
+            match self {
+                LightSource::Directional => 0,
+                LightSource::Point(i) => *i as u32 + 1,
+                LightSource::Spot => MAX_POINT_LIGHTS as u32 + 1
+            }
+        }
+    }
+
+    impl DebugView {
+        /// View after this one, for a key cycling through them all. The lights go from the
+        /// directional one through the first `point_lights` point lights to the spot light.
+        pub fn next(self, point_lights: usize) -> Self {
+            let point_lights = point_lights.min(MAX_POINT_LIGHTS);
+            match self {
+                DebugView::Shaded => DebugView::Wireframe,
+                DebugView::Wireframe => DebugView::Normals,
+                DebugView::Normals => DebugView::VertexNormals,
+                DebugView::VertexNormals => DebugView::UvChecker,
+                DebugView::UvChecker => DebugView::Depth,
+                DebugView::Depth => DebugView::Light(LightSource::Directional),
+                DebugView::Light(LightSource::Directional) if point_lights > 0 => DebugView::Light(LightSource::Point(0)),
+                DebugView::Light(LightSource::Point(i)) if i + 1 < point_lights => DebugView::Light(LightSource::Point(i + 1)),
+                DebugView::Light(LightSource::Directional | LightSource::Point(_)) => DebugView::Light(LightSource::Spot),
+                DebugView::Light(LightSource::Spot) => DebugView::Shaded
+            }
+        }
+
+        /// Value of `debugView` in `debug_view.frag` for the views replacing the programs.
+        pub(crate) fn surface_mode(&self) -> Option<u32> {
+            match self {
+                DebugView::Normals => Some(1),
+                DebugView::UvChecker => Some(2),
+                DebugView::Depth => Some(3),
+                _ => None
+            }
+        }
+    }
+
+    impl Display for DebugView {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            match self {
+                DebugView::Shaded => write!(f, "shaded"),
+                DebugView::Wireframe => write!(f, "wireframe"),
+                DebugView::Normals => write!(f, "normals"),
+                DebugView::VertexNormals => write!(f, "vertex normals"),
+                DebugView::UvChecker => write!(f, "UV checker"),
+                DebugView::Depth => write!(f, "depth"),
+                DebugView::Light(LightSource::Directional) => write!(f, "directional light only"),
+                DebugView::Light(LightSource::Point(i)) => write!(f, "point light {i} only"),
+                DebugView::Light(LightSource::Spot) => write!(f, "spot light only")
+            }
+        }
+    }
+
+    impl DebugViews {
+        pub fn new() -> Result<Self, Error> {
+            let surface = ShaderProgram::from_files(
+                "src/shaders/vertex/lights.vert",
+                "src/shaders/fragment/debug_view.frag"
+            )?;
+            let wireframe = ShaderProgram::from_files(
+                "src/shaders/vertex/light_source.vert",
+                "src/shaders/fragment/wireframe.frag"
+            )?;
+            let vertex_normals = ShaderProgram::from_files_with_geometry(
+                "src/shaders/vertex/vertex_normals.vert",
+                "src/shaders/geometry/vertex_normals.geom",
+                "src/shaders/fragment/debug_lines.frag"
+            )?;
+            Ok(DebugViews {
+                surface,
+                wireframe,
+                vertex_normals,
+                wire_color: Vec3::new(0.1, 1.0, 0.3),
+                normal_color: Vec3::new(0.3, 1.0, 1.0),
+                normal_length: 0.1,
+                checker_cells: 8.0,
+                depth_range: 20.0
+            })
+        }
+    }
+
+    impl Drop for DebugViews {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteProgram(self.surface.shader_program_id);
+                gl::DeleteProgram(self.wireframe.shader_program_id);
+                gl::DeleteProgram(self.vertex_normals.shader_program_id);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn cycles_through_every_view_and_the_point_lights_there_are() {
+            let mut view = DebugView::Shaded;
+            let mut seen = Vec::new();
+            loop {
+                view = view.next(2);
+                if view == DebugView::Shaded {
+                    break;
+                }
+                seen.push(view);
+            }
+            assert_eq!(seen, [
+                DebugView::Wireframe,
+                DebugView::Normals,
+                DebugView::VertexNormals,
+                DebugView::UvChecker,
+                DebugView::Depth,
+                DebugView::Light(LightSource::Directional),
+                DebugView::Light(LightSource::Point(0)),
+                DebugView::Light(LightSource::Point(1)),
+                DebugView::Light(LightSource::Spot)
+            ]);
+            assert_eq!(DebugView::Light(LightSource::Directional).next(0), DebugView::Light(LightSource::Spot));
+        }
+
+        #[test]
+        fn light_indices_match_the_shader() {
+            let shader = include_str!("shaders/fragment/material.frag");
+            let point_lights: u32 = shader.lines()
+                .find_map(|line| line.strip_prefix("#define NR_POINT_LIGHTS "))
+                .and_then(|count| count.trim().parse().ok())
+                .expect("material.frag defines NR_POINT_LIGHTS");
+            assert_eq!(point_lights, MAX_POINT_LIGHTS as u32);
+            assert!(shader.contains("lightShown(0)"));
+            assert!(shader.contains("lightShown(i + 1)"));
+            assert!(shader.contains("lightShown(NR_POINT_LIGHTS + 1)"));
+
+            assert_eq!(LightSource::Directional.index(), 0);
+            assert_eq!(LightSource::Point(0).index(), 1);
+            assert_eq!(LightSource::Spot.index(), point_lights + 1);
+        }
+    }
+}
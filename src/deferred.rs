@@ -0,0 +1,262 @@
+/// Deferred shading: geometry is rendered once into a G-buffer and lighting is computed afterwards
+/// only for the pixels each light can reach
+pub mod deferred {
+    use std::io::Error;
+    use glam::{Mat4, Vec3};
+    use crate::framebuffer::framebuffer::{ColorFormat, DepthStencil, Framebuffer};
+    use crate::lights::lights::{DirectionalLight, PointLight, SpotLight};
+    use crate::mesh::mesh::Mesh;
+    use crate::postprocessing::postprocessing::ScreenQuad;
+    use crate::shaders::shaders::ShaderProgram;
+    use crate::shapes::shapes;
+    use crate::ssao::ssao::Ssao;
+
+    /// What the lighting pass shows: the lit scene or the content of the G-buffer attachments.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum GBufferView {
+        Lit,
+        Position,
+        Normal,
+        Albedo,
+        Specular,
+        Occlusion,
+        All
+    }
+
+    pub struct DeferredRenderer {
+        pub view: GBufferView,
+        pub shininess: f32,
+        pub ssao: Ssao,
+        gbuffer: Framebuffer,
+        geometry_program: ShaderProgram,
+        directional_light_program: ShaderProgram,
+        point_light_program: ShaderProgram,
+        debug_program: ShaderProgram,
+        light_volume: Mesh,
+        quad: ScreenQuad,
+        width: i32,
+        height: i32
+    }
+
+    impl GBufferView {
+        /// Next view, to cycle through all of them from a key binding.
+        pub fn next(&self) -> Self {
+            match self {
+                GBufferView::Lit => GBufferView::Position,
+                GBufferView::Position => GBufferView::Normal,
+                GBufferView::Normal => GBufferView::Albedo,
+                GBufferView::Albedo => GBufferView::Specular,
+                GBufferView::Specular => GBufferView::Occlusion,
+                GBufferView::Occlusion => GBufferView::All,
+                GBufferView::All => GBufferView::Lit
+            }
+        }
+
+        fn shader_index(&self) -> u32 {
+            match self {
+                GBufferView::Lit => 0,
+                GBufferView::Position => 1,
+                GBufferView::Normal => 2,
+                GBufferView::Albedo => 3,
+                GBufferView::Specular => 4,
+                GBufferView::All => 5,
+                GBufferView::Occlusion => 6
+            }
+        }
+    }
+
+    impl DeferredRenderer {
+        const LIGHT_VOLUME_SECTORS: u32 = 16;
+        const LIGHT_VOLUME_STACKS: u32 = 12;
+
+        /// Creates the G-buffer for a window of the given size: view space positions, view space
+        /// normals and albedo with the specular intensity in the alpha channel.
+        pub fn new(width: i32, height: i32) -> Result<Self, Error> {
+            let gbuffer = Framebuffer::new(
+                width,
+                height,
+                1,
+                &[ColorFormat::RGBA16F, ColorFormat::RGBA16F, ColorFormat::RGBA8],
+                DepthStencil::Renderbuffer
+            )?;
+
+            let geometry_program = ShaderProgram::from_files(
+                "src/shaders/vertex/lights.vert",
+                "src/shaders/fragment/gbuffer.frag"
+            )?;
+            geometry_program.use_program();
+            geometry_program.set_int(c"material.diffuse", 0);
+            geometry_program.set_int(c"material.specular", 1);
+
+            let directional_light_program = Self::lighting_program(
+                "src/shaders/vertex/screen_quad.vert",
+                "src/shaders/fragment/deferred_directional_light.frag"
+            )?;
+            let point_light_program = Self::lighting_program(
+                "src/shaders/vertex/light_source.vert",
+                "src/shaders/fragment/deferred_point_light.frag"
+            )?;
+            let debug_program = Self::lighting_program(
+                "src/shaders/vertex/screen_quad.vert",
+                "src/shaders/fragment/gbuffer_debug.frag"
+            )?;
+
+            Ok(DeferredRenderer {
+                view: GBufferView::Lit,
+                shininess: 32.0,
+                ssao: Ssao::new(width, height)?,
+                gbuffer,
+                geometry_program,
+                directional_light_program,
+                point_light_program,
+                debug_program,
+                light_volume: shapes::uv_sphere(Self::LIGHT_VOLUME_SECTORS, Self::LIGHT_VOLUME_STACKS),
+                quad: ScreenQuad::new(),
+                width,
+                height
+            })
+        }
+
+        /// Binds and clears the G-buffer and returns the program to draw the scene with. It uses the
+        /// same vertex shader as the forward path, so the caller sets `model` per object and binds
+        /// the diffuse map to texture unit 0 and the specular map to unit 1.
+        pub fn begin_geometry_pass(&self, view: &Mat4, projection: &Mat4) -> &ShaderProgram {
+            self.gbuffer.bind();
+            unsafe {
+                gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                gl::Enable(gl::DEPTH_TEST);
+                gl::Disable(gl::BLEND);
+            }
+            self.geometry_program.use_program();
+            self.geometry_program.set_mat4(c"view", view);
+            self.geometry_program.set_mat4(c"projection", projection);
+            &self.geometry_program
+        }
+
+        /// Lights the G-buffer into `output`, or into the window when it is `None`. The directional
+        /// and spot lights are applied with a fullscreen pass, while every point light draws a
+        /// sphere sized to the distance it can reach so only the pixels it affects are shaded.
+        /// When SSAO is enabled the ambient term of every light is darkened by the occlusion.
+        /// Afterwards the depth of the G-buffer is copied to the output so forward rendered objects
+        /// can be drawn on top. The output must not be multisampled.
+        pub fn render_lighting(
+            &mut self,
+            view: &Mat4,
+            projection: &Mat4,
+            directional_light: &DirectionalLight,
+            spot_light: &SpotLight,
+            point_lights: &[PointLight],
+            output: Option<&Framebuffer>
+        ) {
+            unsafe { gl::Disable(gl::DEPTH_TEST); }
+            let occlusion = if self.ssao.enabled || self.view == GBufferView::Occlusion {
+                Some(self.ssao.render(
+                    &self.quad,
+                    self.gbuffer.color_texture(0),
+                    self.gbuffer.color_texture(1),
+                    projection
+                ))
+            } else {
+                None
+            };
+
+            self.bind_output(output);
+            unsafe {
+                gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                for i in 0..3 {
+                    gl::ActiveTexture(gl::TEXTURE0 + i);
+                    gl::BindTexture(gl::TEXTURE_2D, self.gbuffer.color_texture(i as usize));
+                }
+                gl::ActiveTexture(gl::TEXTURE3);
+                gl::BindTexture(gl::TEXTURE_2D, occlusion.unwrap_or(0));
+            }
+
+            if self.view != GBufferView::Lit {
+                self.debug_program.use_program();
+                self.debug_program.set_int(c"view", self.view.shader_index());
+                self.quad.draw();
+            } else {
+                let program = &self.directional_light_program;
+                program.use_program();
+                program.set_float(c"shininess", self.shininess);
+                program.set_bool(c"ssaoEnabled", occlusion.is_some());
+                directional_light.set_uniforms(program, "dirLight", view);
+                spot_light.set_uniforms(program, "spotLight", view);
+                self.quad.draw();
+
+                unsafe {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::ONE, gl::ONE);
+                    // only the back faces get drawn, so the volume still covers the pixels when
+                    // the camera is inside of it
+                    gl::Enable(gl::CULL_FACE);
+                    gl::CullFace(gl::FRONT);
+                }
+                let program = &self.point_light_program;
+                program.use_program();
+                program.set_float(c"shininess", self.shininess);
+                program.set_vec2(c"screenSize", &[self.width as f32, self.height as f32]);
+                program.set_bool(c"ssaoEnabled", occlusion.is_some());
+                program.set_mat4(c"view", view);
+                program.set_mat4(c"projection", projection);
+                for light in point_lights {
+                    let model = Mat4::from_translation(light.position) * Mat4::from_scale(Vec3::splat(Self::volume_scale(light)));
+                    program.set_mat4(c"model", &model);
+                    light.set_uniforms(program, "light", view);
+                    self.light_volume.draw(program);
+                }
+                unsafe {
+                    gl::CullFace(gl::BACK);
+                    gl::Disable(gl::CULL_FACE);
+                    gl::Disable(gl::BLEND);
+                }
+            }
+
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::Enable(gl::DEPTH_TEST);
+            }
+            self.gbuffer.blit_depth_to(output);
+            self.bind_output(output);
+        }
+
+        /// Recreates the G-buffer and the occlusion targets for the new window size, keeping them
+        /// while the window is minimised.
+        pub fn resize(&mut self, width: i32, height: i32) -> Result<(), Error> {
+            if width <= 0 || height <= 0 {
+                return Ok(());
+            }
+            self.gbuffer.resize(width, height)?;
+            self.ssao.resize(width, height)?;
+            self.width = width;
+            self.height = height;
+            Ok(())
+        }
+
+        /// The sphere is made of flat faces that sit inside the actual sphere, so it is scaled a bit
+        /// further than the radius of the light to not cut its edges.
+        fn volume_scale(light: &PointLight) -> f32 {
+            let sector_angle = std::f32::consts::PI / Self::LIGHT_VOLUME_SECTORS as f32;
+            light.radius() / sector_angle.cos()
+        }
+
+        fn bind_output(&self, output: Option<&Framebuffer>) {
+            match output {
+                Some(framebuffer) => framebuffer.bind(),
+                None => Framebuffer::bind_default(self.width, self.height)
+            }
+        }
+
+        fn lighting_program(vertex_path: &str, fragment_path: &str) -> Result<ShaderProgram, Error> {
+            let program = ShaderProgram::from_files(vertex_path, fragment_path)?;
+            program.use_program();
+            program.set_int(c"gPosition", 0);
+            program.set_int(c"gNormal", 1);
+            program.set_int(c"gAlbedoSpec", 2);
+            program.set_int(c"ssao", 3);
+            Ok(program)
+        }
+    }
+}
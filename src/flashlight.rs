@@ -15,6 +15,10 @@ pub mod flashlight {
             self.is_on = !self.is_on;
         }
         
+        pub fn is_on(&self) -> bool {
+            self.is_on
+        }
+
         pub fn get_light(&self) -> Vec<f32> {
             if self.is_on {
                 return vec![1.0, 1.0, 1.0].to_owned()
@@ -0,0 +1,392 @@
+/// Frame times on the CPU and GPU times of named render passes over the last frames, to measure
+/// what a change does to performance
+pub mod frame_stats {
+    use std::collections::VecDeque;
+    use std::fmt::{Display, Formatter};
+    use std::time::Instant;
+
+    /// Frames the statistics cover by default, a few seconds at the usual frame rates.
+    pub const DEFAULT_FRAMES: usize = 240;
+    /// Timer queries per pass. A query is read a few frames after it was issued, once the GPU is
+    /// done with it, so timing never waits for the GPU.
+    const QUERIES_PER_PASS: usize = 4;
+
+    /// The last `capacity` values of a time, in milliseconds.
+    #[derive(Debug, Clone)]
+    pub struct Samples {
+        values: VecDeque<f32>,
+        capacity: usize
+    }
+
+    /// Average and percentiles of `Samples`, in milliseconds.
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    pub struct Summary {
+        pub average: f32,
+        pub median: f32,
+        pub p95: f32,
+        pub p99: f32,
+        pub max: f32
+    }
+
+    struct PassTimer {
+        name: String,
+        queries: [u32; QUERIES_PER_PASS],
+        free: Vec<u32>,
+        /// Queries issued and not read yet, oldest first
+        in_flight: VecDeque<u32>,
+        /// Queries in flight when the samples were reset, read and thrown away
+        discard: usize,
+        samples: Samples
+    }
+
+    /// Records the time between frames, the CPU time spent from `begin_frame` to `end_frame` and
+    /// the GPU time of each pass between `begin_pass` and `end_pass`, measured with
+    /// `GL_TIME_ELAPSED` queries. Only one such query can run at a time, so passes don't nest:
+    /// beginning a pass ends the one before. A pass whose queries are all still in flight isn't
+    /// timed that frame.
+    pub struct FrameStats {
+        frame: Samples,
+        cpu: Samples,
+        passes: Vec<PassTimer>,
+        /// Index of the pass being timed and its query, if one was free
+        open: Option<(usize, Option<u32>)>,
+        last_frame: Option<f32>,
+        frame_start: Option<Instant>,
+        since_report: f32,
+        frames: usize
+    }
+
+    impl Samples {
+        pub fn new(capacity: usize) -> Self {
+            Samples {
+                values: VecDeque::with_capacity(capacity),
+                capacity: capacity.max(1)
+            }
+        }
+
+        /// Adds a value, dropping the oldest one when full.
+        pub fn push(&mut self, value: f32) {
+            if self.values.len() == self.capacity {
+                self.values.pop_front();
+            }
+            self.values.push_back(value);
+        }
+
+        pub fn len(&self) -> usize {
+            self.values.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.values.is_empty()
+        }
+
+        pub fn clear(&mut self) {
+            self.values.clear();
+        }
+
+        pub fn latest(&self) -> Option<f32> {
+            self.values.back().copied()
+        }
+
+        /// Average of the values, 0 when there are none.
+        pub fn average(&self) -> f32 {
+            if self.values.is_empty() {
+                return 0.0;
+            }
+            self.values.iter().sum::<f32>() / self.values.len() as f32
+        }
+
+        /// Smallest value at least `percent` percent of the values are lower than or equal to, 0
+        /// when there are none.
+        pub fn percentile(&self, percent: f32) -> f32 {
+            percentile(&self.sorted(), percent)
+        }
+
+        pub fn summary(&self) -> Summary {
+            let sorted = self.sorted();
+            Summary {
+                average: self.average(),
+                median: percentile(&sorted, 50.0),
+                p95: percentile(&sorted, 95.0),
+                p99: percentile(&sorted, 99.0),
+                max: sorted.last().copied().unwrap_or(0.0)
+            }
+        }
+
+        fn sorted(&self) -> Vec<f32> {
+            let mut sorted: Vec<f32> = self.values.iter().copied().collect();
+            sorted.sort_by(f32::total_cmp);
+            sorted
+        }
+    }
+
+    /// Nearest rank percentile of sorted values.
+    fn percentile(sorted: &[f32], percent: f32) -> f32 {
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        let rank = (percent.clamp(0.0, 100.0) / 100.0 * sorted.len() as f32).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+
+    impl Display for Summary {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "{:.2} ms avg, {:.2} median, {:.2} p95, {:.2} p99, {:.2} max",
+                self.average, self.median, self.p95, self.p99, self.max
+            )
+        }
+    }
+
+    impl PassTimer {
+        fn new(name: &str, frames: usize) -> Self {
+            let mut queries = [0; QUERIES_PER_PASS];
+            unsafe {
+                gl::GenQueries(QUERIES_PER_PASS as i32, queries.as_mut_ptr());
+            }
+            PassTimer {
+                name: name.to_string(),
+                queries,
+                free: queries.to_vec(),
+                in_flight: VecDeque::with_capacity(QUERIES_PER_PASS),
+                discard: 0,
+                samples: Samples::new(frames)
+            }
+        }
+
+        /// Reads the queries the GPU is done with. They finish in the order they were issued, so
+        /// the first one still running ends the search.
+        fn collect(&mut self) {
+            while let Some(&query) = self.in_flight.front() {
+                let mut available: i32 = 0;
+                let mut nanoseconds: u64 = 0;
+                unsafe {
+                    gl::GetQueryObjectiv(query, gl::QUERY_RESULT_AVAILABLE, &mut available);
+                    if available == 0 {
+                        break;
+                    }
+                    gl::GetQueryObjectui64v(query, gl::QUERY_RESULT, &mut nanoseconds);
+                }
+                self.in_flight.pop_front();
+                self.free.push(query);
+                if self.discard > 0 {
+                    self.discard -= 1;
+                } else {
+                    self.samples.push(nanoseconds as f32 / 1_000_000.0);
+                }
+            }
+        }
+    }
+
+    impl FrameStats {
+        /// Statistics over the last `frames` frames.
+        pub fn new(frames: usize) -> Self {
+            FrameStats {
+                frame: Samples::new(frames),
+                cpu: Samples::new(frames),
+                passes: Vec::new(),
+                open: None,
+                last_frame: None,
+                frame_start: None,
+                since_report: 0.0,
+                frames
+            }
+        }
+
+        /// Starts a frame at `now` seconds and returns the time since the last one, or since 0 for
+        /// the first frame, which isn't recorded as it includes the loading. Also picks up the GPU
+        /// times that came in since the last frame.
+        pub fn begin_frame(&mut self, now: f32) -> f32 {
+            let delta_time = now - self.last_frame.unwrap_or(0.0);
+            if self.last_frame.is_some() {
+                self.frame.push(delta_time * 1000.0);
+                self.since_report += delta_time;
+            }
+            self.last_frame = Some(now);
+            self.frame_start = Some(Instant::now());
+            for pass in &mut self.passes {
+                pass.collect();
+            }
+            delta_time
+        }
+
+        /// Ends the frame, and the pass still open, before swapping the buffers so the CPU time
+        /// doesn't include waiting for the display.
+        pub fn end_frame(&mut self) {
+            self.end_pass();
+            if let Some(start) = self.frame_start.take() {
+                self.cpu.push(start.elapsed().as_secs_f32() * 1000.0);
+            }
+        }
+
+        /// Times the GPU work submitted from now until `end_pass` under `name`.
+        pub fn begin_pass(&mut self, name: &str) {
+            self.end_pass();
+            let index = match self.passes.iter().position(|pass| pass.name == name) {
+                Some(index) => index,
+                None => {
+                    self.passes.push(PassTimer::new(name, self.frames));
+                    self.passes.len() - 1
+                }
+            };
+            let query = self.passes[index].free.pop();
+            if let Some(query) = query {
+                unsafe {
+                    gl::BeginQuery(gl::TIME_ELAPSED, query);
+                }
+            }
+            self.open = Some((index, query));
+        }
+
+        pub fn end_pass(&mut self) {
+            if let Some((index, query)) = self.open.take()
+                && let Some(query) = query {
+                unsafe {
+                    gl::EndQuery(gl::TIME_ELAPSED);
+                }
+                self.passes[index].in_flight.push_back(query);
+            }
+        }
+
+        /// Milliseconds between the starts of consecutive frames.
+        pub fn frame_time(&self) -> &Samples {
+            &self.frame
+        }
+
+        /// Milliseconds from `begin_frame` to `end_frame`.
+        pub fn cpu_time(&self) -> &Samples {
+            &self.cpu
+        }
+
+        /// Milliseconds the GPU spent on the pass, None for a pass that was never begun.
+        pub fn gpu_time(&self, name: &str) -> Option<&Samples> {
+            self.passes.iter().find(|pass| pass.name == name).map(|pass| &pass.samples)
+        }
+
+        /// Names and GPU times of the passes, in the order they were first begun.
+        pub fn passes(&self) -> impl Iterator<Item = (&str, &Samples)> {
+            self.passes.iter().map(|pass| (pass.name.as_str(), &pass.samples))
+        }
+
+        /// Average frames per second, 0 before the second frame.
+        pub fn frame_rate(&self) -> f32 {
+            let average = self.frame.average();
+            if average > 0.0 { 1000.0 / average } else { 0.0 }
+        }
+
+        /// Forgets every time recorded so far, e.g. after switching between two ways of rendering
+        /// to compare them. Queries still in flight are thrown away when they come in.
+        pub fn reset(&mut self) {
+            self.frame.clear();
+            self.cpu.clear();
+            for pass in &mut self.passes {
+                pass.samples.clear();
+                pass.discard = pass.in_flight.len();
+            }
+            self.since_report = 0.0;
+        }
+
+        /// The statistics as text, when at least `interval` seconds of frames went by since the
+        /// last report, for logging them every so often.
+        pub fn report(&mut self, interval: f32) -> Option<String> {
+            if self.since_report < interval {
+                return None;
+            }
+            self.since_report = 0.0;
+            Some(self.to_string())
+        }
+
+        /// One line for the frame time, one for the CPU time and one per pass, then the sum of the
+        /// average pass times.
+        pub fn lines(&self) -> Vec<String> {
+            let mut lines = vec![
+                format!("{:.0} FPS, frame {}", self.frame_rate(), self.frame.summary()),
+                format!("CPU {}", self.cpu.summary())
+            ];
+            lines.extend(self.passes.iter().map(|pass| format!("GPU {} {}", pass.name, pass.samples.summary())));
+            if !self.passes.is_empty() {
+                let total: f32 = self.passes.iter().map(|pass| pass.samples.average()).sum();
+                lines.push(format!("GPU total {:.2} ms avg", total));
+            }
+            lines
+        }
+    }
+
+    impl Default for FrameStats {
+        fn default() -> Self {
+            FrameStats::new(DEFAULT_FRAMES)
+        }
+    }
+
+    impl Display for FrameStats {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.lines().join("\n"))
+        }
+    }
+
+    impl Drop for FrameStats {
+        fn drop(&mut self) {
+            for pass in &self.passes {
+                unsafe {
+                    gl::DeleteQueries(QUERIES_PER_PASS as i32, pass.queries.as_ptr());
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn keeps_the_last_values_only() {
+            let mut samples = Samples::new(3);
+            for value in [10.0, 1.0, 2.0, 3.0] {
+                samples.push(value);
+            }
+            assert_eq!(samples.len(), 3);
+            assert_eq!(samples.latest(), Some(3.0));
+            assert_eq!(samples.average(), 2.0);
+            assert_eq!(Samples::new(3).summary(), Summary::default());
+        }
+
+        #[test]
+        fn percentiles_are_nearest_rank() {
+            let mut samples = Samples::new(100);
+            // pushed out of order, as frame times come
+            for value in (1..=100).rev() {
+                samples.push(value as f32);
+            }
+            let summary = samples.summary();
+            assert_eq!(summary.median, 50.0);
+            assert_eq!(summary.p95, 95.0);
+            assert_eq!(summary.p99, 99.0);
+            assert_eq!(summary.max, 100.0);
+            assert_eq!(samples.percentile(0.0), 1.0);
+        }
+
+        #[test]
+        fn records_frame_times_and_reports_every_interval() {
+            let mut stats = FrameStats::new(10);
+            assert_eq!(stats.begin_frame(2.0), 2.0);
+            stats.end_frame();
+            assert!(stats.frame_time().is_empty());
+            assert_eq!(stats.cpu_time().len(), 1);
+
+            stats.begin_frame(2.5);
+            assert!(stats.report(1.0).is_none());
+            stats.begin_frame(3.0);
+            assert_eq!(stats.frame_time().average(), 500.0);
+            assert_eq!(stats.frame_rate(), 2.0);
+            let report = stats.report(1.0).unwrap();
+            assert!(report.starts_with("2 FPS, frame 500.00 ms avg"));
+            assert!(stats.report(1.0).is_none());
+
+            stats.reset();
+            assert!(stats.frame_time().is_empty());
+            assert_eq!(stats.frame_rate(), 0.0);
+            assert!(stats.gpu_time("scene").is_none());
+        }
+    }
+}
@@ -0,0 +1,547 @@
+/// Framebuffer objects for rendering offscreen instead of rendering directly to the default
+/// framebuffer of the window
+pub mod framebuffer {
+    use std::io::Error;
+    use std::ptr;
+    use image::{imageops, RgbaImage};
+
+    /// Formats available for the colour attachments of a `Framebuffer`. Floating point formats
+    /// keep values outside the `[0, 1]` range, which is what HDR and G-buffer targets need.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum ColorFormat {
+        RGBA8,
+        R16F,
+        RG16F,
+        RGB16F,
+        RGBA16F,
+        R32F,
+        RGB32F,
+        RGBA32F,
+        /// One unsigned integer per pixel, such as object ids, read back exactly instead of
+        /// being filtered or normalized.
+        R32UI
+    }
+
+    /// Depth and stencil storage of a `Framebuffer`. A renderbuffer is enough when the depth is only
+    /// used for depth testing, a texture is needed when a later pass has to sample it.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum DepthStencil {
+        None,
+        Renderbuffer,
+        Texture
+    }
+
+    pub struct Framebuffer {
+        pub fbo: u32,
+        width: i32,
+        height: i32,
+        samples: i32,
+        color_formats: Vec<ColorFormat>,
+        color_attachments: Vec<u32>,
+        depth_stencil: DepthStencil,
+        depth_stencil_attachment: u32
+    }
+
+    impl ColorFormat {
+        pub fn internal_format(&self) -> u32 {
+            match self {
+                ColorFormat::RGBA8 => gl::RGBA8,
+                ColorFormat::R16F => gl::R16F,
+                ColorFormat::RG16F => gl::RG16F,
+                ColorFormat::RGB16F => gl::RGB16F,
+                ColorFormat::RGBA16F => gl::RGBA16F,
+                ColorFormat::R32F => gl::R32F,
+                ColorFormat::RGB32F => gl::RGB32F,
+                ColorFormat::RGBA32F => gl::RGBA32F,
+                ColorFormat::R32UI => gl::R32UI
+            }
+        }
+
+        pub fn format(&self) -> u32 {
+            match self {
+                ColorFormat::R16F | ColorFormat::R32F => gl::RED,
+                ColorFormat::RG16F => gl::RG,
+                ColorFormat::RGB16F | ColorFormat::RGB32F => gl::RGB,
+                ColorFormat::RGBA8 | ColorFormat::RGBA16F | ColorFormat::RGBA32F => gl::RGBA,
+                ColorFormat::R32UI => gl::RED_INTEGER
+            }
+        }
+
+        pub fn data_type(&self) -> u32 {
+            match self {
+                ColorFormat::RGBA8 => gl::UNSIGNED_BYTE,
+                ColorFormat::R32UI => gl::UNSIGNED_INT,
+                _ => gl::FLOAT
+            }
+        }
+
+        /// Integer textures can only be sampled without filtering.
+        pub fn is_integer(&self) -> bool {
+            matches!(self, ColorFormat::R32UI)
+        }
+    }
+
+    impl Framebuffer {
+        /// Creates a framebuffer of the given size with one colour attachment per entry of
+        /// `color_formats`, bound to `COLOR_ATTACHMENT0..n` in the same order. When `samples` is
+        /// greater than 1 every attachment is multisampled, and the framebuffer has to be resolved
+        /// into a single sampled one with `blit_to` before its textures can be sampled or read back.
+        /// Returns an `Error` describing the problem if the framebuffer is not complete.
+        pub fn new(
+            width: i32,
+            height: i32,
+            samples: i32,
+            color_formats: &[ColorFormat],
+            depth_stencil: DepthStencil
+        ) -> Result<Self, Error> {
+            let mut fbo = 0;
+            unsafe {
+                gl::GenFramebuffers(1, &mut fbo);
+            }
+            let mut framebuffer = Framebuffer {
+                fbo,
+                width,
+                height,
+                samples: samples.max(1),
+                color_formats: color_formats.to_vec(),
+                color_attachments: Vec::new(),
+                depth_stencil,
+                depth_stencil_attachment: 0
+            };
+            framebuffer.create_attachments()?;
+            Ok(framebuffer)
+        }
+
+        /// Binds the framebuffer for drawing and reading and sets the viewport to its size.
+        pub fn bind(&self) {
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+                gl::Viewport(0, 0, self.width, self.height);
+            }
+        }
+
+        /// Binds back the default framebuffer of the window, restoring the viewport to the given
+        /// window size.
+        pub fn bind_default(width: i32, height: i32) {
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                gl::Viewport(0, 0, width, height);
+            }
+        }
+
+        /// Recreates all attachments with the new size, keeping their formats. Meant to be called
+        /// from the `FramebufferSize` window event. Does nothing if the size did not change, nor
+        /// for the zero size of a minimised window, which no framebuffer can be complete at.
+        pub fn resize(&mut self, width: i32, height: i32) -> Result<(), Error> {
+            if (width == self.width && height == self.height) || width <= 0 || height <= 0 {
+                return Ok(());
+            }
+            self.delete_attachments();
+            self.width = width;
+            self.height = height;
+            self.create_attachments()
+        }
+
+        /// Copies every colour attachment into the attachment with the same index of `target`,
+        /// together with the depth and stencil buffers when both framebuffers have them. This is the
+        /// way to resolve a multisampled framebuffer, in which case both sizes should match.
+        pub fn blit_to(&self, target: &Framebuffer) {
+            unsafe {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+                gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target.fbo);
+                let attachments = self.color_attachments.len().min(target.color_attachments.len());
+                for i in 0..attachments {
+                    let attachment = gl::COLOR_ATTACHMENT0 + i as u32;
+                    gl::ReadBuffer(attachment);
+                    gl::DrawBuffers(1, &attachment);
+                    gl::BlitFramebuffer(
+                        0, 0, self.width, self.height,
+                        0, 0, target.width, target.height,
+                        gl::COLOR_BUFFER_BIT,
+                        self.blit_filter(i)
+                    );
+                }
+                if self.depth_stencil != DepthStencil::None && target.depth_stencil != DepthStencil::None {
+                    gl::BlitFramebuffer(
+                        0, 0, self.width, self.height,
+                        0, 0, target.width, target.height,
+                        gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT,
+                        gl::NEAREST
+                    );
+                }
+                if attachments > 0 {
+                    gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+                }
+                // blitting changed the draw buffers of the target, put all of them back
+                target.set_draw_buffers();
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            }
+        }
+
+        /// Copies the given colour attachment to the default framebuffer, scaling it to the window
+        /// size. Handy to show an offscreen target without a fullscreen quad.
+        pub fn blit_to_default(&self, attachment: usize, width: i32, height: i32) {
+            unsafe {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+                gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0 + attachment as u32);
+                gl::BlitFramebuffer(
+                    0, 0, self.width, self.height,
+                    0, 0, width, height,
+                    gl::COLOR_BUFFER_BIT,
+                    self.blit_filter(attachment)
+                );
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            }
+        }
+
+        /// Copies only the depth and stencil buffers into `target`, or into the window when it is
+        /// `None`. Both must have the same size and a `DEPTH24_STENCIL8` depth buffer, which is what
+        /// the default framebuffer usually has.
+        pub fn blit_depth_to(&self, target: Option<&Framebuffer>) {
+            let (target_fbo, width, height) = match target {
+                Some(framebuffer) => (framebuffer.fbo, framebuffer.width, framebuffer.height),
+                None => (0, self.width, self.height)
+            };
+            unsafe {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+                gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target_fbo);
+                gl::BlitFramebuffer(
+                    0, 0, self.width, self.height,
+                    0, 0, width, height,
+                    gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT,
+                    gl::NEAREST
+                );
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            }
+        }
+
+        /// Reads back the given colour attachment as an 8-bit image, flipped so the first row is the
+        /// top of the image. Floating point attachments are clamped to `[0, 1]` by OpenGL.
+        /// Multisampled framebuffers can not be read directly and have to be resolved first.
+        pub fn read_pixels(&self, attachment: usize) -> Result<RgbaImage, Error> {
+            if self.samples > 1 {
+                return Err(Error::other(
+                    "ERROR::FRAMEBUFFER::READ_PIXELS\nMultisampled framebuffers must be resolved with blit_to before reading them"
+                ));
+            }
+            if attachment >= self.color_attachments.len() {
+                return Err(Error::other(
+                    format!("ERROR::FRAMEBUFFER::READ_PIXELS\nColour attachment {attachment} does not exist, the framebuffer has {}",
+                            self.color_attachments.len())
+                ));
+            }
+            if self.color_formats[attachment].is_integer() {
+                return Err(Error::other(
+                    format!("ERROR::FRAMEBUFFER::READ_PIXELS\nColour attachment {attachment} holds integers, see read_integer")
+                ));
+            }
+            unsafe {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0 + attachment as u32);
+            }
+            let image = Self::read_bound_pixels(self.width, self.height);
+            unsafe {
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+            }
+            Ok(image)
+        }
+
+        /// Reads back one pixel of an `R32UI` attachment, `x` and `y` from the bottom left corner
+        /// like OpenGL. Same limits as `read_pixels`.
+        pub fn read_integer(&self, attachment: usize, x: i32, y: i32) -> Result<u32, Error> {
+            if self.samples > 1 {
+                return Err(Error::other(
+                    "ERROR::FRAMEBUFFER::READ_INTEGER\nMultisampled framebuffers must be resolved with blit_to before reading them"
+                ));
+            }
+            if self.color_formats.get(attachment) != Some(&ColorFormat::R32UI) {
+                return Err(Error::other(
+                    format!("ERROR::FRAMEBUFFER::READ_INTEGER\nColour attachment {attachment} is not an R32UI attachment")
+                ));
+            }
+            let mut value: u32 = 0;
+            unsafe {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0 + attachment as u32);
+                gl::ReadPixels(x, y, 1, 1, gl::RED_INTEGER, gl::UNSIGNED_INT, (&mut value as *mut u32).cast());
+                gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+            }
+            Ok(value)
+        }
+
+        /// Reads back the default framebuffer of the window with the same conventions as
+        /// `read_pixels`.
+        pub fn read_default_pixels(width: i32, height: i32) -> RgbaImage {
+            unsafe {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+            }
+            Self::read_bound_pixels(width, height)
+        }
+
+        /// Texture id of the given colour attachment, to be bound for sampling in a later pass.
+        pub fn color_texture(&self, attachment: usize) -> u32 {
+            self.color_attachments[attachment]
+        }
+
+        /// Texture id of the depth/stencil attachment, only available with `DepthStencil::Texture`.
+        pub fn depth_stencil_texture(&self) -> Option<u32> {
+            match self.depth_stencil {
+                DepthStencil::Texture => Some(self.depth_stencil_attachment),
+                _ => None
+            }
+        }
+
+        pub fn width(&self) -> i32 {
+            self.width
+        }
+
+        pub fn height(&self) -> i32 {
+            self.height
+        }
+
+        pub fn samples(&self) -> i32 {
+            self.samples
+        }
+
+        /// Integer attachments can't be filtered, blitting them with `LINEAR` is an error.
+        fn blit_filter(&self, attachment: usize) -> u32 {
+            if self.color_formats.get(attachment).is_some_and(ColorFormat::is_integer) { gl::NEAREST } else { gl::LINEAR }
+        }
+
+        fn read_bound_pixels(width: i32, height: i32) -> RgbaImage {
+            let mut image = RgbaImage::new(width as u32, height as u32);
+            // RGBA8 rows are 4 byte aligned, as the default PACK_ALIGNMENT expects
+            unsafe {
+                gl::ReadPixels(
+                    0,
+                    0,
+                    width,
+                    height,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    image.as_mut_ptr().cast()
+                );
+            }
+            // OpenGL starts at the bottom left corner while images start at the top left
+            imageops::flip_vertical_in_place(&mut image);
+            image
+        }
+
+        fn create_attachments(&mut self) -> Result<(), Error> {
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            }
+            for (i, format) in self.color_formats.iter().enumerate() {
+                let texture = self.create_color_texture(format);
+                unsafe {
+                    gl::FramebufferTexture2D(
+                        gl::FRAMEBUFFER,
+                        gl::COLOR_ATTACHMENT0 + i as u32,
+                        self.texture_target(),
+                        texture,
+                        0
+                    );
+                }
+                self.color_attachments.push(texture);
+            }
+            self.depth_stencil_attachment = match self.depth_stencil {
+                DepthStencil::None => 0,
+                DepthStencil::Renderbuffer => self.create_depth_stencil_renderbuffer(),
+                DepthStencil::Texture => self.create_depth_stencil_texture()
+            };
+            self.set_draw_buffers();
+
+            let status = self.check_status();
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            }
+            status
+        }
+
+        fn create_color_texture(&self, format: &ColorFormat) -> u32 {
+            let mut texture = 0;
+            unsafe {
+                gl::GenTextures(1, &mut texture);
+                if self.samples > 1 {
+                    gl::BindTexture(gl::TEXTURE_2D_MULTISAMPLE, texture);
+                    gl::TexImage2DMultisample(
+                        gl::TEXTURE_2D_MULTISAMPLE,
+                        self.samples,
+                        format.internal_format(),
+                        self.width,
+                        self.height,
+                        gl::TRUE
+                    );
+                    gl::BindTexture(gl::TEXTURE_2D_MULTISAMPLE, 0);
+                } else {
+                    gl::BindTexture(gl::TEXTURE_2D, texture);
+                    gl::TexImage2D(
+                        gl::TEXTURE_2D,
+                        0,
+                        format.internal_format() as i32,
+                        self.width,
+                        self.height,
+                        0,
+                        format.format(),
+                        format.data_type(),
+                        ptr::null()
+                    );
+                    let filter = if format.is_integer() { gl::NEAREST } else { gl::LINEAR };
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as i32);
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as i32);
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+                    gl::BindTexture(gl::TEXTURE_2D, 0);
+                }
+            }
+            texture
+        }
+
+        fn create_depth_stencil_renderbuffer(&self) -> u32 {
+            let mut rbo = 0;
+            unsafe {
+                gl::GenRenderbuffers(1, &mut rbo);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, rbo);
+                if self.samples > 1 {
+                    gl::RenderbufferStorageMultisample(
+                        gl::RENDERBUFFER,
+                        self.samples,
+                        gl::DEPTH24_STENCIL8,
+                        self.width,
+                        self.height
+                    );
+                } else {
+                    gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, self.width, self.height);
+                }
+                gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+                gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, rbo);
+            }
+            rbo
+        }
+
+        fn create_depth_stencil_texture(&self) -> u32 {
+            let mut texture = 0;
+            unsafe {
+                gl::GenTextures(1, &mut texture);
+                if self.samples > 1 {
+                    gl::BindTexture(gl::TEXTURE_2D_MULTISAMPLE, texture);
+                    gl::TexImage2DMultisample(
+                        gl::TEXTURE_2D_MULTISAMPLE,
+                        self.samples,
+                        gl::DEPTH24_STENCIL8,
+                        self.width,
+                        self.height,
+                        gl::TRUE
+                    );
+                    gl::BindTexture(gl::TEXTURE_2D_MULTISAMPLE, 0);
+                } else {
+                    gl::BindTexture(gl::TEXTURE_2D, texture);
+                    gl::TexImage2D(
+                        gl::TEXTURE_2D,
+                        0,
+                        gl::DEPTH24_STENCIL8 as i32,
+                        self.width,
+                        self.height,
+                        0,
+                        gl::DEPTH_STENCIL,
+                        gl::UNSIGNED_INT_24_8,
+                        ptr::null()
+                    );
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+                    gl::BindTexture(gl::TEXTURE_2D, 0);
+                }
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_STENCIL_ATTACHMENT,
+                    self.texture_target(),
+                    texture,
+                    0
+                );
+            }
+            texture
+        }
+
+        fn set_draw_buffers(&self) {
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+                if self.color_attachments.is_empty() {
+                    gl::DrawBuffer(gl::NONE);
+                    gl::ReadBuffer(gl::NONE);
+                } else {
+                    let attachments: Vec<u32> = (0..self.color_attachments.len() as u32)
+                        .map(|i| gl::COLOR_ATTACHMENT0 + i)
+                        .collect();
+                    gl::DrawBuffers(attachments.len() as i32, attachments.as_ptr());
+                }
+            }
+        }
+
+        fn check_status(&self) -> Result<(), Error> {
+            let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+            if status == gl::FRAMEBUFFER_COMPLETE {
+                return Ok(());
+            }
+            let (name, description) = match status {
+                gl::FRAMEBUFFER_UNDEFINED =>
+                    ("FRAMEBUFFER_UNDEFINED", "the default framebuffer does not exist"),
+                gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT =>
+                    ("FRAMEBUFFER_INCOMPLETE_ATTACHMENT", "an attachment is not complete, check its size and format"),
+                gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT =>
+                    ("FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT", "the framebuffer has no attachments"),
+                gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER =>
+                    ("FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER", "a draw buffer points to a missing attachment"),
+                gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER =>
+                    ("FRAMEBUFFER_INCOMPLETE_READ_BUFFER", "the read buffer points to a missing attachment"),
+                gl::FRAMEBUFFER_UNSUPPORTED =>
+                    ("FRAMEBUFFER_UNSUPPORTED", "the combination of attachment formats is not supported by the driver"),
+                gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE =>
+                    ("FRAMEBUFFER_INCOMPLETE_MULTISAMPLE", "attachments use different sample counts"),
+                gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS =>
+                    ("FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS", "attachments are not all layered or all non layered"),
+                _ => ("UNKNOWN", "unknown framebuffer status")
+            };
+            let error_message: String =
+                format!("ERROR::FRAMEBUFFER::NOT_COMPLETE\n{name} ({status:#x}): {description} \
+                         [{}x{}, {} samples, colour attachments {:?}, depth/stencil {:?}]",
+                        self.width, self.height, self.samples, self.color_formats, self.depth_stencil);
+            Err(Error::other(error_message))
+        }
+
+        fn texture_target(&self) -> u32 {
+            if self.samples > 1 {
+                gl::TEXTURE_2D_MULTISAMPLE
+            } else {
+                gl::TEXTURE_2D
+            }
+        }
+
+        fn delete_attachments(&mut self) {
+            unsafe {
+                gl::DeleteTextures(self.color_attachments.len() as i32, self.color_attachments.as_ptr());
+                match self.depth_stencil {
+                    DepthStencil::None => {},
+                    DepthStencil::Renderbuffer => gl::DeleteRenderbuffers(1, &self.depth_stencil_attachment),
+                    DepthStencil::Texture => gl::DeleteTextures(1, &self.depth_stencil_attachment)
+                }
+            }
+            self.color_attachments.clear();
+            self.depth_stencil_attachment = 0;
+        }
+    }
+
+    impl Drop for Framebuffer {
+        fn drop(&mut self) {
+            self.delete_attachments();
+            unsafe {
+                gl::DeleteFramebuffers(1, &self.fbo);
+            }
+        }
+    }
+}
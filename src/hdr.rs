@@ -0,0 +1,389 @@
+/// HDR rendering: the scene is lit into a floating point target so bright lights keep their
+/// intensity, then bloom, exposure and tone mapping bring it back to the displayable range
+pub mod hdr {
+    use std::io::Error;
+    use crate::framebuffer::framebuffer::{ColorFormat, DepthStencil, Framebuffer};
+    use crate::postprocessing::postprocessing::ScreenQuad;
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// Operators mapping HDR colours to the `[0, 1]` range. `Clamp` cuts everything above 1 like
+    /// rendering straight to the default framebuffer does, to compare against the others.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum ToneMapping {
+        Clamp,
+        Reinhard,
+        Aces,
+        Exposure
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum BloomBlur {
+        Gaussian,
+        DualKawase
+    }
+
+    /// Settings of the bloom pass. Everything brighter than `threshold` bleeds into its
+    /// surroundings, `knee` softens the transition around the threshold. `iterations` is the number
+    /// of horizontal plus vertical passes for the Gaussian blur and the number of mip levels for
+    /// the dual Kawase blur.
+    pub struct Bloom {
+        pub enabled: bool,
+        pub threshold: f32,
+        pub knee: f32,
+        pub intensity: f32,
+        pub blur: BloomBlur,
+        pub iterations: u32
+    }
+
+    /// Settings of the automatic exposure. The average luminance of the scene is brought to
+    /// `key_value`, adapting over time like the eye does at `adaptation_speed`.
+    pub struct AutoExposure {
+        pub enabled: bool,
+        pub key_value: f32,
+        pub adaptation_speed: f32,
+        pub min_exposure: f32,
+        pub max_exposure: f32
+    }
+
+    pub struct HdrRenderer {
+        pub tone_mapping: ToneMapping,
+        pub exposure: f32,
+        pub gamma: f32,
+        pub bloom: Bloom,
+        pub auto_exposure: AutoExposure,
+        scene: Framebuffer,
+        resolved_scene: Option<Framebuffer>,
+        bloom_targets: [Framebuffer; 2],
+        kawase_chain: Vec<Framebuffer>,
+        luminance_chain: Vec<Framebuffer>,
+        adapted_luminance: [Framebuffer; 2],
+        current_luminance: usize,
+        reset_luminance: bool,
+        quad: ScreenQuad,
+        tone_mapping_program: ShaderProgram,
+        bright_pass_program: ShaderProgram,
+        gaussian_blur_program: ShaderProgram,
+        kawase_down_program: ShaderProgram,
+        kawase_up_program: ShaderProgram,
+        luminance_program: ShaderProgram,
+        downsample_program: ShaderProgram,
+        adaptation_program: ShaderProgram,
+        width: i32,
+        height: i32
+    }
+
+    impl ToneMapping {
+        /// Next operator, to cycle through all of them from a key binding.
+        pub fn next(&self) -> Self {
+            match self {
+                ToneMapping::Clamp => ToneMapping::Reinhard,
+                ToneMapping::Reinhard => ToneMapping::Aces,
+                ToneMapping::Aces => ToneMapping::Exposure,
+                ToneMapping::Exposure => ToneMapping::Clamp
+            }
+        }
+
+        fn shader_index(&self) -> u32 {
+            match self {
+                ToneMapping::Clamp => 0,
+                ToneMapping::Reinhard => 1,
+                ToneMapping::Aces => 2,
+                ToneMapping::Exposure => 3
+            }
+        }
+    }
+
+    impl Default for Bloom {
+        fn default() -> Self {
+            Bloom {
+                enabled: true,
+                threshold: 1.0,
+                knee: 0.5,
+                intensity: 0.6,
+                blur: BloomBlur::DualKawase,
+                iterations: 5
+            }
+        }
+    }
+
+    impl Default for AutoExposure {
+        fn default() -> Self {
+            AutoExposure {
+                enabled: false,
+                key_value: 0.18,
+                adaptation_speed: 1.5,
+                min_exposure: 0.1,
+                max_exposure: 10.0
+            }
+        }
+    }
+
+    impl HdrRenderer {
+        const VERTEX_SHADER: &'static str = "src/shaders/vertex/screen_quad.vert";
+        /// Levels of the dual Kawase chain, starting at a quarter of the window size.
+        const KAWASE_LEVELS: usize = 6;
+
+        /// Creates a floating point scene target of the given size with `samples` samples per
+        /// pixel, together with the smaller targets used by bloom and automatic exposure.
+        pub fn new(width: i32, height: i32, samples: i32) -> Result<Self, Error> {
+            let scene = Framebuffer::new(width, height, samples, &[ColorFormat::RGBA16F], DepthStencil::Renderbuffer)?;
+            let resolved_scene = if samples > 1 {
+                Some(Framebuffer::new(width, height, 1, &[ColorFormat::RGBA16F], DepthStencil::None)?)
+            } else {
+                None
+            };
+            let (bloom_width, bloom_height) = ((width / 2).max(1), (height / 2).max(1));
+            let bloom_targets = [
+                Framebuffer::new(bloom_width, bloom_height, 1, &[ColorFormat::RGBA16F], DepthStencil::None)?,
+                Framebuffer::new(bloom_width, bloom_height, 1, &[ColorFormat::RGBA16F], DepthStencil::None)?
+            ];
+            let adapted_luminance = [
+                Framebuffer::new(1, 1, 1, &[ColorFormat::R32F], DepthStencil::None)?,
+                Framebuffer::new(1, 1, 1, &[ColorFormat::R32F], DepthStencil::None)?
+            ];
+
+            let tone_mapping_program = Self::load_program("hdr_tone_mapping")?;
+            tone_mapping_program.use_program();
+            tone_mapping_program.set_int(c"hdrTexture", 0);
+            tone_mapping_program.set_int(c"bloomTexture", 1);
+            tone_mapping_program.set_int(c"luminanceTexture", 2);
+            let adaptation_program = Self::load_program("hdr_adapt_luminance")?;
+            adaptation_program.use_program();
+            adaptation_program.set_int(c"averageLuminance", 0);
+            adaptation_program.set_int(c"previousLuminance", 1);
+
+            Ok(HdrRenderer {
+                tone_mapping: ToneMapping::Aces,
+                exposure: 1.0,
+                gamma: 2.2,
+                bloom: Bloom::default(),
+                auto_exposure: AutoExposure::default(),
+                scene,
+                resolved_scene,
+                bloom_targets,
+                kawase_chain: Self::create_kawase_chain(width, height)?,
+                luminance_chain: Self::create_luminance_chain(width, height)?,
+                adapted_luminance,
+                current_luminance: 0,
+                reset_luminance: true,
+                quad: ScreenQuad::new(),
+                tone_mapping_program,
+                bright_pass_program: Self::load_program("hdr_bright_pass")?,
+                gaussian_blur_program: Self::load_program("hdr_gaussian_blur")?,
+                kawase_down_program: Self::load_program("hdr_kawase_down")?,
+                kawase_up_program: Self::load_program("hdr_kawase_up")?,
+                luminance_program: Self::load_program("hdr_luminance")?,
+                downsample_program: Self::load_program("hdr_downsample")?,
+                adaptation_program,
+                width,
+                height
+            })
+        }
+
+        /// Binds the floating point scene target, everything drawn until `render` is called can
+        /// go above 1.
+        pub fn begin_scene(&self) {
+            self.scene.bind();
+        }
+
+        /// Runs bloom and automatic exposure if enabled, then tone maps and gamma corrects the scene
+        /// into `output`, or into the window when it is `None`. `delta_time` is the time since the
+        /// last frame in seconds, used to adapt the exposure.
+        pub fn render(&mut self, delta_time: f32, output: Option<&Framebuffer>) {
+            let depth_test = unsafe { gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE };
+            unsafe { gl::Disable(gl::DEPTH_TEST); }
+
+            let scene_texture = match &self.resolved_scene {
+                Some(resolved_scene) => {
+                    self.scene.blit_to(resolved_scene);
+                    resolved_scene.color_texture(0)
+                },
+                None => self.scene.color_texture(0)
+            };
+            if self.auto_exposure.enabled {
+                self.adapt_luminance(scene_texture, delta_time);
+            } else {
+                self.reset_luminance = true;
+            }
+            let bloom_texture = if self.bloom.enabled {
+                self.render_bloom(scene_texture)
+            } else {
+                0
+            };
+
+            match output {
+                Some(framebuffer) => framebuffer.bind(),
+                None => Framebuffer::bind_default(self.width, self.height)
+            }
+            let program = &self.tone_mapping_program;
+            program.use_program();
+            program.set_int(c"toneMapping", self.tone_mapping.shader_index());
+            program.set_float(c"exposure", self.exposure);
+            program.set_float(c"gamma", self.gamma);
+            program.set_bool(c"bloom", self.bloom.enabled);
+            program.set_float(c"bloomIntensity", self.bloom.intensity);
+            program.set_bool(c"autoExposure", self.auto_exposure.enabled);
+            program.set_float(c"keyValue", self.auto_exposure.key_value);
+            program.set_float(c"minExposure", self.auto_exposure.min_exposure);
+            program.set_float(c"maxExposure", self.auto_exposure.max_exposure);
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, scene_texture);
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, bloom_texture);
+                gl::ActiveTexture(gl::TEXTURE2);
+                gl::BindTexture(gl::TEXTURE_2D, self.adapted_luminance[self.current_luminance].color_texture(0));
+            }
+            self.quad.draw();
+
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                if depth_test {
+                    gl::Enable(gl::DEPTH_TEST);
+                }
+            }
+        }
+
+        /// Recreates every target for the new window size, keeping them while the window is
+        /// minimised.
+        pub fn resize(&mut self, width: i32, height: i32) -> Result<(), Error> {
+            if width <= 0 || height <= 0 {
+                return Ok(());
+            }
+            self.scene.resize(width, height)?;
+            if let Some(resolved_scene) = &mut self.resolved_scene {
+                resolved_scene.resize(width, height)?;
+            }
+            for target in &mut self.bloom_targets {
+                target.resize((width / 2).max(1), (height / 2).max(1))?;
+            }
+            self.kawase_chain = Self::create_kawase_chain(width, height)?;
+            self.luminance_chain = Self::create_luminance_chain(width, height)?;
+            self.width = width;
+            self.height = height;
+            Ok(())
+        }
+
+        /// Average luminance of the scene is computed by taking the log luminance at half the size
+        /// of the window and halving it until a single pixel is left. The result is then blended
+        /// with the previous frame so the exposure changes smoothly.
+        fn adapt_luminance(&mut self, scene_texture: u32, delta_time: f32) {
+            self.luminance_program.use_program();
+            Self::draw_into(&self.quad, &self.luminance_chain[0], scene_texture);
+
+            self.downsample_program.use_program();
+            for i in 1..self.luminance_chain.len() {
+                let source = &self.luminance_chain[i - 1];
+                self.downsample_program.set_vec2(c"texelSize", &Self::texel_size(source));
+                Self::draw_into(&self.quad, &self.luminance_chain[i], source.color_texture(0));
+            }
+
+            let previous = self.current_luminance;
+            self.current_luminance = 1 - previous;
+            self.adaptation_program.use_program();
+            self.adaptation_program.set_float(c"deltaTime", delta_time);
+            self.adaptation_program.set_float(c"adaptationSpeed", self.auto_exposure.adaptation_speed);
+            self.adaptation_program.set_bool(c"reset", self.reset_luminance);
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, self.adapted_luminance[previous].color_texture(0));
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+            let average = self.luminance_chain.last().unwrap().color_texture(0);
+            Self::draw_into(&self.quad, &self.adapted_luminance[self.current_luminance], average);
+            self.reset_luminance = false;
+        }
+
+        /// Extracts the bright parts of the scene at half resolution and blurs them, returning the
+        /// texture holding the result.
+        fn render_bloom(&self, scene_texture: u32) -> u32 {
+            self.bright_pass_program.use_program();
+            self.bright_pass_program.set_float(c"threshold", self.bloom.threshold);
+            self.bright_pass_program.set_float(c"knee", self.bloom.knee);
+            Self::draw_into(&self.quad, &self.bloom_targets[0], scene_texture);
+
+            match self.bloom.blur {
+                BloomBlur::Gaussian => {
+                    let program = &self.gaussian_blur_program;
+                    program.use_program();
+                    program.set_vec2(c"texelSize", &Self::texel_size(&self.bloom_targets[0]));
+                    for i in 0..(self.bloom.iterations * 2) as usize {
+                        program.set_bool(c"horizontal", i % 2 == 0);
+                        let source = self.bloom_targets[i % 2].color_texture(0);
+                        Self::draw_into(&self.quad, &self.bloom_targets[(i + 1) % 2], source);
+                    }
+                    self.bloom_targets[0].color_texture(0)
+                },
+                BloomBlur::DualKawase => {
+                    let levels = (self.bloom.iterations as usize).clamp(1, self.kawase_chain.len());
+                    self.kawase_down_program.use_program();
+                    let mut source = &self.bloom_targets[0];
+                    for target in &self.kawase_chain[..levels] {
+                        self.kawase_down_program.set_vec2(c"texelSize", &Self::texel_size(source));
+                        Self::draw_into(&self.quad, target, source.color_texture(0));
+                        source = target;
+                    }
+
+                    self.kawase_up_program.use_program();
+                    for i in (0..levels).rev() {
+                        let target = if i == 0 { &self.bloom_targets[1] } else { &self.kawase_chain[i - 1] };
+                        self.kawase_up_program.set_vec2(c"texelSize", &Self::texel_size(source));
+                        Self::draw_into(&self.quad, target, source.color_texture(0));
+                        source = target;
+                    }
+                    self.bloom_targets[1].color_texture(0)
+                }
+            }
+        }
+
+        /// Draws the fullscreen quad with the program in use into `target`, sampling `texture` from
+        /// texture unit 0.
+        fn draw_into(quad: &ScreenQuad, target: &Framebuffer, texture: u32) {
+            target.bind();
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+            }
+            quad.draw();
+        }
+
+        fn texel_size(framebuffer: &Framebuffer) -> [f32; 2] {
+            [1.0 / framebuffer.width() as f32, 1.0 / framebuffer.height() as f32]
+        }
+
+        fn create_kawase_chain(width: i32, height: i32) -> Result<Vec<Framebuffer>, Error> {
+            let mut chain = Vec::new();
+            let (mut level_width, mut level_height) = (width / 2, height / 2);
+            for _ in 0..Self::KAWASE_LEVELS {
+                level_width = (level_width / 2).max(1);
+                level_height = (level_height / 2).max(1);
+                chain.push(Framebuffer::new(level_width, level_height, 1, &[ColorFormat::RGBA16F], DepthStencil::None)?);
+            }
+            Ok(chain)
+        }
+
+        fn create_luminance_chain(width: i32, height: i32) -> Result<Vec<Framebuffer>, Error> {
+            let mut chain = Vec::new();
+            let (mut level_width, mut level_height) = ((width / 2).max(1), (height / 2).max(1));
+            loop {
+                chain.push(Framebuffer::new(level_width, level_height, 1, &[ColorFormat::R32F], DepthStencil::None)?);
+                if level_width == 1 && level_height == 1 {
+                    break;
+                }
+                level_width = (level_width / 2).max(1);
+                level_height = (level_height / 2).max(1);
+            }
+            Ok(chain)
+        }
+
+        fn load_program(name: &str) -> Result<ShaderProgram, Error> {
+            let program = ShaderProgram::from_files(
+                Self::VERTEX_SHADER.to_string(),
+                format!("src/shaders/fragment/{name}.frag")
+            )?;
+            program.use_program();
+            program.set_int(c"screenTexture", 0);
+            Ok(program)
+        }
+    }
+}
@@ -0,0 +1,141 @@
+/// Offscreen OpenGL context and render target, to render scenes without a display or window system
+pub mod headless {
+    use std::io::Error;
+    use std::path::Path;
+    use image::RgbaImage;
+    use khronos_egl as egl;
+    use crate::framebuffer::framebuffer::{ColorFormat, DepthStencil, Framebuffer};
+
+    /// `EGL_PLATFORM_SURFACELESS_MESA`, a display that needs neither a window system nor a GPU.
+    const PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+    /// Something that can be drawn frame by frame into whatever framebuffer is bound.
+    pub trait Scene {
+        /// Draws the frame at `time` seconds, `delta_time` after the previous one. The target is
+        /// already bound with a `width` x `height` viewport and must not be changed.
+        fn render(&mut self, time: f32, delta_time: f32, width: i32, height: i32);
+    }
+
+    /// OpenGL 3.3 core context without any surface, current on the thread that created it.
+    pub struct HeadlessContext {
+        egl: egl::DynamicInstance<egl::EGL1_5>,
+        display: egl::Display,
+        context: egl::Context
+    }
+
+    /// Colour and depth target the scenes are rendered into, multisampled if asked to.
+    pub struct HeadlessRenderer {
+        target: Framebuffer,
+        resolved: Option<Framebuffer>
+    }
+
+    impl HeadlessContext {
+        /// Creates the context and loads the OpenGL functions for it. Mesa's surfaceless platform
+        /// is tried first, which runs on llvmpipe on a machine without GPU, then the default
+        /// display. Setting `LIBGL_ALWAYS_SOFTWARE=1` forces Mesa to render on the CPU.
+        pub fn new() -> Result<Self, Error> {
+            let egl = unsafe { egl::DynamicInstance::<egl::EGL1_5>::load_required() }
+                .map_err(|e| Error::other(format!("ERROR::HEADLESS::EGL_NOT_FOUND\n{e}")))?;
+
+            let display = unsafe { egl.get_platform_display(PLATFORM_SURFACELESS_MESA, egl::DEFAULT_DISPLAY, &[egl::ATTRIB_NONE]) }
+                .ok()
+                .or_else(|| unsafe { egl.get_display(egl::DEFAULT_DISPLAY) })
+                .ok_or_else(|| Error::other("ERROR::HEADLESS::NO_DISPLAY"))?;
+            egl.initialize(display).map_err(|e| egl_error("INITIALIZE", e))?;
+
+            // the default surface type is a window, which the surfaceless platform doesn't have,
+            // and the colour and depth buffers all live in framebuffer objects anyway
+            let config_attributes = [
+                egl::SURFACE_TYPE, egl::PBUFFER_BIT,
+                egl::RENDERABLE_TYPE, egl::OPENGL_BIT,
+                egl::NONE
+            ];
+            let config = egl.choose_first_config(display, &config_attributes)
+                .map_err(|e| egl_error("CHOOSE_CONFIG", e))?
+                .ok_or_else(|| Error::other("ERROR::HEADLESS::NO_CONFIG\nNo configuration supports desktop OpenGL"))?;
+
+            egl.bind_api(egl::OPENGL_API).map_err(|e| egl_error("BIND_API", e))?;
+            let context_attributes = [
+                egl::CONTEXT_MAJOR_VERSION, 3,
+                egl::CONTEXT_MINOR_VERSION, 3,
+                egl::CONTEXT_OPENGL_PROFILE_MASK, egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                egl::NONE
+            ];
+            let context = egl.create_context(display, config, None, &context_attributes)
+                .map_err(|e| egl_error("CREATE_CONTEXT", e))?;
+            // no surface at all, everything is drawn into framebuffer objects
+            egl.make_current(display, None, None, Some(context))
+                .map_err(|e| egl_error("MAKE_CURRENT", e))?;
+
+            gl::load_with(|symbol| match egl.get_proc_address(symbol) {
+                Some(f) => f as *const _,
+                None => std::ptr::null(),
+            });
+            Ok(HeadlessContext { egl, display, context })
+        }
+    }
+
+    impl Drop for HeadlessContext {
+        fn drop(&mut self) {
+            let _ = self.egl.make_current(self.display, None, None, None);
+            let _ = self.egl.destroy_context(self.display, self.context);
+            let _ = self.egl.terminate(self.display);
+        }
+    }
+
+    impl HeadlessRenderer {
+        /// Creates a `width` x `height` RGBA8 target with `samples` samples per pixel. A context
+        /// must be current, see `HeadlessContext`.
+        pub fn new(width: i32, height: i32, samples: i32) -> Result<Self, Error> {
+            let target = Framebuffer::new(width, height, samples, &[ColorFormat::RGBA8], DepthStencil::Renderbuffer)?;
+            let resolved = if samples > 1 {
+                Some(Framebuffer::new(width, height, 1, &[ColorFormat::RGBA8], DepthStencil::None)?)
+            } else {
+                None
+            };
+            Ok(HeadlessRenderer { target, resolved })
+        }
+
+        pub fn width(&self) -> i32 {
+            self.target.width()
+        }
+
+        pub fn height(&self) -> i32 {
+            self.target.height()
+        }
+
+        /// Renders `frames` frames of `scene` at a fixed time step, the first one at time 0, and
+        /// returns the last one as an image with its first row at the top.
+        pub fn render(&self, scene: &mut dyn Scene, frames: u32, time_step: f32) -> Result<RgbaImage, Error> {
+            let (width, height) = (self.width(), self.height());
+            for frame in 0..frames.max(1) {
+                self.target.bind();
+                scene.render(frame as f32 * time_step, time_step, width, height);
+            }
+            let image = match &self.resolved {
+                Some(resolved) => {
+                    self.target.blit_to(resolved);
+                    resolved.read_pixels(0)?
+                }
+                None => self.target.read_pixels(0)?
+            };
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            }
+            Ok(image)
+        }
+
+        /// Same as `render`, writing the last frame as a PNG at `path`.
+        pub fn render_to_png<P: AsRef<Path>>(&self, scene: &mut dyn Scene, frames: u32, time_step: f32, path: P) -> Result<(), Error> {
+            let image = self.render(scene, frames, time_step)?;
+            if let Some(parent) = path.as_ref().parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            image.save(path.as_ref()).map_err(Error::other)
+        }
+    }
+
+    fn egl_error(call: &str, error: egl::Error) -> Error {
+        Error::other(format!("ERROR::HEADLESS::{call}\n{error}"))
+    }
+}
@@ -0,0 +1,510 @@
+/// Image based lighting: an equirectangular HDR environment is turned into the cubemaps and the
+/// lookup table the PBR shader needs to light objects with it
+pub mod ibl {
+    use std::fs::File;
+    use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Write};
+    use std::path::{Path, PathBuf};
+    use glam::{Mat4, Vec3};
+    use crate::mesh::mesh::Mesh;
+    use crate::postprocessing::postprocessing::ScreenQuad;
+    use crate::shaders::shaders::ShaderProgram;
+    use crate::shapes::shapes;
+
+    /// Texture units the maps are bound to by `set_uniforms`, after the ones used by the material.
+    pub const IRRADIANCE_UNIT: u32 = 6;
+    pub const PREFILTER_UNIT: u32 = 7;
+    pub const BRDF_LUT_UNIT: u32 = 8;
+
+    pub struct Environment {
+        environment_map: u32,
+        irradiance_map: u32,
+        prefiltered_map: u32,
+        brdf_lut: u32,
+        skybox_program: ShaderProgram,
+        cube: Mesh
+    }
+
+    /// Framebuffer with a depth renderbuffer used to render into the faces of the cubemaps and the
+    /// lookup table during the precompute step.
+    struct Capture {
+        fbo: u32,
+        rbo: u32
+    }
+
+    impl Environment {
+        pub const ENVIRONMENT_SIZE: i32 = 512;
+        pub const IRRADIANCE_SIZE: i32 = 32;
+        pub const PREFILTER_SIZE: i32 = 128;
+        /// Mip levels of the prefiltered map, the roughness goes from 0 at level 0 to 1 at the last one.
+        pub const PREFILTER_MIP_LEVELS: i32 = 5;
+        pub const BRDF_LUT_SIZE: i32 = 512;
+        const CACHE_MAGIC: &'static [u8; 4] = b"IBL1";
+
+        /// Loads the equirectangular `.hdr` image at `hdr_path` and computes every map from it. When
+        /// `cache_dir` is given, the maps are read from `<cache_dir>/<image name>.ibl` if that file
+        /// is newer than the image, and written there after being computed otherwise.
+        pub fn from_equirectangular<P: AsRef<Path>>(hdr_path: P, cache_dir: Option<&Path>) -> Result<Self, Error> {
+            let hdr_path = hdr_path.as_ref();
+            let cache_path = cache_dir.map(|dir| Self::cache_path(dir, hdr_path));
+            let skybox_program = ShaderProgram::from_files(
+                "src/shaders/vertex/skybox.vert",
+                "src/shaders/fragment/skybox.frag"
+            )?;
+            skybox_program.use_program();
+            skybox_program.set_int(c"environmentMap", 0);
+
+            let mut environment = Environment {
+                environment_map: create_cubemap(Self::ENVIRONMENT_SIZE, true),
+                irradiance_map: create_cubemap(Self::IRRADIANCE_SIZE, false),
+                prefiltered_map: create_cubemap(Self::PREFILTER_SIZE, true),
+                brdf_lut: create_lut(Self::BRDF_LUT_SIZE),
+                skybox_program,
+                cube: shapes::cube()
+            };
+            unsafe { gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS); }
+
+            if let Some(cache_path) = &cache_path
+                && Self::is_cache_valid(cache_path, hdr_path)
+            {
+                match environment.read_cache(cache_path) {
+                    Ok(()) => return Ok(environment),
+                    Err(e) => eprintln!("ERROR::IBL::CACHE_READ_FAILED {}: {}", cache_path.display(), e)
+                }
+            }
+
+            environment.precompute(hdr_path)?;
+            if let Some(cache_path) = &cache_path
+                && let Err(e) = environment.write_cache(cache_path)
+            {
+                eprintln!("ERROR::IBL::CACHE_WRITE_FAILED {}: {}", cache_path.display(), e);
+            }
+            Ok(environment)
+        }
+
+        pub fn environment_map(&self) -> u32 {
+            self.environment_map
+        }
+
+        pub fn irradiance_map(&self) -> u32 {
+            self.irradiance_map
+        }
+
+        pub fn prefiltered_map(&self) -> u32 {
+            self.prefiltered_map
+        }
+
+        pub fn brdf_lut(&self) -> u32 {
+            self.brdf_lut
+        }
+
+        /// Binds the maps for `pbr.frag` and sets `useIbl`, along with the inverse of the view
+        /// matrix since the cubemaps are looked up with world space directions.
+        pub fn set_uniforms(&self, program: &ShaderProgram, view: &Mat4) {
+            program.set_bool(c"useIbl", true);
+            program.set_float(c"prefilterMaxLod", (Self::PREFILTER_MIP_LEVELS - 1) as f32);
+            program.set_mat4(c"inverseView", &view.inverse());
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0 + IRRADIANCE_UNIT);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.irradiance_map);
+                gl::ActiveTexture(gl::TEXTURE0 + PREFILTER_UNIT);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.prefiltered_map);
+                gl::ActiveTexture(gl::TEXTURE0 + BRDF_LUT_UNIT);
+                gl::BindTexture(gl::TEXTURE_2D, self.brdf_lut);
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+        }
+
+        /// Draws the environment behind everything already in the depth buffer, so it goes last.
+        pub fn draw_skybox(&self, view: &Mat4, projection: &Mat4) {
+            self.skybox_program.use_program();
+            self.skybox_program.set_mat4(c"view", view);
+            self.skybox_program.set_mat4(c"projection", projection);
+            unsafe {
+                gl::DepthFunc(gl::LEQUAL);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.environment_map);
+            }
+            self.cube.draw(&self.skybox_program);
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+                gl::DepthFunc(gl::LESS);
+            }
+        }
+
+        fn precompute(&mut self, hdr_path: &Path) -> Result<(), Error> {
+            let image = image::open(hdr_path)
+                .map_err(|e| Error::other(format!("ERROR::IBL::HDR_LOAD_FAILED {}: {}", hdr_path.display(), e)))?
+                .flipv()
+                .into_rgb32f();
+            let mut equirectangular_map = 0;
+            unsafe {
+                gl::GenTextures(1, &mut equirectangular_map);
+                gl::BindTexture(gl::TEXTURE_2D, equirectangular_map);
+                gl::TexImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    gl::RGB16F as i32,
+                    image.width() as i32,
+                    image.height() as i32,
+                    0,
+                    gl::RGB,
+                    gl::FLOAT,
+                    image.as_ptr().cast()
+                );
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            }
+
+            let mut viewport = [0; 4];
+            let depth_test = unsafe {
+                gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+                gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE
+            };
+            unsafe {
+                gl::Enable(gl::DEPTH_TEST);
+                gl::DepthFunc(gl::LEQUAL);
+            }
+            let capture = Capture::new();
+            let projection = Mat4::perspective_rh_gl(90.0_f32.to_radians(), 1.0, 0.1, 10.0);
+
+            // equirectangular image to the faces of the environment cubemap
+            let program = ShaderProgram::from_files(
+                "src/shaders/vertex/cubemap.vert",
+                "src/shaders/fragment/equirect_to_cubemap.frag"
+            )?;
+            program.use_program();
+            program.set_int(c"equirectangularMap", 0);
+            program.set_mat4(c"projection", &projection);
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, equirectangular_map);
+            }
+            capture.render_cubemap(self.environment_map, Self::ENVIRONMENT_SIZE, 0, &program, &self.cube);
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.environment_map);
+                gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+                gl::DeleteTextures(1, &equirectangular_map);
+                gl::DeleteProgram(program.shader_program_id);
+            }
+
+            // diffuse irradiance
+            let program = ShaderProgram::from_files(
+                "src/shaders/vertex/cubemap.vert",
+                "src/shaders/fragment/irradiance_convolution.frag"
+            )?;
+            program.use_program();
+            program.set_int(c"environmentMap", 0);
+            program.set_mat4(c"projection", &projection);
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.environment_map);
+            }
+            capture.render_cubemap(self.irradiance_map, Self::IRRADIANCE_SIZE, 0, &program, &self.cube);
+            unsafe {
+                gl::DeleteProgram(program.shader_program_id);
+            }
+
+            // specular, one mip level per roughness step
+            let program = ShaderProgram::from_files(
+                "src/shaders/vertex/cubemap.vert",
+                "src/shaders/fragment/prefilter.frag"
+            )?;
+            program.use_program();
+            program.set_int(c"environmentMap", 0);
+            program.set_mat4(c"projection", &projection);
+            program.set_float(c"resolution", Self::ENVIRONMENT_SIZE as f32);
+            for mip in 0..Self::PREFILTER_MIP_LEVELS {
+                let roughness = mip as f32 / (Self::PREFILTER_MIP_LEVELS - 1) as f32;
+                program.set_float(c"roughness", roughness);
+                capture.render_cubemap(self.prefiltered_map, Self::PREFILTER_SIZE >> mip, mip, &program, &self.cube);
+            }
+            unsafe {
+                gl::DeleteProgram(program.shader_program_id);
+            }
+
+            // BRDF integration lookup table
+            let program = ShaderProgram::from_files(
+                "src/shaders/vertex/screen_quad.vert",
+                "src/shaders/fragment/brdf_lut.frag"
+            )?;
+            program.use_program();
+            capture.attach(gl::TEXTURE_2D, self.brdf_lut, Self::BRDF_LUT_SIZE, 0);
+            unsafe {
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            }
+            ScreenQuad::new().draw();
+
+            unsafe {
+                gl::DeleteProgram(program.shader_program_id);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+                gl::DepthFunc(gl::LESS);
+                if !depth_test {
+                    gl::Disable(gl::DEPTH_TEST);
+                }
+            }
+            Ok(())
+        }
+
+        fn cache_path(cache_dir: &Path, hdr_path: &Path) -> PathBuf {
+            let name = hdr_path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+            cache_dir.join(format!("{name}.ibl"))
+        }
+
+        fn is_cache_valid(cache_path: &Path, hdr_path: &Path) -> bool {
+            let modified = |path: &Path| path.metadata().and_then(|metadata| metadata.modified());
+            match (modified(cache_path), modified(hdr_path)) {
+                (Ok(cache), Ok(hdr)) => cache >= hdr,
+                _ => false
+            }
+        }
+
+        /// Sizes the cache was written with, so a cache from different settings is not used.
+        fn cache_header() -> [i32; 5] {
+            [
+                Self::ENVIRONMENT_SIZE,
+                Self::IRRADIANCE_SIZE,
+                Self::PREFILTER_SIZE,
+                Self::PREFILTER_MIP_LEVELS,
+                Self::BRDF_LUT_SIZE
+            ]
+        }
+
+        /// The cache holds the header followed by the texels as little endian floats: the six faces
+        /// of the environment map and of the irradiance map, the faces of every prefiltered mip
+        /// level and finally the lookup table. The environment mips are generated again on load.
+        fn write_cache(&self, cache_path: &Path) -> Result<(), Error> {
+            if let Some(parent) = cache_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut writer = BufWriter::new(File::create(cache_path)?);
+            writer.write_all(Self::CACHE_MAGIC)?;
+            for value in Self::cache_header() {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+
+            // rows of RGB and RG floats are tightly packed, put back the caller's alignment when done
+            let mut pack_alignment = 0;
+            unsafe {
+                gl::GetIntegerv(gl::PACK_ALIGNMENT, &mut pack_alignment);
+                gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            }
+            let mut write_texels = |target: u32, level: i32, size: i32, format: u32, channels: usize| -> Result<(), Error> {
+                let mut texels = vec![0.0_f32; (size * size) as usize * channels];
+                unsafe {
+                    gl::GetTexImage(target, level, format, gl::FLOAT, texels.as_mut_ptr().cast());
+                }
+                let bytes: Vec<u8> = texels.iter().flat_map(|texel| texel.to_le_bytes()).collect();
+                writer.write_all(&bytes)
+            };
+            let mut write_all = || -> Result<(), Error> {
+                for (cubemap, size, levels) in self.cached_cubemaps() {
+                    unsafe { gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap); }
+                    for level in 0..levels {
+                        for face in 0..6 {
+                            write_texels(gl::TEXTURE_CUBE_MAP_POSITIVE_X + face, level, size >> level, gl::RGB, 3)?;
+                        }
+                    }
+                }
+                unsafe {
+                    gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+                    gl::BindTexture(gl::TEXTURE_2D, self.brdf_lut);
+                }
+                write_texels(gl::TEXTURE_2D, 0, Self::BRDF_LUT_SIZE, gl::RG, 2)
+            };
+            let written = write_all();
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+                gl::PixelStorei(gl::PACK_ALIGNMENT, pack_alignment);
+            }
+            written?;
+            writer.flush()
+        }
+
+        fn read_cache(&mut self, cache_path: &Path) -> Result<(), Error> {
+            let mut reader = BufReader::new(File::open(cache_path)?);
+            let mut magic = [0; 4];
+            reader.read_exact(&mut magic)?;
+            let mut header = [0; 5];
+            for value in header.iter_mut() {
+                let mut bytes = [0; 4];
+                reader.read_exact(&mut bytes)?;
+                *value = i32::from_le_bytes(bytes);
+            }
+            if &magic != Self::CACHE_MAGIC || header != Self::cache_header() {
+                return Err(Error::new(ErrorKind::InvalidData, "ERROR::IBL::CACHE_OUTDATED"));
+            }
+
+            let mut read_texels = |size: i32, channels: usize| -> Result<Vec<f32>, Error> {
+                let mut bytes = vec![0; (size * size) as usize * channels * size_of::<f32>()];
+                reader.read_exact(&mut bytes)?;
+                Ok(bytes.chunks_exact(4).map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect())
+            };
+            for (cubemap, size, levels) in self.cached_cubemaps() {
+                unsafe { gl::BindTexture(gl::TEXTURE_CUBE_MAP, cubemap); }
+                for level in 0..levels {
+                    for face in 0..6 {
+                        let texels = read_texels(size >> level, 3)?;
+                        unsafe {
+                            gl::TexSubImage2D(
+                                gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                                level,
+                                0,
+                                0,
+                                size >> level,
+                                size >> level,
+                                gl::RGB,
+                                gl::FLOAT,
+                                texels.as_ptr().cast()
+                            );
+                        }
+                    }
+                }
+            }
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.environment_map);
+                gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+                gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+            }
+            let texels = read_texels(Self::BRDF_LUT_SIZE, 2)?;
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, self.brdf_lut);
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    Self::BRDF_LUT_SIZE,
+                    Self::BRDF_LUT_SIZE,
+                    gl::RG,
+                    gl::FLOAT,
+                    texels.as_ptr().cast()
+                );
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+            Ok(())
+        }
+
+        /// (texture, size, mip levels) of the cubemaps stored in the cache, in order.
+        fn cached_cubemaps(&self) -> [(u32, i32, i32); 3] {
+            [
+                (self.environment_map, Self::ENVIRONMENT_SIZE, 1),
+                (self.irradiance_map, Self::IRRADIANCE_SIZE, 1),
+                (self.prefiltered_map, Self::PREFILTER_SIZE, Self::PREFILTER_MIP_LEVELS)
+            ]
+        }
+    }
+
+    impl Drop for Environment {
+        fn drop(&mut self) {
+            unsafe {
+                let textures = [self.environment_map, self.irradiance_map, self.prefiltered_map, self.brdf_lut];
+                gl::DeleteTextures(textures.len() as i32, textures.as_ptr());
+            }
+        }
+    }
+
+    impl Capture {
+        fn new() -> Self {
+            let (mut fbo, mut rbo) = (0, 0);
+            unsafe {
+                gl::GenFramebuffers(1, &mut fbo);
+                gl::GenRenderbuffers(1, &mut rbo);
+                gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, rbo);
+                gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, rbo);
+            }
+            Capture { fbo, rbo }
+        }
+
+        /// Renders `cube` once per face of `cubemap` at mip `level`, looking from its center.
+        fn render_cubemap(&self, cubemap: u32, size: i32, level: i32, program: &ShaderProgram, cube: &Mesh) {
+            // cubemap faces are seen from inside with Y pointing down, as the specification defines them
+            let views = [
+                (Vec3::X, Vec3::NEG_Y),
+                (Vec3::NEG_X, Vec3::NEG_Y),
+                (Vec3::Y, Vec3::Z),
+                (Vec3::NEG_Y, Vec3::NEG_Z),
+                (Vec3::Z, Vec3::NEG_Y),
+                (Vec3::NEG_Z, Vec3::NEG_Y)
+            ];
+            for (face, (direction, up)) in views.into_iter().enumerate() {
+                self.attach(gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32, cubemap, size, level);
+                program.set_mat4(c"view", &Mat4::look_at_rh(Vec3::ZERO, direction, up));
+                unsafe {
+                    gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                }
+                cube.draw(program);
+            }
+        }
+
+        fn attach(&self, target: u32, texture: u32, size: i32, level: i32) {
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, self.rbo);
+                gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, size, size);
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, target, texture, level);
+                gl::Viewport(0, 0, size, size);
+            }
+        }
+    }
+
+    impl Drop for Capture {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteFramebuffers(1, &self.fbo);
+                gl::DeleteRenderbuffers(1, &self.rbo);
+            }
+        }
+    }
+
+    fn create_cubemap(size: i32, mipmaps: bool) -> u32 {
+        let mut texture = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture);
+            for face in 0..6 {
+                gl::TexImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                    0,
+                    gl::RGB16F as i32,
+                    size,
+                    size,
+                    0,
+                    gl::RGB,
+                    gl::FLOAT,
+                    std::ptr::null()
+                );
+            }
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as i32);
+            let min_filter = if mipmaps { gl::LINEAR_MIPMAP_LINEAR } else { gl::LINEAR };
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, min_filter as i32);
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            if mipmaps {
+                // allocates every level so they can be rendered into or uploaded one by one
+                gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+            }
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+        texture
+    }
+
+    fn create_lut(size: i32) -> u32 {
+        let mut texture = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RG16F as i32, size, size, 0, gl::RG, gl::FLOAT, std::ptr::null());
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        texture
+    }
+}
@@ -4,3 +4,31 @@ pub mod camera;
 pub mod textures;
 pub mod flashlight;
 pub mod mesh;
+pub mod framebuffer;
+pub mod postprocessing;
+pub mod hdr;
+pub mod lights;
+pub mod shapes;
+pub mod deferred;
+pub mod ssao;
+pub mod pbr;
+pub mod ibl;
+pub mod material;
+pub mod headless;
+pub mod scenes;
+pub mod capture;
+pub mod scene_file;
+pub mod scene_graph;
+pub mod model;
+pub mod renderer;
+pub mod bounds;
+pub mod simplify;
+pub mod lod;
+pub mod oit;
+pub mod outline;
+pub mod ray;
+pub mod picking;
+pub mod debug_draw;
+pub mod debug_view;
+pub mod text;
+pub mod frame_stats;
\ No newline at end of file
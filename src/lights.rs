@@ -0,0 +1,148 @@
+/// Light types shared by the lighting shaders. All shaders light in view space, so positions and
+/// directions are kept in world space here and transformed with the view matrix when uploaded.
+pub mod lights {
+    use std::ffi::CString;
+    use glam::{Mat4, Vec3};
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// Point lights the Phong lighting shaders have room for, `NR_POINT_LIGHTS` in `lights.frag`
+    /// and `material.frag`.
+    pub const MAX_POINT_LIGHTS: usize = 4;
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct DirectionalLight {
+        pub direction: Vec3,
+        pub ambient: Vec3,
+        pub diffuse: Vec3,
+        pub specular: Vec3
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct PointLight {
+        pub position: Vec3,
+        pub ambient: Vec3,
+        pub diffuse: Vec3,
+        pub specular: Vec3,
+        pub constant: f32,
+        pub linear: f32,
+        pub quadratic: f32
+    }
+
+    /// Cone shaped light, the cut off angles are in degrees.
+    #[derive(Debug, Clone, Copy)]
+    pub struct SpotLight {
+        pub position: Vec3,
+        pub direction: Vec3,
+        pub cut_off: f32,
+        pub outer_cut_off: f32,
+        pub ambient: Vec3,
+        pub diffuse: Vec3,
+        pub specular: Vec3,
+        pub constant: f32,
+        pub linear: f32,
+        pub quadratic: f32
+    }
+
+    impl DirectionalLight {
+        /// Sets `{name}.direction`, `{name}.ambient`, `{name}.diffuse` and `{name}.specular`.
+        pub fn set_uniforms(&self, program: &ShaderProgram, name: &str, view: &Mat4) {
+            let direction = view.transform_vector3(self.direction);
+            program.set_vec3(&uniform_name(name, "direction"), &direction.to_array());
+            program.set_vec3(&uniform_name(name, "ambient"), &self.ambient.to_array());
+            program.set_vec3(&uniform_name(name, "diffuse"), &self.diffuse.to_array());
+            program.set_vec3(&uniform_name(name, "specular"), &self.specular.to_array());
+        }
+    }
+
+    impl PointLight {
+        /// Light intensity below which the light is considered to not contribute anymore, 5/256
+        /// is about the smallest step an 8-bit channel can show.
+        const MIN_INTENSITY: f32 = 5.0 / 256.0;
+
+        /// Point light with the attenuation used throughout the examples, reaching about 38 units
+        /// with a diffuse of 1.0, see `radius`.
+        pub fn new(position: Vec3, ambient: Vec3, diffuse: Vec3, specular: Vec3) -> Self {
+            PointLight {
+                position,
+                ambient,
+                diffuse,
+                specular,
+                constant: 1.0,
+                linear: 0.09,
+                quadratic: 0.032
+            }
+        }
+
+        /// Light that adds nothing, for the unused slots of a shader's point light array. Its
+        /// attenuation stays valid, so the shader doesn't divide by zero.
+        pub fn dark() -> Self {
+            PointLight::new(Vec3::ZERO, Vec3::ZERO, Vec3::ZERO, Vec3::ZERO)
+        }
+
+        /// Distance at which the attenuated light falls below what can be seen, found by solving
+        /// `max_channel / (constant + linear * d + quadratic * d^2) = MIN_INTENSITY` for `d`.
+        pub fn radius(&self) -> f32 {
+            let max_channel = self.diffuse.max_element().max(self.specular.max_element());
+            let c = self.constant - max_channel / Self::MIN_INTENSITY;
+            if self.quadratic <= 0.0 {
+                if self.linear <= 0.0 {
+                    return f32::INFINITY;
+                }
+                return (-c / self.linear).max(0.0);
+            }
+            let discriminant = self.linear * self.linear - 4.0 * self.quadratic * c;
+            ((-self.linear + discriminant.max(0.0).sqrt()) / (2.0 * self.quadratic)).max(0.0)
+        }
+
+        /// Sets `{name}.position` and the colour and attenuation fields.
+        pub fn set_uniforms(&self, program: &ShaderProgram, name: &str, view: &Mat4) {
+            let position = view.transform_point3(self.position);
+            program.set_vec3(&uniform_name(name, "position"), &position.to_array());
+            program.set_vec3(&uniform_name(name, "ambient"), &self.ambient.to_array());
+            program.set_vec3(&uniform_name(name, "diffuse"), &self.diffuse.to_array());
+            program.set_vec3(&uniform_name(name, "specular"), &self.specular.to_array());
+            program.set_float(&uniform_name(name, "constant"), self.constant);
+            program.set_float(&uniform_name(name, "linear"), self.linear);
+            program.set_float(&uniform_name(name, "quadratic"), self.quadratic);
+        }
+    }
+
+    impl SpotLight {
+        /// Flashlight held by the camera, with the settings used in the light casters examples.
+        pub fn flashlight(position: Vec3, direction: Vec3, color: Vec3) -> Self {
+            SpotLight {
+                position,
+                direction,
+                cut_off: 12.5,
+                outer_cut_off: 17.5,
+                ambient: Vec3::ZERO,
+                diffuse: color,
+                specular: color,
+                constant: 1.0,
+                linear: 0.09,
+                quadratic: 0.032
+            }
+        }
+
+        /// Sets `{name}.position`, `{name}.direction`, the cosine of both cut off angles and the
+        /// colour and attenuation fields.
+        pub fn set_uniforms(&self, program: &ShaderProgram, name: &str, view: &Mat4) {
+            let position = view.transform_point3(self.position);
+            let direction = view.transform_vector3(self.direction);
+            program.set_vec3(&uniform_name(name, "position"), &position.to_array());
+            program.set_vec3(&uniform_name(name, "direction"), &direction.to_array());
+            program.set_float(&uniform_name(name, "cutOff"), self.cut_off.to_radians().cos());
+            program.set_float(&uniform_name(name, "outerCutOff"), self.outer_cut_off.to_radians().cos());
+            program.set_vec3(&uniform_name(name, "ambient"), &self.ambient.to_array());
+            program.set_vec3(&uniform_name(name, "diffuse"), &self.diffuse.to_array());
+            program.set_vec3(&uniform_name(name, "specular"), &self.specular.to_array());
+            program.set_float(&uniform_name(name, "constant"), self.constant);
+            program.set_float(&uniform_name(name, "linear"), self.linear);
+            program.set_float(&uniform_name(name, "quadratic"), self.quadratic);
+        }
+    }
+
+    fn uniform_name(name: &str, field: &str) -> CString {
+        CString::new(format!("{name}.{field}")).unwrap()
+    }
+}
@@ -0,0 +1,189 @@
+/// Levels of detail: coarser meshes for objects that cover little of the screen, chosen every
+/// frame from their projected size
+pub mod lod {
+    use std::io::Error;
+    use glam::Vec3;
+    use crate::bounds::bounds::BoundingSphere;
+    use crate::camera::camera::Camera;
+    use crate::mesh::mesh::{Mesh, Vertex};
+    use crate::simplify::simplify;
+
+    /// Colours the levels are tinted with when visualising them, from the most to the least
+    /// detailed. Levels past the last one reuse it.
+    pub const LEVEL_COLORS: [Vec3; 5] = [
+        Vec3::new(0.2, 0.8, 0.2),
+        Vec3::new(0.9, 0.9, 0.2),
+        Vec3::new(1.0, 0.55, 0.1),
+        Vec3::new(0.9, 0.15, 0.15),
+        Vec3::new(0.8, 0.2, 0.9)
+    ];
+
+    /// Picks a level from the screen size of an object. Level `i + 1` takes over once the size
+    /// drops below `thresholds[i]`. Near a threshold the current level is kept until the size is
+    /// `hysteresis` past it, a fraction, so objects sitting at the limit don't flicker between
+    /// two levels.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct LodSelector {
+        thresholds: Vec<f32>,
+        pub hysteresis: f32
+    }
+
+    /// The same mesh at decreasing levels of detail, the first one being the original.
+    pub struct LodMesh {
+        pub levels: Vec<Mesh>,
+        pub selector: LodSelector
+    }
+
+    /// Height of the sphere on screen as a fraction of the screen height, above 1 when it
+    /// overflows the screen and infinite when the camera is inside it.
+    pub fn screen_size(sphere: &BoundingSphere, camera: &Camera) -> f32 {
+        let distance = sphere.center.distance(camera.position);
+        if distance <= sphere.radius {
+            return f32::INFINITY;
+        }
+        // the screen is 2 * distance * tan(fov / 2) high at that distance
+        sphere.radius / (distance * (camera.zoom.to_radians() * 0.5).tan())
+    }
+
+    impl LodSelector {
+        pub const DEFAULT_HYSTERESIS: f32 = 0.15;
+
+        /// Fails unless the thresholds go strictly down.
+        pub fn new(thresholds: Vec<f32>) -> Result<Self, Error> {
+            let mut selector = LodSelector::default();
+            for threshold in thresholds {
+                selector.push(threshold)?;
+            }
+            Ok(selector)
+        }
+
+        pub fn levels(&self) -> usize {
+            self.thresholds.len() + 1
+        }
+
+        pub fn thresholds(&self) -> &[f32] {
+            &self.thresholds
+        }
+
+        /// Adds a coarser level, used below `threshold`. Fails unless it is below the last one.
+        pub fn push(&mut self, threshold: f32) -> Result<(), Error> {
+            match self.thresholds.last() {
+                Some(last) if threshold.is_nan() || threshold >= *last => Err(Error::other(format!(
+                    "ERROR::LOD::THRESHOLD\nThe level is used below {threshold}, which must be below the {last} of the previous level"
+                ))),
+                _ if threshold.is_nan() => Err(Error::other("ERROR::LOD::THRESHOLD\nThe level is used below NaN")),
+                _ => {
+                    self.thresholds.push(threshold);
+                    Ok(())
+                }
+            }
+        }
+
+        /// Level for the screen size, without hysteresis.
+        pub fn level_for(&self, screen_size: f32) -> usize {
+            self.thresholds.iter().take_while(|threshold| screen_size < **threshold).count()
+        }
+
+        /// Level to use this frame for an object that used `current` the frame before.
+        pub fn select(&self, current: usize, screen_size: f32) -> usize {
+            let margin = 1.0 + self.hysteresis;
+            let coarser = self.level_for(screen_size * margin);
+            if coarser > current {
+                return coarser;
+            }
+            let finer = self.level_for(screen_size / margin);
+            if finer < current {
+                return finer;
+            }
+            current.min(self.levels() - 1)
+        }
+    }
+
+    impl Default for LodSelector {
+        /// A single level.
+        fn default() -> Self {
+            LodSelector { thresholds: Vec::new(), hysteresis: Self::DEFAULT_HYSTERESIS }
+        }
+    }
+
+    impl LodMesh {
+        /// Generates a level per `(ratio, threshold)` pair by simplifying the previous level,
+        /// keeping `ratio` of the triangles of the original. Fails before simplifying anything
+        /// unless the thresholds go down.
+        pub fn generate(vertices: Vec<Vertex>, indices: Vec<u32>, levels: &[(f32, f32)]) -> Result<Self, Error> {
+            let selector = LodSelector::new(levels.iter().map(|(_, threshold)| *threshold).collect())?;
+            let triangles = indices.len() / 3;
+            let mut meshes = vec![Mesh::new(vertices, indices, Vec::new())];
+            for (ratio, _) in levels {
+                let previous = meshes.last().unwrap();
+                let target = (triangles as f32 * ratio) as usize;
+                let (vertices, indices) = simplify::simplify(&previous.vertices, &previous.indices, target);
+                meshes.push(Mesh::new(vertices, indices, Vec::new()));
+            }
+            Ok(LodMesh { levels: meshes, selector })
+        }
+
+        /// Mesh of the level, the coarsest one past the last level.
+        pub fn mesh(&self, level: usize) -> &Mesh {
+            &self.levels[level.min(self.levels.len() - 1)]
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn levels_follow_the_thresholds() {
+            let selector = LodSelector::new(vec![0.5, 0.2, 0.05]).unwrap();
+            assert_eq!(selector.levels(), 4);
+            assert_eq!(selector.level_for(2.0), 0);
+            assert_eq!(selector.level_for(0.3), 1);
+            assert_eq!(selector.level_for(0.1), 2);
+            assert_eq!(selector.level_for(0.01), 3);
+        }
+
+        #[test]
+        fn hysteresis_keeps_the_level_near_a_threshold() {
+            let mut selector = LodSelector::new(vec![0.5]).unwrap();
+            selector.hysteresis = 0.2;
+            // shrinking, the finer level holds until the size is 20% below the threshold
+            assert_eq!(selector.select(0, 0.45), 0);
+            assert_eq!(selector.select(0, 0.4), 1);
+            // growing back, the coarser one holds until 20% above
+            assert_eq!(selector.select(1, 0.55), 1);
+            assert_eq!(selector.select(1, 0.61), 0);
+            // far from the thresholds any level jumps straight to the right one
+            assert_eq!(selector.select(0, 0.01), 1);
+            assert_eq!(selector.select(1, 3.0), 0);
+        }
+
+        #[test]
+        fn screen_size_shrinks_with_distance_and_zoom() {
+            let mut camera = Camera::new(Vec3::ZERO, Vec3::Y, false);
+            let near = screen_size(&BoundingSphere::new(Vec3::new(0.0, 0.0, -5.0), 1.0), &camera);
+            let far = screen_size(&BoundingSphere::new(Vec3::new(0.0, 0.0, -10.0), 1.0), &camera);
+            assert!((near / far - 2.0).abs() < 1e-4);
+            assert_eq!(screen_size(&BoundingSphere::new(Vec3::ZERO, 1.0), &camera), f32::INFINITY);
+
+            // zooming in narrows the field of view, so the same sphere covers more of the screen
+            camera.zoom = 20.0;
+            assert!(screen_size(&BoundingSphere::new(Vec3::new(0.0, 0.0, -5.0), 1.0), &camera) > near);
+        }
+
+        #[test]
+        fn thresholds_must_go_down() {
+            let error = LodSelector::new(vec![0.1, 0.2]).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "ERROR::LOD::THRESHOLD\nThe level is used below 0.2, which must be below the 0.1 of the previous level"
+            );
+            assert!(LodSelector::new(vec![0.5, 0.5]).is_err());
+            assert!(LodSelector::new(vec![f32::NAN]).is_err());
+
+            let mut selector = LodSelector::new(vec![0.5]).unwrap();
+            assert!(selector.push(0.6).is_err());
+            assert_eq!(selector.thresholds(), &[0.5]);
+        }
+    }
+}
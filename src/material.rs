@@ -0,0 +1,178 @@
+/// Material for `material.frag`, binding its textures to fixed units and setting the samplers
+pub mod material {
+    use glam::{Vec2, Vec3};
+    use serde::Deserialize;
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// How the surface combines with what is behind it. The opacity is the product of `alpha`
+    /// with the alpha of the opacity map, or of the diffuse map when there is none.
+    /// - `Opaque` ignores the opacity.
+    /// - `Cutout` discards the fragments below `alpha_cutoff` and draws the rest as opaque, so it
+    ///   needs no sorting.
+    /// - `Blended` mixes the colour over the background by the opacity, like glass.
+    /// - `Additive` adds the colour scaled by the opacity, for glows and fire.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum BlendMode {
+        #[default]
+        Opaque,
+        Cutout,
+        Blended,
+        Additive
+    }
+
+    /// A specular map either scales the specular colour of the lights with a single intensity read
+    /// from its red channel, or tints the highlights with its own colour.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SpecularMap {
+        Intensity(u32),
+        Color(u32)
+    }
+
+    /// Light given off by the surface regardless of the lights in the scene. The map scrolls by
+    /// `scroll` texture coordinates per second, and when `pulse_frequency` is not 0 the intensity
+    /// fades in and out that many times per second.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Emission {
+        pub map: u32,
+        pub intensity: f32,
+        pub scroll: Vec2,
+        pub pulse_frequency: f32
+    }
+
+    /// Every map is optional: without a diffuse map `diffuse_color` is used, without a specular
+    /// map `specular_color`. What the opacity does depends on `blend`.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Material {
+        pub diffuse: Option<u32>,
+        pub diffuse_color: Vec3,
+        pub specular: Option<SpecularMap>,
+        pub specular_color: Vec3,
+        pub shininess: f32,
+        pub emission: Option<Emission>,
+        pub opacity: Option<u32>,
+        pub alpha: f32,
+        pub alpha_cutoff: f32,
+        pub blend: BlendMode
+    }
+
+    impl Emission {
+        /// Constant emission at full intensity.
+        pub fn new(map: u32) -> Self {
+            Emission {
+                map,
+                intensity: 1.0,
+                scroll: Vec2::ZERO,
+                pulse_frequency: 0.0
+            }
+        }
+
+        /// Intensity at `time` seconds, between 0 and `intensity` when pulsing.
+        pub fn intensity_at(&self, time: f32) -> f32 {
+            if self.pulse_frequency == 0.0 {
+                return self.intensity;
+            }
+            let phase = time * self.pulse_frequency * std::f32::consts::TAU;
+            self.intensity * (phase.sin() * 0.5 + 0.5)
+        }
+
+        /// Texture coordinate offset at `time` seconds, wrapped to stay in `[0, 1)`.
+        pub fn offset_at(&self, time: f32) -> Vec2 {
+            (self.scroll * time).fract()
+        }
+    }
+
+    impl BlendMode {
+        /// Whether the surface lets what is behind it show through, so it has to be drawn after
+        /// the opaque surfaces without writing depth.
+        pub fn is_transparent(&self) -> bool {
+            matches!(self, BlendMode::Blended | BlendMode::Additive)
+        }
+    }
+
+    impl Material {
+        pub const DIFFUSE_UNIT: u32 = 0;
+        pub const SPECULAR_UNIT: u32 = 1;
+        pub const EMISSION_UNIT: u32 = 2;
+        pub const OPACITY_UNIT: u32 = 3;
+
+        /// The usual diffuse and specular intensity maps of the lighting examples.
+        pub fn new(diffuse: u32, specular: u32) -> Self {
+            Material {
+                diffuse: Some(diffuse),
+                specular: Some(SpecularMap::Intensity(specular)),
+                ..Self::default()
+            }
+        }
+
+        /// Binds the maps and sets the `material` uniform of the program in use. `time` is in
+        /// seconds and drives the emission animation.
+        pub fn bind(&self, program: &ShaderProgram, time: f32) {
+            self.set_uniforms(program, time);
+            unsafe {
+                for (unit, map) in self.maps() {
+                    gl::ActiveTexture(gl::TEXTURE0 + unit);
+                    gl::BindTexture(gl::TEXTURE_2D, map);
+                }
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+        }
+
+        /// Sets the `material` uniform of the program in use without touching the texture units.
+        pub fn set_uniforms(&self, program: &ShaderProgram, time: f32) {
+            program.set_int(c"material.diffuse", Self::DIFFUSE_UNIT);
+            program.set_int(c"material.specular", Self::SPECULAR_UNIT);
+            program.set_int(c"material.emission", Self::EMISSION_UNIT);
+            program.set_int(c"material.opacity", Self::OPACITY_UNIT);
+
+            program.set_bool(c"material.hasDiffuse", self.diffuse.is_some());
+            program.set_vec3(c"material.diffuseColor", &self.diffuse_color.to_array());
+            program.set_bool(c"material.hasSpecular", self.specular.is_some());
+            program.set_bool(c"material.specularIsColor", matches!(self.specular, Some(SpecularMap::Color(_))));
+            program.set_vec3(c"material.specularColor", &self.specular_color.to_array());
+            program.set_float(c"material.shininess", self.shininess);
+
+            program.set_bool(c"material.hasEmission", self.emission.is_some());
+            if let Some(emission) = &self.emission {
+                program.set_vec2(c"material.emissionOffset", &emission.offset_at(time).to_array());
+                program.set_float(c"material.emissionIntensity", emission.intensity_at(time));
+            }
+
+            program.set_bool(c"material.hasOpacity", self.opacity.is_some());
+            program.set_float(c"material.alpha", self.alpha);
+            program.set_float(c"material.alphaCutoff", self.alpha_cutoff);
+            program.set_int(c"material.blend", self.blend as u32);
+        }
+
+        /// Texture unit and texture of every map, 0 for the maps the material doesn't have.
+        pub fn maps(&self) -> [(u32, u32); 4] {
+            let specular = self.specular.map(|map| match map {
+                SpecularMap::Intensity(id) | SpecularMap::Color(id) => id
+            });
+            [
+                (Self::DIFFUSE_UNIT, self.diffuse.unwrap_or(0)),
+                (Self::SPECULAR_UNIT, specular.unwrap_or(0)),
+                (Self::EMISSION_UNIT, self.emission.map_or(0, |emission| emission.map)),
+                (Self::OPACITY_UNIT, self.opacity.unwrap_or(0))
+            ]
+        }
+    }
+
+    impl Default for Material {
+        /// Opaque white, without maps, with the shininess used throughout the examples.
+        fn default() -> Self {
+            Material {
+                diffuse: None,
+                diffuse_color: Vec3::ONE,
+                specular: None,
+                specular_color: Vec3::splat(0.5),
+                shininess: 32.0,
+                emission: None,
+                opacity: None,
+                alpha: 1.0,
+                alpha_cutoff: 0.5,
+                blend: BlendMode::Opaque
+            }
+        }
+    }
+}
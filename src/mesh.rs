@@ -3,20 +3,34 @@ pub mod mesh {
     use std::ffi::CString;
     use std::mem::offset_of;
     use std::ptr;
-    use glam::{Vec2, Vec3};
+    use glam::{Mat4, Vec2, Vec3, Vec4};
+    use crate::bounds::bounds::{Aabb, BoundingSphere};
     use crate::shaders::shaders::ShaderProgram;
 
-    enum TextureType {
-        DIFFUSE,
-        SPECULAR
+    pub enum TextureType {
+        Diffuse,
+        Specular,
+        Emission,
+        Opacity
     }
 
+    #[derive(Debug, Clone, Copy)]
     pub struct Vertex {
         position: Vec3,
         normal: Vec3,
         tex_coord: Vec2
     }
 
+    /// Per instance attributes of `Mesh::draw_instanced`: the model matrix takes locations 3 to
+    /// 6, one per column, the colour location 7 and `data`, free for the shader to use, location 8.
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    pub struct Instance {
+        pub model: Mat4,
+        pub color: Vec4,
+        pub data: Vec4
+    }
+
     pub struct Texture {
         id: u32,
         tex_type: TextureType
@@ -28,23 +42,159 @@ pub mod mesh {
         pub textures: Vec<Texture>,
         vao: u32,
         vbo: u32,
-        ebo: u32
+        ebo: u32,
+        instance_vbo: u32,
+        instances: usize,
+        aabb: Aabb,
+        bounding_sphere: BoundingSphere
+    }
+
+    impl Vertex {
+        pub fn new(position: Vec3, normal: Vec3, tex_coord: Vec2) -> Self {
+            Vertex {
+                position,
+                normal,
+                tex_coord
+            }
+        }
+
+        pub fn position(&self) -> Vec3 {
+            self.position
+        }
+
+        pub fn normal(&self) -> Vec3 {
+            self.normal
+        }
+
+        pub fn tex_coord(&self) -> Vec2 {
+            self.tex_coord
+        }
+    }
+
+    impl Instance {
+        /// White instance without custom data.
+        pub fn new(model: Mat4) -> Self {
+            Instance {
+                model,
+                color: Vec4::ONE,
+                data: Vec4::ZERO
+            }
+        }
+    }
+
+    impl Texture {
+        pub fn new(id: u32, tex_type: TextureType) -> Self {
+            Texture {
+                id,
+                tex_type
+            }
+        }
     }
 
     impl Mesh {
         pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>, textures: Vec<Texture>) -> Self {
             let (vao, vbo, ebo) = Self::setup_mesh(&vertices, &indices);
+            let positions: Vec<Vec3> = vertices.iter().map(Vertex::position).collect();
+            let aabb = Aabb::from_points(positions.iter().copied());
+            let bounding_sphere = BoundingSphere::from_points(&positions);
             Mesh {
                 vertices,
                 indices,
                 textures,
                 vao,
                 vbo,
-                ebo
+                ebo,
+                instance_vbo: 0,
+                instances: 0,
+                aabb,
+                bounding_sphere
             }
         }
 
+        /// Box around the vertices, in model space.
+        pub fn aabb(&self) -> &Aabb {
+            &self.aabb
+        }
+
+        /// Sphere around the vertices, in model space.
+        pub fn bounding_sphere(&self) -> &BoundingSphere {
+            &self.bounding_sphere
+        }
+
         pub fn draw(&self, shader: &ShaderProgram) {
+            self.bind_textures(shader);
+            unsafe {
+                // Draw mesh
+                gl::BindVertexArray(self.vao);
+                gl::DrawElements(gl::TRIANGLES, self.indices.len() as i32, gl::UNSIGNED_INT, ptr::null());
+                gl::BindVertexArray(0);
+            }
+        }
+
+        /// Replaces the instances drawn by `draw_instanced`. The buffer is created on first use and
+        /// orphaned on later calls, so updating it every frame doesn't wait on the frames still
+        /// drawing from the previous data.
+        pub fn set_instances(&mut self, instances: &[Instance]) {
+            unsafe {
+                if self.instance_vbo == 0 {
+                    gl::GenBuffers(1, &mut self.instance_vbo);
+                    gl::BindVertexArray(self.vao);
+                    gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_vbo);
+                    let stride = size_of::<Instance>() as i32;
+                    for column in 0..4 {
+                        let location = 3 + column;
+                        gl::EnableVertexAttribArray(location);
+                        gl::VertexAttribPointer(
+                            location,
+                            4,
+                            gl::FLOAT,
+                            gl::FALSE,
+                            stride,
+                            (offset_of!(Instance, model) + column as usize * size_of::<Vec4>()) as *const _
+                        );
+                        gl::VertexAttribDivisor(location, 1);
+                    }
+                    for (location, offset) in [(7, offset_of!(Instance, color)), (8, offset_of!(Instance, data))] {
+                        gl::EnableVertexAttribArray(location);
+                        gl::VertexAttribPointer(location, 4, gl::FLOAT, gl::FALSE, stride, offset as *const _);
+                        gl::VertexAttribDivisor(location, 1);
+                    }
+                    gl::BindVertexArray(0);
+                }
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_vbo);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    size_of_val(instances) as isize,
+                    instances.as_ptr().cast(),
+                    gl::STREAM_DRAW
+                );
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            }
+            self.instances = instances.len();
+        }
+
+        /// Number of instances given to the last `set_instances`.
+        pub fn instances(&self) -> usize {
+            self.instances
+        }
+
+        /// Draws the first `count` instances in a single call, at most as many as were set.
+        pub fn draw_instanced(&self, shader: &ShaderProgram, count: usize) {
+            self.bind_textures(shader);
+            unsafe {
+                gl::BindVertexArray(self.vao);
+                gl::DrawElementsInstanced(
+                    gl::TRIANGLES,
+                    self.indices.len() as i32,
+                    gl::UNSIGNED_INT,
+                    ptr::null(),
+                    count.min(self.instances) as i32
+                );
+                gl::BindVertexArray(0);
+            }
+        }
+
+        fn bind_textures(&self, shader: &ShaderProgram) {
             let mut diffuse_n = 1;
             let mut specular_n = 1;
 
@@ -55,18 +205,29 @@ pub mod mesh {
                 let mut number = 0;
                 let mut name = String::new();
                 match texture.tex_type {
-                    TextureType::DIFFUSE => {
+                    TextureType::Diffuse => {
                         number = diffuse_n;
                         diffuse_n += 1;
                         name = "texture_diffuse".to_string();
                     },
-                    TextureType::SPECULAR => {
+                    TextureType::Specular => {
                         number = specular_n;
                         specular_n += 1;
                         name = "texture_specular".to_string();
+                    },
+                    // material.frag has a single emission and opacity sampler, see material.rs
+                    TextureType::Emission => {
+                        name = "emission".to_string();
+                    },
+                    TextureType::Opacity => {
+                        name = "opacity".to_string();
                     }
                 }
-                let c_str = CString::new(format!("material.{name}{number}")).unwrap();
+                let c_str = if number == 0 {
+                    CString::new(format!("material.{name}")).unwrap()
+                } else {
+                    CString::new(format!("material.{name}{number}")).unwrap()
+                };
                 shader.set_int(&*c_str, i as u32);
                 unsafe {
                     gl::BindTexture(gl::TEXTURE_2D, texture.id);
@@ -74,11 +235,6 @@ pub mod mesh {
             }
             unsafe {
                 gl::ActiveTexture(gl::TEXTURE0);
-
-                // Draw mesh
-                gl::BindVertexArray(0);
-                gl::DrawElements(gl::TRIANGLES, self.indices.len() as i32, gl::UNSIGNED_INT, ptr::null());
-                gl::BindVertexArray(0);
             }
         }
 
@@ -146,4 +302,14 @@ pub mod mesh {
             (vao, vbo, ebo)
         }
     }
+
+    impl Drop for Mesh {
+        fn drop(&mut self) {
+            unsafe {
+                let buffers = [self.vbo, self.ebo, self.instance_vbo];
+                gl::DeleteBuffers(buffers.len() as i32, buffers.as_ptr());
+                gl::DeleteVertexArrays(1, &self.vao);
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,167 @@
+/// Models loaded with Assimp, keeping the node hierarchy of the file in a scene graph
+pub mod model {
+    use std::io::Error;
+    use std::path::Path;
+    use glam::{Mat4, Vec2, Vec3};
+    use russimp_ng::node::Node as AssimpNode;
+    use russimp_ng::scene::{PostProcess, Scene};
+    use crate::bounds::bounds::{Aabb, BoundingSphere};
+    use crate::lod::lod::LodSelector;
+    use crate::material::material::Material;
+    use crate::mesh::mesh::{Mesh, Vertex};
+    use crate::renderer::renderer::{DrawItem, RenderQueue};
+    use crate::scene_graph::scene_graph::{NodeId, SceneGraph, Transform};
+    use crate::shaders::shaders::ShaderProgram;
+    use crate::simplify::simplify;
+
+    /// The meshes of a model file and the nodes placing them. Each node carries the indices of
+    /// the meshes it draws. Materials are not loaded, bind one before drawing.
+    ///
+    /// Coarser levels of detail, authored or generated, hold a mesh for every mesh of the model
+    /// at the same index, and `lod_selector` tells when to use them.
+    pub struct Model {
+        pub meshes: Vec<Mesh>,
+        pub graph: SceneGraph<Vec<usize>>,
+        pub root: NodeId,
+        pub lod_selector: LodSelector,
+        lods: Vec<Vec<Mesh>>,
+        aabb: Aabb
+    }
+
+    impl Model {
+        /// Loads any format Assimp reads, triangulated and with normals generated where missing.
+        pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+            let path = path.as_ref();
+            let scene = Scene::from_file(
+                &path.to_string_lossy(),
+                vec![
+                    PostProcess::Triangulate,
+                    PostProcess::GenerateSmoothNormals,
+                    PostProcess::JoinIdenticalVertices
+                ]
+            ).map_err(|e| Error::other(format!("ERROR::MODEL::LOAD_FAILED {}\n{e}", path.display())))?;
+            let root_node = scene.root.as_ref()
+                .ok_or_else(|| Error::other(format!("ERROR::MODEL::NO_ROOT_NODE {}", path.display())))?;
+
+            let meshes: Vec<Mesh> = scene.meshes.iter()
+                .map(|mesh| {
+                    let tex_coords = mesh.texture_coords.first().and_then(Option::as_ref);
+                    let vertices = mesh.vertices.iter().enumerate()
+                        .map(|(i, position)| {
+                            let normal = mesh.normals.get(i).map_or(Vec3::ZERO, |n| Vec3::new(n.x, n.y, n.z));
+                            let tex_coord = tex_coords
+                                .and_then(|tex_coords| tex_coords.get(i))
+                                .map_or(Vec2::ZERO, |t| Vec2::new(t.x, t.y));
+                            Vertex::new(Vec3::new(position.x, position.y, position.z), normal, tex_coord)
+                        })
+                        .collect();
+                    let indices = mesh.faces.iter().flat_map(|face| face.0.iter().copied()).collect();
+                    Mesh::new(vertices, indices, Vec::new())
+                })
+                .collect();
+
+            let mut graph = SceneGraph::new();
+            let root = add_node(&mut graph, None, root_node);
+            graph.update();
+            let mut aabb = Aabb::EMPTY;
+            graph.traverse(|_, node| {
+                for mesh in &node.data {
+                    aabb = aabb.union(&meshes[*mesh].aabb().transformed(node.world()));
+                }
+            });
+            Ok(Model { meshes, graph, root, lod_selector: LodSelector::default(), lods: Vec::new(), aabb })
+        }
+
+        /// Box around every mesh placed by its node, as the nodes were in the file.
+        pub fn aabb(&self) -> &Aabb {
+            &self.aabb
+        }
+
+        /// Sphere around `aabb`.
+        pub fn bounding_sphere(&self) -> BoundingSphere {
+            BoundingSphere::new(self.aabb.center(), self.aabb.extents().length())
+        }
+
+        /// Adds the meshes of another file as the next level of detail, used below `screen_size`,
+        /// which must be below the one of the previous level. The file must hold as many meshes,
+        /// in the same order, as usually exported from the same scene with fewer polygons. Its
+        /// nodes are ignored, the meshes are placed by this model's.
+        pub fn add_lod(&mut self, lod: Model, screen_size: f32) -> Result<(), Error> {
+            if lod.meshes.len() != self.meshes.len() {
+                return Err(Error::other(format!(
+                    "ERROR::MODEL::LOD_MISMATCH\nThe level has {} meshes, the model {}", lod.meshes.len(), self.meshes.len()
+                )));
+            }
+            self.lod_selector.push(screen_size)?;
+            self.lods.push(lod.meshes);
+            Ok(())
+        }
+
+        /// Adds a level of detail used below `screen_size` by simplifying every mesh of the
+        /// previous level, down to `ratio` of the triangles of the full detail meshes. The
+        /// threshold must be below the one of the previous level.
+        pub fn generate_lod(&mut self, ratio: f32, screen_size: f32) -> Result<(), Error> {
+            self.lod_selector.push(screen_size)?;
+            let previous = self.lods.last().unwrap_or(&self.meshes);
+            let meshes = previous.iter().zip(&self.meshes)
+                .map(|(mesh, original)| {
+                    let target = (original.indices.len() as f32 / 3.0 * ratio) as usize;
+                    let (vertices, indices) = simplify::simplify(&mesh.vertices, &mesh.indices, target);
+                    Mesh::new(vertices, indices, Vec::new())
+                })
+                .collect();
+            self.lods.push(meshes);
+            Ok(())
+        }
+
+        /// Meshes of a level of detail, 0 being the full detail, the coarsest past the last level.
+        pub fn lod(&self, level: usize) -> &[Mesh] {
+            match level.min(self.lods.len()) {
+                0 => &self.meshes,
+                level => &self.lods[level - 1]
+            }
+        }
+
+        /// Draws every mesh with the world matrix of its node, placed by `model`. Call
+        /// `graph.update()` first when nodes were moved.
+        pub fn draw(&self, program: &ShaderProgram, model: &Mat4) {
+            self.graph.traverse(|_, node| {
+                if node.data.is_empty() {
+                    return;
+                }
+                program.set_mat4(c"model", &(*model * *node.world()));
+                for mesh in &node.data {
+                    self.meshes[*mesh].draw(program);
+                }
+            });
+        }
+
+        /// Queues every mesh of a level of detail with the world matrix of its node, placed by
+        /// `model`, all with the same material and program.
+        pub fn queue<'a>(&'a self, queue: &mut RenderQueue<'a>, level: usize, material: &'a Material, program: &'a ShaderProgram, model: &Mat4) {
+            let meshes = self.lod(level);
+            self.graph.traverse(|_, node| {
+                for mesh in &node.data {
+                    queue.push(DrawItem::new(&meshes[*mesh], material, program, *model * *node.world()));
+                }
+            });
+        }
+    }
+
+    fn add_node(graph: &mut SceneGraph<Vec<usize>>, parent: Option<NodeId>, node: &AssimpNode) -> NodeId {
+        let t = &node.transformation;
+        // Assimp matrices are row major
+        let matrix = Mat4::from_cols_array(&[
+            t.a1, t.b1, t.c1, t.d1,
+            t.a2, t.b2, t.c2, t.d2,
+            t.a3, t.b3, t.c3, t.d3,
+            t.a4, t.b4, t.c4, t.d4
+        ]);
+        let meshes = node.meshes.iter().map(|mesh| *mesh as usize).collect();
+        let id = graph.add(parent, &node.name, Transform::from_matrix(&matrix), meshes);
+        for child in node.children.borrow().iter() {
+            add_node(graph, Some(id), child);
+        }
+        id
+    }
+}
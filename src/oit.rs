@@ -0,0 +1,113 @@
+/// Weighted blended order-independent transparency: blended surfaces are accumulated in any order
+/// into an offscreen target, then composited once over the opaque image
+pub mod oit {
+    use std::io::Error;
+    use crate::framebuffer::framebuffer::{ColorFormat, DepthStencil, Framebuffer};
+    use crate::postprocessing::postprocessing::ScreenQuad;
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// Targets and program of the technique from McGuire and Bavoil. Every blended fragment adds
+    /// its premultiplied colour times a weight to the first attachment, and its weighted opacity
+    /// to the second. The alpha of the first attachment multiplies the transparencies of the
+    /// fragments together, which is how much of the background still shows through. Compositing
+    /// divides the colour sum by the weight sum and lays that average over the background.
+    ///
+    /// Overlapping layers come out close to sorted without sorting anything, and intersecting
+    /// surfaces work too, which no draw order can get right. The colours are an approximation
+    /// though, strongest when the layers have similar opacities.
+    ///
+    /// Programs drawn into it need a `weightedBlended` uniform switching their output to the
+    /// accumulation, see `material.frag`. Both sums use the same blend function so it works
+    /// without the per attachment blending of OpenGL 4.
+    pub struct WeightedBlended {
+        accumulation: Framebuffer,
+        composite_program: ShaderProgram,
+        quad: ScreenQuad
+    }
+
+    /// Framebuffer and viewport to get back to after accumulating.
+    pub(crate) struct Target {
+        fbo: u32,
+        viewport: [i32; 4]
+    }
+
+    impl WeightedBlended {
+        pub fn new(width: i32, height: i32) -> Result<Self, Error> {
+            let accumulation = Framebuffer::new(
+                width,
+                height,
+                1,
+                &[ColorFormat::RGBA16F, ColorFormat::R16F],
+                DepthStencil::Renderbuffer
+            )?;
+            let composite_program = ShaderProgram::from_files(
+                "src/shaders/vertex/screen_quad.vert",
+                "src/shaders/fragment/oit_composite.frag"
+            )?;
+            composite_program.use_program();
+            composite_program.set_int(c"accumulation", 0);
+            composite_program.set_int(c"weights", 1);
+            Ok(WeightedBlended { accumulation, composite_program, quad: ScreenQuad::new() })
+        }
+
+        /// Recreates the targets for the new window size.
+        pub fn resize(&mut self, width: i32, height: i32) -> Result<(), Error> {
+            self.accumulation.resize(width, height)
+        }
+
+        /// Starts accumulating into the offscreen targets. The depth of the framebuffer bound
+        /// right now is copied over so the opaque surfaces still hide the blended ones, so it must
+        /// be the size of the targets and have a `DEPTH24_STENCIL8` depth buffer.
+        pub(crate) fn begin(&self) -> Target {
+            let mut fbo = 0;
+            let mut viewport = [0; 4];
+            unsafe {
+                gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut fbo);
+                gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, fbo as u32);
+                gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.accumulation.fbo);
+                gl::BlitFramebuffer(
+                    0, 0, self.accumulation.width(), self.accumulation.height(),
+                    0, 0, self.accumulation.width(), self.accumulation.height(),
+                    gl::DEPTH_BUFFER_BIT,
+                    gl::NEAREST
+                );
+                self.accumulation.bind();
+                // nothing accumulated and the background fully showing through
+                gl::ClearBufferfv(gl::COLOR, 0, [0.0, 0.0, 0.0, 1.0].as_ptr());
+                gl::ClearBufferfv(gl::COLOR, 1, [0.0; 4].as_ptr());
+
+                gl::Enable(gl::BLEND);
+                gl::BlendFuncSeparate(gl::ONE, gl::ONE, gl::ZERO, gl::ONE_MINUS_SRC_ALPHA);
+                gl::DepthMask(gl::FALSE);
+            }
+            Target { fbo: fbo as u32, viewport }
+        }
+
+        /// Lays the accumulated surfaces over the target `begin` started from, binding it back.
+        /// Texture units 0 and 1 are left with the accumulation targets.
+        pub(crate) fn composite(&self, target: Target) {
+            let [x, y, width, height] = target.viewport;
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, target.fbo);
+                gl::Viewport(x, y, width, height);
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                gl::Disable(gl::DEPTH_TEST);
+
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.accumulation.color_texture(0));
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, self.accumulation.color_texture(1));
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+            self.composite_program.use_program();
+            self.quad.draw();
+            unsafe {
+                gl::Enable(gl::DEPTH_TEST);
+                gl::DepthMask(gl::TRUE);
+                gl::Disable(gl::BLEND);
+            }
+        }
+    }
+}
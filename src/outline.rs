@@ -0,0 +1,69 @@
+/// Outlines around objects, drawn with the stencil buffer as in the stencil testing chapter
+pub mod outline {
+    use std::io::Error;
+    use glam::{Mat4, Vec3};
+    use crate::mesh::mesh::Mesh;
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// Border drawn around the silhouette of an object, `thickness` world units wide. It shows
+    /// through whatever is in front of the object, which is what a selection needs.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Outline {
+        pub color: Vec3,
+        pub thickness: f32
+    }
+
+    /// Program the outlines are drawn with, see `RenderQueue::outlines`. Each outlined mesh is
+    /// first drawn into the stencil buffer only, then again a bit larger in a flat colour
+    /// wherever the stencil wasn't marked, which leaves the border.
+    pub struct OutlinePass {
+        pub(crate) program: ShaderProgram
+    }
+
+    impl Outline {
+        pub fn new(color: Vec3, thickness: f32) -> Self {
+            Outline { color, thickness }
+        }
+
+        /// Model matrix of the larger copy of `mesh` placed by `model`. Every axis of the mesh
+        /// bounds grows by the thickness on both sides in world units, so flattened and stretched
+        /// objects get an even border too.
+        pub(crate) fn grown(&self, mesh: &Mesh, model: &Mat4) -> Mat4 {
+            let aabb = mesh.aabb();
+            let center = aabb.center();
+            let half_extents = aabb.extents();
+            let world_lengths = Vec3::new(
+                model.x_axis.truncate().length(),
+                model.y_axis.truncate().length(),
+                model.z_axis.truncate().length()
+            ) * half_extents;
+            let scale = Vec3::ONE + Vec3::splat(self.thickness) / world_lengths.max(Vec3::splat(f32::EPSILON));
+            *model * Mat4::from_translation(center) * Mat4::from_scale(scale) * Mat4::from_translation(-center)
+        }
+    }
+
+    impl Default for Outline {
+        /// The orange of the usual selection highlight.
+        fn default() -> Self {
+            Outline::new(Vec3::new(1.0, 0.6, 0.1), 0.04)
+        }
+    }
+
+    impl OutlinePass {
+        pub fn new() -> Result<Self, Error> {
+            let program = ShaderProgram::from_files(
+                "src/shaders/vertex/light_source.vert",
+                "src/shaders/fragment/outline.frag"
+            )?;
+            Ok(OutlinePass { program })
+        }
+    }
+
+    impl Drop for OutlinePass {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteProgram(self.program.shader_program_id);
+            }
+        }
+    }
+}
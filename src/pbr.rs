@@ -0,0 +1,91 @@
+/// Metallic-roughness material for the physically based shader `pbr.frag`
+pub mod pbr {
+    use std::io::Error;
+    use glam::Vec3;
+    use crate::ibl::ibl::{BRDF_LUT_UNIT, IRRADIANCE_UNIT, PREFILTER_UNIT};
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// Every map is optional, the scalar values are used instead when a map is `None`. The maps
+    /// are texture ids, metallic, roughness and AO are read from their red channel.
+    #[derive(Debug, Clone, Copy)]
+    pub struct PbrMaterial {
+        pub albedo: Vec3,
+        pub metallic: f32,
+        pub roughness: f32,
+        pub ao: f32,
+        pub emissive: Vec3,
+        pub albedo_map: Option<u32>,
+        pub metallic_map: Option<u32>,
+        pub roughness_map: Option<u32>,
+        pub ao_map: Option<u32>,
+        pub normal_map: Option<u32>,
+        pub emissive_map: Option<u32>
+    }
+
+    impl PbrMaterial {
+        /// Untextured material without emission and without ambient occlusion.
+        pub fn new(albedo: Vec3, metallic: f32, roughness: f32) -> Self {
+            PbrMaterial {
+                albedo,
+                metallic,
+                roughness,
+                ao: 1.0,
+                emissive: Vec3::ZERO,
+                albedo_map: None,
+                metallic_map: None,
+                roughness_map: None,
+                ao_map: None,
+                normal_map: None,
+                emissive_map: None
+            }
+        }
+
+        /// Sets the `material` uniform of `pbr.frag` and binds the maps to the texture units 0 to 5.
+        pub fn set_uniforms(&self, program: &ShaderProgram) {
+            program.set_vec3(c"material.albedo", &self.albedo.to_array());
+            program.set_float(c"material.metallic", self.metallic);
+            program.set_float(c"material.roughness", self.roughness);
+            program.set_float(c"material.ao", self.ao);
+            program.set_vec3(c"material.emissive", &self.emissive.to_array());
+
+            let maps = [
+                (self.albedo_map, c"material.albedoMap", c"material.hasAlbedoMap"),
+                (self.metallic_map, c"material.metallicMap", c"material.hasMetallicMap"),
+                (self.roughness_map, c"material.roughnessMap", c"material.hasRoughnessMap"),
+                (self.ao_map, c"material.aoMap", c"material.hasAoMap"),
+                (self.normal_map, c"material.normalMap", c"material.hasNormalMap"),
+                (self.emissive_map, c"material.emissiveMap", c"material.hasEmissiveMap")
+            ];
+            for (unit, (map, sampler, has_map)) in maps.into_iter().enumerate() {
+                program.set_int(sampler, unit as u32);
+                program.set_bool(has_map, map.is_some());
+                unsafe {
+                    gl::ActiveTexture(gl::TEXTURE0 + unit as u32);
+                    gl::BindTexture(gl::TEXTURE_2D, map.unwrap_or(0));
+                }
+            }
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+        }
+    }
+
+    /// Compiles `pbr.frag` with the vertex shader of the other lit examples. Every sampler gets its
+    /// own texture unit up front, the cubemaps of image based lighting can't share a unit with
+    /// the 2D maps of the material even when they are not used.
+    pub fn program() -> Result<ShaderProgram, Error> {
+        let program = ShaderProgram::from_files("src/shaders/vertex/lights.vert", "src/shaders/fragment/pbr.frag")?;
+        program.use_program();
+        program.set_int(c"irradianceMap", IRRADIANCE_UNIT);
+        program.set_int(c"prefilterMap", PREFILTER_UNIT);
+        program.set_int(c"brdfLUT", BRDF_LUT_UNIT);
+        program.set_bool(c"useIbl", false);
+        Ok(program)
+    }
+
+    impl Default for PbrMaterial {
+        fn default() -> Self {
+            Self::new(Vec3::ONE, 0.0, 0.5)
+        }
+    }
+}
@@ -0,0 +1,76 @@
+/// Picking on the GPU: objects drawn with their id instead of a colour, then the pixel under the
+/// cursor read back
+pub mod picking {
+    use std::io::Error;
+    use glam::Mat4;
+    use crate::framebuffer::framebuffer::{ColorFormat, DepthStencil, Framebuffer};
+    use crate::mesh::mesh::Mesh;
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// Integer target the objects are drawn into with their id, so the pixel under the cursor
+    /// tells which one is in front there, exactly as drawn, whatever the shape of the meshes.
+    /// Id 0 is the background. Unlike casting rays it costs a draw call per object and a read
+    /// back that waits for the GPU, so it is only worth doing on a click.
+    pub struct IdBuffer {
+        framebuffer: Framebuffer,
+        program: ShaderProgram
+    }
+
+    impl IdBuffer {
+        pub fn new(width: i32, height: i32) -> Result<Self, Error> {
+            let framebuffer = Framebuffer::new(width, height, 1, &[ColorFormat::R32UI], DepthStencil::Renderbuffer)?;
+            let program = ShaderProgram::from_files(
+                "src/shaders/vertex/light_source.vert",
+                "src/shaders/fragment/object_id.frag"
+            )?;
+            Ok(IdBuffer { framebuffer, program })
+        }
+
+        /// Recreates the target for the new window size.
+        pub fn resize(&mut self, width: i32, height: i32) -> Result<(), Error> {
+            self.framebuffer.resize(width, height)
+        }
+
+        /// Draws every `(id, mesh, model)` with its id, ids starting at 1. The framebuffer and
+        /// viewport bound before are bound back afterwards. Depth testing is expected to be
+        /// enabled.
+        pub fn render<'a>(&self, view: &Mat4, projection: &Mat4, objects: impl IntoIterator<Item = (u32, &'a Mesh, Mat4)>) {
+            let mut fbo = 0;
+            let mut viewport = [0; 4];
+            unsafe {
+                gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut fbo);
+                gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            }
+
+            self.framebuffer.bind();
+            unsafe {
+                gl::ClearBufferuiv(gl::COLOR, 0, [0u32; 4].as_ptr());
+                gl::Clear(gl::DEPTH_BUFFER_BIT);
+            }
+            self.program.use_program();
+            self.program.set_mat4(c"view", view);
+            self.program.set_mat4(c"projection", projection);
+            for (id, mesh, model) in objects {
+                self.program.set_uint(c"objectId", id);
+                self.program.set_mat4(c"model", &model);
+                mesh.draw(&self.program);
+            }
+
+            let [x, y, width, height] = viewport;
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, fbo as u32);
+                gl::Viewport(x, y, width, height);
+            }
+        }
+
+        /// Id of the object drawn at the pixel, from the top left corner like window coordinates,
+        /// `None` for the background or outside of the target.
+        pub fn id_at(&self, x: i32, y: i32) -> Result<Option<u32>, Error> {
+            if x < 0 || y < 0 || x >= self.framebuffer.width() || y >= self.framebuffer.height() {
+                return Ok(None);
+            }
+            let id = self.framebuffer.read_integer(0, x, self.framebuffer.height() - 1 - y)?;
+            Ok((id != 0).then_some(id))
+        }
+    }
+}
@@ -0,0 +1,377 @@
+/// Post-processing stack that renders the scene offscreen and runs it through a chain of fullscreen
+/// effects before it reaches the window
+pub mod postprocessing {
+    use std::io::Error;
+    use std::mem;
+    use std::ptr;
+    use crate::framebuffer::framebuffer::{ColorFormat, DepthStencil, Framebuffer};
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// Weights for the 3x3 convolution effect, from the top left to the bottom right sample.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Kernel {
+        Sharpen,
+        Blur,
+        EdgeDetection,
+        Custom([f32; 9])
+    }
+
+    /// Every effect the stack knows how to run, together with the settings it can be tweaked with
+    /// at runtime.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Effect {
+        Grayscale,
+        Inversion,
+        Kernel(Kernel),
+        Vignette { intensity: f32, radius: f32, softness: f32 },
+        ChromaticAberration { strength: f32 },
+        FilmGrain { intensity: f32 },
+        Fxaa { span_max: f32, reduce_min: f32, reduce_mul: f32 }
+    }
+
+    pub struct Pass {
+        pub effect: Effect,
+        pub enabled: bool
+    }
+
+    /// Two triangles covering the whole screen in normalized device coordinates, used to run a
+    /// fragment shader once per pixel.
+    pub struct ScreenQuad {
+        vao: u32,
+        vbo: u32
+    }
+
+    pub struct PostProcessingStack {
+        pub passes: Vec<Pass>,
+        scene: Framebuffer,
+        resolved_scene: Option<Framebuffer>,
+        ping_pong: [Framebuffer; 2],
+        quad: ScreenQuad,
+        programs: Vec<(&'static str, ShaderProgram)>,
+        width: i32,
+        height: i32
+    }
+
+    impl Kernel {
+        pub fn weights(&self) -> [f32; 9] {
+            match self {
+                Kernel::Sharpen => [
+                    -1.0, -1.0, -1.0,
+                    -1.0,  9.0, -1.0,
+                    -1.0, -1.0, -1.0
+                ],
+                Kernel::Blur => [
+                    1.0 / 16.0, 2.0 / 16.0, 1.0 / 16.0,
+                    2.0 / 16.0, 4.0 / 16.0, 2.0 / 16.0,
+                    1.0 / 16.0, 2.0 / 16.0, 1.0 / 16.0
+                ],
+                Kernel::EdgeDetection => [
+                    1.0,  1.0, 1.0,
+                    1.0, -8.0, 1.0,
+                    1.0,  1.0, 1.0
+                ],
+                Kernel::Custom(weights) => *weights
+            }
+        }
+    }
+
+    impl Effect {
+        /// Vignette with the settings used in the examples.
+        pub fn vignette() -> Self {
+            Effect::Vignette { intensity: 0.8, radius: 0.75, softness: 0.45 }
+        }
+
+        pub fn chromatic_aberration() -> Self {
+            Effect::ChromaticAberration { strength: 0.01 }
+        }
+
+        pub fn film_grain() -> Self {
+            Effect::FilmGrain { intensity: 0.08 }
+        }
+
+        /// FXAA with the defaults of the original implementation.
+        pub fn fxaa() -> Self {
+            Effect::Fxaa { span_max: 8.0, reduce_min: 1.0 / 128.0, reduce_mul: 1.0 / 8.0 }
+        }
+
+        /// Name of the fragment shader, in `src/shaders/fragment`, that runs the effect.
+        pub fn shader_name(&self) -> &'static str {
+            match self {
+                Effect::Grayscale => "post_grayscale",
+                Effect::Inversion => "post_inversion",
+                Effect::Kernel(_) => "post_kernel",
+                Effect::Vignette { .. } => "post_vignette",
+                Effect::ChromaticAberration { .. } => "post_chromatic_aberration",
+                Effect::FilmGrain { .. } => "post_film_grain",
+                Effect::Fxaa { .. } => "post_fxaa"
+            }
+        }
+
+        /// Whether both effects are the same kind of effect, ignoring their settings.
+        pub fn same_kind(&self, other: &Effect) -> bool {
+            match (self, other) {
+                (Effect::Kernel(a), Effect::Kernel(b)) => mem::discriminant(a) == mem::discriminant(b),
+                _ => mem::discriminant(self) == mem::discriminant(other)
+            }
+        }
+
+        fn set_uniforms(&self, program: &ShaderProgram, width: i32, height: i32, time: f32) {
+            let texel_size = [1.0 / width as f32, 1.0 / height as f32];
+            match self {
+                Effect::Grayscale | Effect::Inversion => {},
+                Effect::Kernel(kernel) => {
+                    program.set_vec2(c"texelSize", &texel_size);
+                    program.set_float_array(c"kernel", &kernel.weights());
+                },
+                Effect::Vignette { intensity, radius, softness } => {
+                    program.set_float(c"intensity", *intensity);
+                    program.set_float(c"radius", *radius);
+                    program.set_float(c"softness", *softness);
+                },
+                Effect::ChromaticAberration { strength } => {
+                    program.set_float(c"strength", *strength);
+                },
+                Effect::FilmGrain { intensity } => {
+                    program.set_float(c"intensity", *intensity);
+                    program.set_float(c"time", time);
+                },
+                Effect::Fxaa { span_max, reduce_min, reduce_mul } => {
+                    program.set_vec2(c"texelSize", &texel_size);
+                    program.set_float(c"spanMax", *span_max);
+                    program.set_float(c"reduceMin", *reduce_min);
+                    program.set_float(c"reduceMul", *reduce_mul);
+                }
+            }
+        }
+    }
+
+    impl ScreenQuad {
+        pub fn new() -> Self {
+            let quad_vertices: [f32; 24] = [
+                // positions  // texture coords
+                -1.0,  1.0,  0.0, 1.0,
+                -1.0, -1.0,  0.0, 0.0,
+                 1.0, -1.0,  1.0, 0.0,
+
+                -1.0,  1.0,  0.0, 1.0,
+                 1.0, -1.0,  1.0, 0.0,
+                 1.0,  1.0,  1.0, 1.0
+            ];
+            let (mut vao, mut vbo) = (0, 0);
+            unsafe {
+                gl::GenVertexArrays(1, &mut vao);
+                gl::GenBuffers(1, &mut vbo);
+                gl::BindVertexArray(vao);
+                gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    size_of_val(&quad_vertices) as isize,
+                    quad_vertices.as_ptr().cast(),
+                    gl::STATIC_DRAW
+                );
+
+                // Position attribute
+                gl::VertexAttribPointer(
+                    0,
+                    2,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    4 * size_of::<f32>() as i32,
+                    ptr::null()
+                );
+                gl::EnableVertexAttribArray(0);
+
+                // Texture attribute
+                gl::VertexAttribPointer(
+                    1,
+                    2,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    4 * size_of::<f32>() as i32,
+                    (2 * size_of::<f32>()) as *const _
+                );
+                gl::EnableVertexAttribArray(1);
+                gl::BindVertexArray(0);
+            }
+            ScreenQuad { vao, vbo }
+        }
+
+        pub fn draw(&self) {
+            unsafe {
+                gl::BindVertexArray(self.vao);
+                gl::DrawArrays(gl::TRIANGLES, 0, 6);
+                gl::BindVertexArray(0);
+            }
+        }
+    }
+
+    impl Default for ScreenQuad {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Drop for ScreenQuad {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteBuffers(1, &self.vbo);
+                gl::DeleteVertexArrays(1, &self.vao);
+            }
+        }
+    }
+
+    impl PostProcessingStack {
+        const VERTEX_SHADER: &'static str = "src/shaders/vertex/screen_quad.vert";
+        const EFFECTS: [Effect; 7] = [
+            Effect::Grayscale,
+            Effect::Inversion,
+            Effect::Kernel(Kernel::Blur),
+            Effect::Vignette { intensity: 0.0, radius: 0.0, softness: 0.0 },
+            Effect::ChromaticAberration { strength: 0.0 },
+            Effect::FilmGrain { intensity: 0.0 },
+            Effect::Fxaa { span_max: 0.0, reduce_min: 0.0, reduce_mul: 0.0 }
+        ];
+
+        /// Creates the offscreen targets for a window of the given size and compiles the shaders
+        /// of every effect. The scene is rendered into a target of `scene_format` with `samples`
+        /// samples per pixel, the intermediate ping-pong targets use the same format.
+        pub fn new(width: i32, height: i32, samples: i32, scene_format: ColorFormat) -> Result<Self, Error> {
+            let scene = Framebuffer::new(width, height, samples, &[scene_format], DepthStencil::Renderbuffer)?;
+            let resolved_scene = if samples > 1 {
+                Some(Framebuffer::new(width, height, 1, &[scene_format], DepthStencil::None)?)
+            } else {
+                None
+            };
+            let ping_pong = [
+                Framebuffer::new(width, height, 1, &[scene_format], DepthStencil::None)?,
+                Framebuffer::new(width, height, 1, &[scene_format], DepthStencil::None)?
+            ];
+
+            let mut programs = Vec::new();
+            for effect in Self::EFFECTS {
+                let name = effect.shader_name();
+                let program = ShaderProgram::from_files(
+                    Self::VERTEX_SHADER.to_string(),
+                    format!("src/shaders/fragment/{name}.frag")
+                )?;
+                program.use_program();
+                program.set_int(c"screenTexture", 0);
+                programs.push((name, program));
+            }
+
+            Ok(PostProcessingStack {
+                passes: Vec::new(),
+                scene,
+                resolved_scene,
+                ping_pong,
+                quad: ScreenQuad::new(),
+                programs,
+                width,
+                height
+            })
+        }
+
+        /// Appends an enabled pass at the end of the chain.
+        pub fn push(&mut self, effect: Effect) {
+            self.passes.push(Pass { effect, enabled: true });
+        }
+
+        /// Removes the pass of the same kind as `effect` if it is in the chain, otherwise appends
+        /// it at the end. Toggling effects in a different order builds a different chain.
+        pub fn toggle(&mut self, effect: Effect) {
+            match self.passes.iter().position(|pass| pass.effect.same_kind(&effect)) {
+                Some(index) => {
+                    self.passes.remove(index);
+                },
+                None => self.push(effect)
+            }
+        }
+
+        pub fn clear(&mut self) {
+            self.passes.clear();
+        }
+
+        /// Binds the scene target, everything drawn until `render` is called goes through the
+        /// post-processing chain.
+        pub fn begin_scene(&self) {
+            self.scene.bind();
+        }
+
+        /// Scene target the chain reads from, for passes outside the stack that produce the scene
+        /// instead of drawing it directly, like tone mapping an HDR target.
+        pub fn scene_target(&self) -> &Framebuffer {
+            &self.scene
+        }
+
+        /// Runs every enabled pass in order, each one reading the output of the previous one, with
+        /// the last one drawing to the window. `time` is in seconds and only used by animated
+        /// effects.
+        pub fn render(&self, time: f32) {
+            let input = self.resolve_scene();
+            let enabled: Vec<&Pass> = self.passes.iter().filter(|pass| pass.enabled).collect();
+            if enabled.is_empty() {
+                input.blit_to_default(0, self.width, self.height);
+                return;
+            }
+
+            let depth_test = unsafe { gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE };
+            unsafe {
+                gl::Disable(gl::DEPTH_TEST);
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+            let mut texture = input.color_texture(0);
+            for (i, pass) in enabled.iter().enumerate() {
+                if i == enabled.len() - 1 {
+                    Framebuffer::bind_default(self.width, self.height);
+                } else {
+                    self.ping_pong[i % 2].bind();
+                }
+                let program = self.program(&pass.effect);
+                program.use_program();
+                pass.effect.set_uniforms(program, self.width, self.height, time);
+                unsafe {
+                    gl::BindTexture(gl::TEXTURE_2D, texture);
+                }
+                self.quad.draw();
+                texture = self.ping_pong[i % 2].color_texture(0);
+            }
+            if depth_test {
+                unsafe { gl::Enable(gl::DEPTH_TEST); }
+            }
+        }
+
+        /// Recreates the offscreen targets for the new window size, keeping them while the window
+        /// is minimised.
+        pub fn resize(&mut self, width: i32, height: i32) -> Result<(), Error> {
+            if width <= 0 || height <= 0 {
+                return Ok(());
+            }
+            self.scene.resize(width, height)?;
+            if let Some(resolved_scene) = &mut self.resolved_scene {
+                resolved_scene.resize(width, height)?;
+            }
+            for target in &mut self.ping_pong {
+                target.resize(width, height)?;
+            }
+            self.width = width;
+            self.height = height;
+            Ok(())
+        }
+
+        fn resolve_scene(&self) -> &Framebuffer {
+            match &self.resolved_scene {
+                Some(resolved_scene) => {
+                    self.scene.blit_to(resolved_scene);
+                    resolved_scene
+                },
+                None => &self.scene
+            }
+        }
+
+        fn program(&self, effect: &Effect) -> &ShaderProgram {
+            let name = effect.shader_name();
+            // every effect gets compiled in new, so it is always there
+            &self.programs.iter().find(|(program_name, _)| *program_name == name).unwrap().1
+        }
+    }
+}
@@ -0,0 +1,163 @@
+/// Rays and what they hit, to find the object under the cursor on the CPU
+pub mod ray {
+    use glam::{Mat4, Vec3};
+    use crate::bounds::bounds::{Aabb, BoundingSphere};
+    use crate::mesh::mesh::Mesh;
+
+    /// Half line starting at `origin`. Intersections return the distance `t` along it to the
+    /// nearest hit in front of the origin, which is a distance in world units when `direction`
+    /// is unit length as `new` makes it.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Ray {
+        pub origin: Vec3,
+        pub direction: Vec3
+    }
+
+    impl Ray {
+        pub fn new(origin: Vec3, direction: Vec3) -> Self {
+            Ray { origin, direction: direction.normalize() }
+        }
+
+        pub fn at(&self, t: f32) -> Vec3 {
+            self.origin + self.direction * t
+        }
+
+        /// Same ray moved by the matrix. The direction keeps the length the matrix gives it, so
+        /// distances found against the moved ray are still distances along this one.
+        pub fn transformed(&self, matrix: &Mat4) -> Self {
+            Ray { origin: matrix.transform_point3(self.origin), direction: matrix.transform_vector3(self.direction) }
+        }
+
+        /// Slab test, 0 when the origin is inside the box.
+        pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+            let inverse = self.direction.recip();
+            let t1 = (aabb.min - self.origin) * inverse;
+            let t2 = (aabb.max - self.origin) * inverse;
+            let near = t1.min(t2).max_element();
+            let far = t1.max(t2).min_element();
+            if far >= near.max(0.0) { Some(near.max(0.0)) } else { None }
+        }
+
+        /// 0 when the origin is inside the sphere.
+        pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+            let offset = self.origin - sphere.center;
+            let a = self.direction.length_squared();
+            let half_b = offset.dot(self.direction);
+            let c = offset.length_squared() - sphere.radius * sphere.radius;
+            if c <= 0.0 {
+                return Some(0.0);
+            }
+            let discriminant = half_b * half_b - a * c;
+            if discriminant < 0.0 {
+                return None;
+            }
+            let t = (-half_b - discriminant.sqrt()) / a;
+            (t >= 0.0).then_some(t)
+        }
+
+        /// Möller-Trumbore, hitting both faces of the triangle.
+        pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+            let edge1 = b - a;
+            let edge2 = c - a;
+            let p = self.direction.cross(edge2);
+            let determinant = edge1.dot(p);
+            if determinant.abs() < 1e-8 {
+                // parallel to the triangle
+                return None;
+            }
+            let inverse = 1.0 / determinant;
+            let s = self.origin - a;
+            let u = s.dot(p) * inverse;
+            if !(0.0..=1.0).contains(&u) {
+                return None;
+            }
+            let q = s.cross(edge1);
+            let v = self.direction.dot(q) * inverse;
+            if v < 0.0 || u + v > 1.0 {
+                return None;
+            }
+            let t = edge2.dot(q) * inverse;
+            (t >= 0.0).then_some(t)
+        }
+
+        /// Nearest triangle of the mesh placed by `model` the ray goes through. The bounds are
+        /// tested first so most misses don't look at any triangle.
+        pub fn intersect_mesh(&self, mesh: &Mesh, model: &Mat4) -> Option<f32> {
+            let local = self.transformed(&model.inverse());
+            local.intersect_aabb(mesh.aabb())?;
+            mesh.indices.chunks_exact(3)
+                .filter_map(|triangle| local.intersect_triangle(
+                    mesh.vertices[triangle[0] as usize].position(),
+                    mesh.vertices[triangle[1] as usize].position(),
+                    mesh.vertices[triangle[2] as usize].position()
+                ))
+                .min_by(f32::total_cmp)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use glam::Vec4;
+        use crate::camera::camera::Camera;
+
+        const FORWARD: Ray = Ray { origin: Vec3::new(0.0, 0.0, 5.0), direction: Vec3::NEG_Z };
+
+        #[test]
+        fn boxes_hit_from_outside_and_inside() {
+            let aabb = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+            assert_eq!(FORWARD.intersect_aabb(&aabb), Some(4.0));
+            assert_eq!(Ray::new(Vec3::ZERO, Vec3::X).intersect_aabb(&aabb), Some(0.0));
+            // behind the origin, and beside the box along an axis aligned direction
+            assert_eq!(Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::Z).intersect_aabb(&aabb), None);
+            assert_eq!(Ray::new(Vec3::new(2.0, 0.0, 5.0), Vec3::NEG_Z).intersect_aabb(&aabb), None);
+        }
+
+        #[test]
+        fn spheres_hit_at_the_near_side() {
+            let sphere = BoundingSphere::new(Vec3::ZERO, 1.0);
+            assert_eq!(FORWARD.intersect_sphere(&sphere), Some(4.0));
+            assert_eq!(Ray::new(Vec3::ZERO, Vec3::Y).intersect_sphere(&sphere), Some(0.0));
+            assert_eq!(Ray::new(Vec3::new(0.0, 1.5, 5.0), Vec3::NEG_Z).intersect_sphere(&sphere), None);
+            assert_eq!(Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::Z).intersect_sphere(&sphere), None);
+        }
+
+        #[test]
+        fn triangles_hit_inside_their_edges_only() {
+            let (a, b, c) = (Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+            assert_eq!(FORWARD.intersect_triangle(a, b, c), Some(5.0));
+            // the winding doesn't matter
+            assert_eq!(FORWARD.intersect_triangle(a, c, b), Some(5.0));
+            assert_eq!(Ray::new(Vec3::new(0.9, 0.9, 5.0), Vec3::NEG_Z).intersect_triangle(a, b, c), None);
+            assert_eq!(Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::X).intersect_triangle(a, b, c), None);
+        }
+
+        #[test]
+        fn transformed_rays_keep_world_distances() {
+            let model = Mat4::from_translation(Vec3::new(0.0, 0.0, -3.0)) * Mat4::from_scale(Vec3::splat(2.0));
+            let local = FORWARD.transformed(&model.inverse());
+            let aabb = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+            // the box spans z from -5 to -1 in world space
+            let t = local.intersect_aabb(&aabb).unwrap();
+            assert!((t - 6.0).abs() < 1e-5);
+            assert!((FORWARD.at(t).z + 1.0).abs() < 1e-5);
+        }
+
+        #[test]
+        fn screen_points_unproject_through_the_camera() {
+            let camera = Camera::new(Vec3::new(0.0, 0.0, 5.0), Vec3::Y, false);
+            let viewport = Vec4::new(0.0, 0.0, 800.0, 600.0);
+            let projection = camera.get_projection_matrix(800.0 / 600.0, 0.5, 50.0);
+            let center = camera.screen_point_to_ray(400.0, 300.0, viewport, &projection);
+            assert!(center.direction.abs_diff_eq(camera.front(), 1e-5));
+            // starts on the near plane of the projection
+            assert!((center.origin.z - 4.5).abs() < 1e-4);
+
+            // the top edge is half the field of view up, the left edge as far left
+            let top = camera.screen_point_to_ray(400.0, 0.0, viewport, &projection);
+            assert!((top.direction.angle_between(center.direction).to_degrees() - camera.zoom * 0.5).abs() < 1e-3);
+            assert!(top.direction.y > 0.0);
+            assert!(camera.screen_point_to_ray(0.0, 300.0, viewport, &projection).direction.x < 0.0);
+        }
+    }
+}
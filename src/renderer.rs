@@ -0,0 +1,431 @@
+/// Draw items collected over a frame and submitted in an order that keeps state changes down
+pub mod renderer {
+    use std::cmp::Ordering;
+    use std::fmt::{Display, Formatter};
+    use glam::{Mat4, Vec3};
+    use crate::bounds::bounds::Frustum;
+    use crate::debug_view::debug_view::{DebugView, DebugViews};
+    use crate::material::material::{BlendMode, Material};
+    use crate::mesh::mesh::Mesh;
+    use crate::oit::oit::WeightedBlended;
+    use crate::outline::outline::{Outline, OutlinePass};
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// One mesh to draw with a material and a program, placed by `model`. The blend mode of the
+    /// material decides when and how it is drawn. Items with an outline get one once everything
+    /// else is drawn, when the queue has an outline pass.
+    #[derive(Clone, Copy)]
+    pub struct DrawItem<'a> {
+        pub mesh: &'a Mesh,
+        pub material: &'a Material,
+        pub program: &'a ShaderProgram,
+        pub model: Mat4,
+        pub outline: Option<Outline>
+    }
+
+    /// What submitting a queue cost, to compare scenes and orderings.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct RenderStats {
+        pub draw_calls: u32,
+        pub program_changes: u32,
+        pub material_changes: u32,
+        pub texture_binds: u32,
+        pub opaque: u32,
+        pub transparent: u32,
+        pub outlined: u32,
+        /// Items left out because they were outside the frustum
+        pub culled: u32
+    }
+
+    /// Items of one frame. Opaque and cutout items are drawn first, grouped by program, then by
+    /// textures, then by material, so each is bound once per group. Blended and additive items
+    /// follow from the farthest to the nearest, as blending needs, whatever that costs in state
+    /// changes. A queue made with a frustum drops the items outside of it as they are pushed.
+    ///
+    /// With `order_independent`, blended items go through weighted blended transparency instead
+    /// of being sorted, and additive ones, which don't depend on the order, are drawn last.
+    /// With `debug_view`, the items are drawn the way the view shows them.
+    #[derive(Default)]
+    pub struct RenderQueue<'a> {
+        opaque: Vec<DrawItem<'a>>,
+        transparent: Vec<DrawItem<'a>>,
+        outlined: Vec<DrawItem<'a>>,
+        frustum: Option<Frustum>,
+        order_independent: Option<&'a WeightedBlended>,
+        outline_pass: Option<&'a OutlinePass>,
+        debug_view: Option<(DebugView, &'a DebugViews)>,
+        culled: u32
+    }
+
+    impl<'a> DrawItem<'a> {
+        /// Opaque item.
+        pub fn new(mesh: &'a Mesh, material: &'a Material, program: &'a ShaderProgram, model: Mat4) -> Self {
+            DrawItem { mesh, material, program, model, outline: None }
+        }
+
+        /// Same item, outlined.
+        pub fn outlined(self, outline: Outline) -> Self {
+            DrawItem { outline: Some(outline), ..self }
+        }
+
+        pub fn blend(&self) -> BlendMode {
+            self.material.blend
+        }
+
+        /// Whether the mesh bounds, placed by the model matrix, reach into the frustum. The
+        /// sphere is cheaper to test and rejects most items, the box is tighter for the rest.
+        pub fn is_visible(&self, frustum: &Frustum) -> bool {
+            frustum.intersects_sphere(&self.mesh.bounding_sphere().transformed(&self.model))
+                && frustum.intersects_aabb(&self.mesh.aabb().transformed(&self.model))
+        }
+
+        /// World space position the item is sorted by when transparent, the centre of its bounds
+        /// rather than the origin of the mesh which can be anywhere.
+        fn position(&self) -> Vec3 {
+            self.model.transform_point3(self.mesh.bounding_sphere().center)
+        }
+    }
+
+    impl Display for RenderStats {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "{} draw calls ({} opaque, {} transparent, {} outlined), {} culled, {} program changes, {} material changes, {} texture binds",
+                self.draw_calls, self.opaque, self.transparent, self.outlined, self.culled, self.program_changes, self.material_changes, self.texture_binds
+            )
+        }
+    }
+
+    impl<'a> RenderQueue<'a> {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Queue culling what is outside `frustum`, usually `Camera::frustum`.
+        pub fn with_frustum(frustum: Frustum) -> Self {
+            RenderQueue { frustum: Some(frustum), ..Self::default() }
+        }
+
+        /// Same queue drawing its blended items with weighted blended transparency.
+        pub fn order_independent(self, targets: &'a WeightedBlended) -> Self {
+            RenderQueue { order_independent: Some(targets), ..self }
+        }
+
+        /// Same queue drawing the outlines of the outlined items. The framebuffer drawn into needs
+        /// a stencil buffer, which the window has by default.
+        pub fn outlines(self, pass: &'a OutlinePass) -> Self {
+            RenderQueue { outline_pass: Some(pass), ..self }
+        }
+
+        /// Same queue drawn in one of the debug views, with the programs and settings of `views`.
+        pub fn debug_view(self, view: DebugView, views: &'a DebugViews) -> Self {
+            RenderQueue { debug_view: Some((view, views)), ..self }
+        }
+
+        pub fn push(&mut self, item: DrawItem<'a>) {
+            if let Some(frustum) = &self.frustum
+                && !item.is_visible(frustum) {
+                self.culled += 1;
+                return;
+            }
+            if item.outline.is_some() && self.outline_pass.is_some() {
+                self.outlined.push(item);
+            }
+            if item.blend().is_transparent() {
+                self.transparent.push(item);
+            } else {
+                self.opaque.push(item);
+            }
+        }
+
+        /// Items queued, culled ones left aside.
+        pub fn len(&self) -> usize {
+            self.opaque.len() + self.transparent.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Draws every item and empties the queue, keeping its frustum. `setup` is called each time
+        /// a program starts being used, to set what it shares between items such as the view,
+        /// projection and lights. `eye` is the camera position transparent items are sorted
+        /// against, `time` drives material animations. Depth testing is expected to be enabled,
+        /// and blending disabled.
+        pub fn render(&mut self, eye: Vec3, time: f32, mut setup: impl FnMut(&ShaderProgram)) -> RenderStats {
+            self.opaque.sort_by(|a, b| {
+                a.program.shader_program_id.cmp(&b.program.shader_program_id)
+                    .then_with(|| a.material.maps().cmp(&b.material.maps()))
+                    .then_with(|| (a.material as *const Material).cmp(&(b.material as *const Material)))
+            });
+            self.transparent.sort_by(|a, b| {
+                let distance_a = a.position().distance_squared(eye);
+                let distance_b = b.position().distance_squared(eye);
+                distance_b.partial_cmp(&distance_a).unwrap_or(Ordering::Equal)
+            });
+
+            let mut state = State::default();
+            let mut stats = RenderStats {
+                opaque: self.opaque.len() as u32,
+                transparent: self.transparent.len() as u32,
+                outlined: self.outlined.len() as u32,
+                culled: std::mem::take(&mut self.culled),
+                ..RenderStats::default()
+            };
+
+            let debug_view = self.debug_view.filter(|(view, _)| *view != DebugView::Shaded);
+            // the overlays draw the items again once they are drained
+            let overlaid: Vec<DrawItem> = match debug_view {
+                Some((DebugView::Wireframe | DebugView::VertexNormals, _)) => self.opaque.iter().chain(&self.transparent).copied().collect(),
+                _ => Vec::new()
+            };
+            let isolated_programs = match debug_view {
+                Some((DebugView::Light(light), _)) => {
+                    state.isolated_light = Some(light.index());
+                    unique_programs(self.opaque.iter().chain(&self.transparent))
+                }
+                _ => Vec::new()
+            };
+            if let Some((view, views)) = debug_view
+                && let Some(mode) = view.surface_mode() {
+                self.draw_surfaces(mode, views, &mut setup, &mut stats);
+            }
+
+            for item in self.opaque.drain(..) {
+                state.draw(&item, time, &mut setup, &mut stats);
+            }
+
+            if let Some(targets) = self.order_independent {
+                let (blended, additive): (Vec<_>, Vec<_>) = self.transparent.drain(..)
+                    .partition(|item| item.blend() == BlendMode::Blended);
+                if !blended.is_empty() {
+                    let target = targets.begin();
+                    state.reset(true);
+                    for item in &blended {
+                        state.draw(item, time, &mut setup, &mut stats);
+                    }
+                    targets.composite(target);
+                    // leave the programs drawing normally for whoever uses them next
+                    for program in unique_programs(&blended) {
+                        program.use_program();
+                        program.set_bool(c"weightedBlended", false);
+                    }
+                    // the composite pass used its own program and textures
+                    state.reset(false);
+                }
+                self.transparent = additive;
+            }
+
+            if !self.transparent.is_empty() {
+                unsafe {
+                    gl::Enable(gl::BLEND);
+                    // transparent items still hide behind opaque ones but not behind each other
+                    gl::DepthMask(gl::FALSE);
+                }
+                for item in self.transparent.drain(..) {
+                    state.set_blend(item.blend());
+                    state.draw(&item, time, &mut setup, &mut stats);
+                }
+                unsafe {
+                    gl::DepthMask(gl::TRUE);
+                    gl::Disable(gl::BLEND);
+                }
+            }
+
+            if let Some((view, views)) = debug_view
+                && !overlaid.is_empty() {
+                draw_overlay(view, views, &overlaid, &mut setup, &mut stats);
+            }
+            for program in isolated_programs {
+                program.use_program();
+                program.set_bool(c"isolateLight", false);
+            }
+
+            if let Some(pass) = self.outline_pass
+                && !self.outlined.is_empty() {
+                self.draw_outlines(pass, &mut setup, &mut stats);
+            }
+            stats
+        }
+
+        /// Draws every item, transparent ones too, with the flat program of the debug views
+        /// replacing the programs of the items, and empties the queue but for the outlines.
+        fn draw_surfaces(&mut self, mode: u32, views: &DebugViews, setup: &mut impl FnMut(&ShaderProgram), stats: &mut RenderStats) {
+            let program = &views.surface;
+            program.use_program();
+            setup(program);
+            program.set_int(c"debugView", mode);
+            program.set_float(c"checkerCells", views.checker_cells);
+            program.set_float(c"depthRange", views.depth_range);
+            stats.program_changes += 1;
+            for item in self.opaque.drain(..).chain(self.transparent.drain(..)) {
+                program.set_mat4(c"model", &item.model);
+                item.mesh.draw(program);
+                stats.draw_calls += 1;
+            }
+        }
+
+        /// Marks the silhouettes of the outlined items in the stencil buffer, then draws them grown
+        /// where nothing is marked. Depth testing is off for both so the outlines of hidden parts
+        /// show as well.
+        fn draw_outlines(&mut self, pass: &OutlinePass, setup: &mut impl FnMut(&ShaderProgram), stats: &mut RenderStats) {
+            let program = &pass.program;
+            program.use_program();
+            setup(program);
+            stats.program_changes += 1;
+            unsafe {
+                gl::Disable(gl::DEPTH_TEST);
+                gl::Enable(gl::STENCIL_TEST);
+                gl::StencilMask(0xFF);
+                gl::Clear(gl::STENCIL_BUFFER_BIT);
+                gl::StencilFunc(gl::ALWAYS, 1, 0xFF);
+                gl::StencilOp(gl::KEEP, gl::KEEP, gl::REPLACE);
+                gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+            }
+            for item in &self.outlined {
+                program.set_mat4(c"model", &item.model);
+                item.mesh.draw(program);
+                stats.draw_calls += 1;
+            }
+
+            unsafe {
+                gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+                gl::StencilFunc(gl::NOTEQUAL, 1, 0xFF);
+                gl::StencilMask(0x00);
+            }
+            for item in self.outlined.drain(..) {
+                let Some(outline) = item.outline else { continue };
+                program.set_vec3(c"outlineColor", &outline.color.to_array());
+                program.set_mat4(c"model", &outline.grown(item.mesh, &item.model));
+                item.mesh.draw(program);
+                stats.draw_calls += 1;
+            }
+            unsafe {
+                gl::StencilMask(0xFF);
+                gl::Disable(gl::STENCIL_TEST);
+                gl::Enable(gl::DEPTH_TEST);
+            }
+        }
+    }
+
+    /// Wireframe or vertex normals of the items over what is drawn already. The lines are
+    /// pulled slightly towards the camera so the surfaces they lie on don't hide them.
+    fn draw_overlay(view: DebugView, views: &DebugViews, items: &[DrawItem], setup: &mut impl FnMut(&ShaderProgram), stats: &mut RenderStats) {
+        let wireframe = view == DebugView::Wireframe;
+        let program = if wireframe { &views.wireframe } else { &views.vertex_normals };
+        program.use_program();
+        setup(program);
+        program.set_vec3(c"wireColor", &views.wire_color.to_array());
+        program.set_vec3(c"normalColor", &views.normal_color.to_array());
+        program.set_float(c"normalLength", views.normal_length);
+        stats.program_changes += 1;
+        unsafe {
+            if wireframe {
+                gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+                gl::Enable(gl::POLYGON_OFFSET_LINE);
+                gl::PolygonOffset(-1.0, -1.0);
+            }
+            gl::DepthFunc(gl::LEQUAL);
+        }
+        for item in items {
+            program.set_mat4(c"model", &item.model);
+            item.mesh.draw(program);
+            stats.draw_calls += 1;
+        }
+        unsafe {
+            gl::DepthFunc(gl::LESS);
+            if wireframe {
+                gl::Disable(gl::POLYGON_OFFSET_LINE);
+                gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+            }
+        }
+    }
+
+    /// Programs of the items, each once.
+    fn unique_programs<'a, 'b: 'a>(items: impl IntoIterator<Item = &'a DrawItem<'b>>) -> Vec<&'b ShaderProgram> {
+        let mut programs: Vec<&ShaderProgram> = items.into_iter().map(|item| item.program).collect();
+        programs.sort_by_key(|program| program.shader_program_id);
+        programs.dedup_by_key(|program| program.shader_program_id);
+        programs
+    }
+
+    /// What is bound right now, so only what differs from the previous item gets bound again.
+    #[derive(Default)]
+    struct State {
+        program: Option<u32>,
+        material: Option<*const Material>,
+        textures: [Option<u32>; 4],
+        blend: Option<BlendMode>,
+        weighted_blended: bool,
+        /// Light programs are set up to show alone, see `DebugView::Light`.
+        isolated_light: Option<u32>
+    }
+
+    impl State {
+        /// Forgets everything bound, for after something else used the context. Programs used
+        /// from then on are set up for the weighted blended pass or not.
+        fn reset(&mut self, weighted_blended: bool) {
+            *self = State { weighted_blended, isolated_light: self.isolated_light, ..State::default() };
+        }
+
+        fn set_blend(&mut self, blend: BlendMode) {
+            if self.blend == Some(blend) {
+                return;
+            }
+            unsafe {
+                match blend {
+                    BlendMode::Additive => gl::BlendFunc(gl::SRC_ALPHA, gl::ONE),
+                    _ => gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA)
+                }
+            }
+            self.blend = Some(blend);
+        }
+
+        fn draw(&mut self, item: &DrawItem, time: f32, setup: &mut impl FnMut(&ShaderProgram), stats: &mut RenderStats) {
+            let program = item.program.shader_program_id;
+            if self.program != Some(program) {
+                item.program.use_program();
+                setup(item.program);
+                item.program.set_bool(c"weightedBlended", self.weighted_blended);
+                if let Some(light) = self.isolated_light {
+                    item.program.set_bool(c"isolateLight", true);
+                    item.program.set_int(c"isolatedLight", light);
+                }
+                self.program = Some(program);
+                // material uniforms belong to the program, the new one hasn't got them yet
+                self.material = None;
+                stats.program_changes += 1;
+            }
+
+            let material = item.material as *const Material;
+            if self.material != Some(material) {
+                item.material.set_uniforms(item.program, time);
+                self.material = Some(material);
+                stats.material_changes += 1;
+
+                for (unit, texture) in item.material.maps() {
+                    let bound = &mut self.textures[unit as usize];
+                    if *bound != Some(texture) {
+                        unsafe {
+                            gl::ActiveTexture(gl::TEXTURE0 + unit);
+                            gl::BindTexture(gl::TEXTURE_2D, texture);
+                        }
+                        *bound = Some(texture);
+                        stats.texture_binds += 1;
+                    }
+                }
+                unsafe {
+                    gl::ActiveTexture(gl::TEXTURE0);
+                }
+            }
+
+            item.program.set_mat4(c"model", &item.model);
+            item.mesh.draw(item.program);
+            if !item.mesh.textures.is_empty() {
+                // meshes with their own textures bind them from unit 0 up
+                self.textures = [None; 4];
+            }
+            stats.draw_calls += 1;
+        }
+    }
+}
@@ -0,0 +1,698 @@
+/// Scenes described in TOML files instead of code: camera, lights, textures, materials, shaders and
+/// the objects using them
+pub mod scene_file {
+    use std::collections::BTreeMap;
+    use std::ffi::CString;
+    use std::io::Error;
+    use std::path::{Path, PathBuf};
+    use glam::{Mat4, Vec2, Vec3};
+    use serde::Deserialize;
+    use crate::camera::camera::Camera;
+    use crate::debug_view::debug_view::{DebugView, DebugViews};
+    use crate::headless::headless::Scene;
+    use crate::lights::lights::{DirectionalLight, PointLight, SpotLight, MAX_POINT_LIGHTS};
+    use crate::material::material::{BlendMode, Emission, Material, SpecularMap};
+    use crate::mesh::mesh::Mesh;
+    use crate::model::model::Model;
+    use crate::outline::outline::{Outline, OutlinePass};
+    use crate::renderer::renderer::{DrawItem, RenderQueue, RenderStats};
+    use crate::shaders::shaders::ShaderProgram;
+    use crate::shapes::shapes;
+    use crate::textures::textures::Texture;
+
+    /// Shader used by objects that don't name one, always available without being declared.
+    pub const DEFAULT_SHADER: &str = "material";
+
+    /// Everything a scene file contains. Paths are relative to the working directory, like
+    /// everywhere else in the examples. See `scenes/multiple_lights.toml` for an example.
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct SceneDescription {
+        #[serde(default = "SceneDescription::default_background")]
+        pub background: Vec3,
+        /// Draws a small cube in the colour of each point light.
+        #[serde(default)]
+        pub lamps: bool,
+        #[serde(default)]
+        pub camera: CameraDescription,
+        #[serde(default)]
+        pub textures: BTreeMap<String, TextureDescription>,
+        #[serde(default)]
+        pub shaders: BTreeMap<String, ShaderDescription>,
+        #[serde(default)]
+        pub materials: BTreeMap<String, MaterialDescription>,
+        pub directional_light: Option<DirectionalLightDescription>,
+        #[serde(default)]
+        pub point_lights: Vec<PointLightDescription>,
+        pub flashlight: Option<FlashlightDescription>,
+        #[serde(default)]
+        pub objects: Vec<ObjectDescription>
+    }
+
+    /// Starting pose of the camera, angles in degrees.
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct CameraDescription {
+        pub position: Vec3,
+        pub yaw: f32,
+        pub pitch: f32,
+        pub fov: f32,
+        /// Keeps the camera at the same height when moving, like walking.
+        pub fps: bool
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct TextureDescription {
+        pub path: PathBuf,
+        /// Colour textures stored in sRGB, see `Texture::load_srgb_texture`.
+        #[serde(default)]
+        pub srgb: bool
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct ShaderDescription {
+        pub vertex: PathBuf,
+        pub fragment: PathBuf
+    }
+
+    /// `Material` with the maps given by texture name.
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct MaterialDescription {
+        pub diffuse: Option<String>,
+        pub diffuse_color: Vec3,
+        pub specular: Option<String>,
+        /// Tints the highlights with the specular map instead of reading an intensity from it.
+        pub specular_is_color: bool,
+        pub specular_color: Vec3,
+        pub shininess: f32,
+        pub emission: Option<EmissionDescription>,
+        pub opacity: Option<String>,
+        pub alpha: f32,
+        pub alpha_cutoff: f32,
+        /// `"opaque"`, `"cutout"`, `"blended"` or `"additive"`.
+        pub blend: BlendMode
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct EmissionDescription {
+        pub texture: String,
+        #[serde(default = "one")]
+        pub intensity: f32,
+        #[serde(default)]
+        pub scroll: Vec2,
+        #[serde(default)]
+        pub pulse_frequency: f32
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct DirectionalLightDescription {
+        pub direction: Vec3,
+        #[serde(default = "DirectionalLightDescription::default_ambient")]
+        pub ambient: Vec3,
+        #[serde(default = "DirectionalLightDescription::default_diffuse")]
+        pub diffuse: Vec3,
+        #[serde(default = "white")]
+        pub specular: Vec3
+    }
+
+    /// Point light, the attenuation defaults to the one of `PointLight::new`.
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct PointLightDescription {
+        pub position: Vec3,
+        #[serde(default = "PointLightDescription::default_ambient")]
+        pub ambient: Vec3,
+        #[serde(default = "white")]
+        pub diffuse: Vec3,
+        #[serde(default = "white")]
+        pub specular: Vec3,
+        pub constant: Option<f32>,
+        pub linear: Option<f32>,
+        pub quadratic: Option<f32>
+    }
+
+    /// Spotlight held by the camera, see `SpotLight::flashlight`.
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct FlashlightDescription {
+        #[serde(default = "white")]
+        pub color: Vec3,
+        /// Whether it starts switched on.
+        #[serde(default = "yes")]
+        pub on: bool
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Shape {
+        Cube,
+        Sphere
+    }
+
+    /// One of `mesh` or `model` must be given. Objects are placed by scaling, then rotating
+    /// `angle` degrees around `axis`, then moving to `position`. A non zero `spin` keeps rotating
+    /// them that many degrees per second.
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct ObjectDescription {
+        pub name: Option<String>,
+        pub mesh: Option<Shape>,
+        /// Model file in any format `Model::load` reads, every mesh in it is drawn where its node
+        /// places it, with the object's material.
+        pub model: Option<PathBuf>,
+        pub material: Option<String>,
+        pub shader: Option<String>,
+        #[serde(default)]
+        pub position: Vec3,
+        #[serde(default = "ObjectDescription::default_axis")]
+        pub axis: Vec3,
+        #[serde(default)]
+        pub angle: f32,
+        #[serde(default)]
+        pub spin: f32,
+        #[serde(default = "white")]
+        pub scale: Vec3,
+        /// Outlined all the time, whether selected or not.
+        pub outline: Option<OutlineDescription>
+    }
+
+    /// `Outline` of an object, by default the one of `Outline::default`.
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct OutlineDescription {
+        pub color: Vec3,
+        pub thickness: f32
+    }
+
+    /// A scene file loaded on the GPU, ready to be drawn.
+    pub struct LoadedScene {
+        /// Camera placed at the start pose of the file, used when rendered as a `Scene`.
+        pub camera: Camera,
+        pub flashlight_on: bool,
+        background: Vec3,
+        programs: Vec<ShaderProgram>,
+        lamp: Option<(ShaderProgram, Mesh)>,
+        meshes: Vec<Mesh>,
+        textures: Vec<u32>,
+        objects: Vec<LoadedObject>,
+        outline_pass: OutlinePass,
+        /// Object outlined with `selection_outline`, by index in the file.
+        pub selected: Option<usize>,
+        pub selection_outline: Outline,
+        debug_views: DebugViews,
+        /// How the objects are drawn, `DebugView::Shaded` but when debugging.
+        pub debug_view: DebugView,
+        directional_light: DirectionalLight,
+        point_lights: Vec<PointLight>,
+        flashlight_color: Vec3
+    }
+
+    struct LoadedObject {
+        name: Option<String>,
+        /// Index of each mesh and where its node places it in the object
+        meshes: Vec<(usize, Mat4)>,
+        program: usize,
+        material: Material,
+        position: Vec3,
+        axis: Vec3,
+        angle: f32,
+        spin: f32,
+        scale: Vec3,
+        outline: Option<Outline>
+    }
+
+    impl SceneDescription {
+        /// Reads, parses and checks a scene file. Syntax errors point at the line and column,
+        /// dangling references name the object and what it refers to.
+        pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+            let path = path.as_ref();
+            let source = std::fs::read_to_string(path)
+                .map_err(|e| Error::other(format!("ERROR::SCENE_FILE::READ {}\n{e}", path.display())))?;
+            Self::parse(&source)
+                .map_err(|(kind, message)| Error::other(format!("ERROR::SCENE_FILE::{kind} {}\n{message}", path.display())))
+        }
+
+        fn parse(source: &str) -> Result<Self, (&'static str, String)> {
+            let description: SceneDescription = toml::from_str(source).map_err(|e| ("PARSE", e.to_string()))?;
+            description.validate().map_err(|message| ("INVALID", message))?;
+            Ok(description)
+        }
+
+        fn validate(&self) -> Result<(), String> {
+            if self.point_lights.len() > MAX_POINT_LIGHTS {
+                return Err(format!("{} point lights, the shaders support at most {MAX_POINT_LIGHTS}", self.point_lights.len()));
+            }
+            for (name, material) in &self.materials {
+                let maps = [
+                    ("diffuse", material.diffuse.as_ref()),
+                    ("specular", material.specular.as_ref()),
+                    ("emission", material.emission.as_ref().map(|emission| &emission.texture)),
+                    ("opacity", material.opacity.as_ref())
+                ];
+                for (map, texture) in maps {
+                    if let Some(texture) = texture
+                        && !self.textures.contains_key(texture) {
+                        return Err(format!(
+                            "material \"{name}\" uses the unknown texture \"{texture}\" as {map} map, declared textures: {}",
+                            names(&self.textures)
+                        ));
+                    }
+                }
+            }
+            for (i, object) in self.objects.iter().enumerate() {
+                let label = match &object.name {
+                    Some(name) => format!("object \"{name}\""),
+                    None => format!("object {i}")
+                };
+                match (&object.mesh, &object.model) {
+                    (None, None) => return Err(format!("{label} needs either a mesh or a model")),
+                    (Some(_), Some(_)) => return Err(format!("{label} has both a mesh and a model, only one is allowed")),
+                    _ => {}
+                }
+                if let Some(material) = &object.material
+                    && !self.materials.contains_key(material) {
+                    return Err(format!("{label} uses the unknown material \"{material}\", declared materials: {}", names(&self.materials)));
+                }
+                if let Some(shader) = &object.shader
+                    && shader != DEFAULT_SHADER
+                    && !self.shaders.contains_key(shader) {
+                    return Err(format!(
+                        "{label} uses the unknown shader \"{shader}\", declared shaders: {} (\"{DEFAULT_SHADER}\" is always available)",
+                        names(&self.shaders)
+                    ));
+                }
+                if object.axis.length_squared() == 0.0 {
+                    return Err(format!("{label} rotates around a zero length axis"));
+                }
+            }
+            Ok(())
+        }
+
+        fn default_background() -> Vec3 {
+            Vec3::splat(0.1)
+        }
+    }
+
+    impl Default for CameraDescription {
+        fn default() -> Self {
+            CameraDescription {
+                position: Vec3::new(0.0, 0.0, 3.0),
+                yaw: -90.0,
+                pitch: 0.0,
+                fov: 45.0,
+                fps: false
+            }
+        }
+    }
+
+    impl Default for MaterialDescription {
+        /// Same as `Material::default`.
+        fn default() -> Self {
+            let material = Material::default();
+            MaterialDescription {
+                diffuse: None,
+                diffuse_color: material.diffuse_color,
+                specular: None,
+                specular_is_color: false,
+                specular_color: material.specular_color,
+                shininess: material.shininess,
+                emission: None,
+                opacity: None,
+                alpha: material.alpha,
+                alpha_cutoff: material.alpha_cutoff,
+                blend: material.blend
+            }
+        }
+    }
+
+    impl DirectionalLightDescription {
+        fn default_ambient() -> Vec3 {
+            Vec3::splat(0.2)
+        }
+
+        fn default_diffuse() -> Vec3 {
+            Vec3::splat(0.5)
+        }
+    }
+
+    impl PointLightDescription {
+        fn default_ambient() -> Vec3 {
+            Vec3::splat(0.1)
+        }
+    }
+
+    impl ObjectDescription {
+        fn default_axis() -> Vec3 {
+            Vec3::Y
+        }
+    }
+
+    impl Default for OutlineDescription {
+        /// Same as `Outline::default`.
+        fn default() -> Self {
+            let outline = Outline::default();
+            OutlineDescription { color: outline.color, thickness: outline.thickness }
+        }
+    }
+
+    impl LoadedObject {
+        /// World matrix `time` seconds in.
+        fn model(&self, time: f32) -> Mat4 {
+            let rotation = Mat4::from_axis_angle(self.axis, (self.angle + self.spin * time).to_radians());
+            Mat4::from_translation(self.position) * rotation * Mat4::from_scale(self.scale)
+        }
+    }
+
+    impl LoadedScene {
+        /// Loads a scene file, see `SceneDescription::from_file`. A context must be current.
+        pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+            Self::new(&SceneDescription::from_file(path)?)
+        }
+
+        /// Compiles the shaders, loads the textures and models and builds the meshes of a scene.
+        pub fn new(description: &SceneDescription) -> Result<Self, Error> {
+            let mut textures = Vec::new();
+            let mut texture_ids = BTreeMap::new();
+            for (name, texture) in &description.textures {
+                let id = load_texture(texture)?;
+                textures.push(id);
+                texture_ids.insert(name.as_str(), id);
+            }
+
+            let mut programs = vec![ShaderProgram::from_files("src/shaders/vertex/lights.vert", "src/shaders/fragment/material.frag")?];
+            let mut program_indices = BTreeMap::from([(DEFAULT_SHADER, 0)]);
+            for (name, shader) in &description.shaders {
+                program_indices.insert(name.as_str(), programs.len());
+                programs.push(ShaderProgram::from_files(&shader.vertex, &shader.fragment)?);
+            }
+
+            let mut meshes = Vec::new();
+            let mut shape_meshes: Vec<(Shape, usize)> = Vec::new();
+            let mut objects = Vec::new();
+            for object in &description.objects {
+                let object_meshes = match (object.mesh, &object.model) {
+                    (Some(shape), _) => {
+                        let index = match shape_meshes.iter().find(|(loaded, _)| *loaded == shape) {
+                            Some((_, index)) => *index,
+                            None => {
+                                meshes.push(match shape {
+                                    Shape::Cube => shapes::cube(),
+                                    Shape::Sphere => shapes::uv_sphere(64, 32)
+                                });
+                                shape_meshes.push((shape, meshes.len() - 1));
+                                meshes.len() - 1
+                            }
+                        };
+                        vec![(index, Mat4::IDENTITY)]
+                    }
+                    (None, Some(path)) => {
+                        let model = Model::load(path)?;
+                        let first = meshes.len();
+                        let mut placed = Vec::new();
+                        model.graph.traverse(|_, node| {
+                            placed.extend(node.data.iter().map(|mesh| (first + mesh, *node.world())));
+                        });
+                        meshes.extend(model.meshes);
+                        placed
+                    }
+                    (None, None) => Vec::new()
+                };
+                let material = match &object.material {
+                    Some(name) => build_material(&description.materials[name], &texture_ids),
+                    None => Material::default()
+                };
+                objects.push(LoadedObject {
+                    name: object.name.clone(),
+                    meshes: object_meshes,
+                    program: program_indices[object.shader.as_deref().unwrap_or(DEFAULT_SHADER)],
+                    material,
+                    position: object.position,
+                    axis: object.axis.normalize(),
+                    angle: object.angle,
+                    spin: object.spin,
+                    scale: object.scale,
+                    outline: object.outline.as_ref().map(|outline| Outline::new(outline.color, outline.thickness))
+                });
+            }
+
+            let lamp = if description.lamps {
+                let program = ShaderProgram::from_files("src/shaders/vertex/light_source.vert", "src/shaders/fragment/light_source.frag")?;
+                Some((program, shapes::cube()))
+            } else {
+                None
+            };
+
+            let camera_description = &description.camera;
+            let mut camera = Camera::new(camera_description.position, Vec3::Y, camera_description.fps);
+            camera.set_orientation(camera_description.yaw, camera_description.pitch);
+            camera.zoom = camera_description.fov;
+
+            let directional_light = match &description.directional_light {
+                Some(light) => DirectionalLight {
+                    direction: light.direction,
+                    ambient: light.ambient,
+                    diffuse: light.diffuse,
+                    specular: light.specular
+                },
+                None => DirectionalLight {
+                    direction: Vec3::NEG_Y,
+                    ambient: Vec3::ZERO,
+                    diffuse: Vec3::ZERO,
+                    specular: Vec3::ZERO
+                }
+            };
+            let point_lights = description.point_lights.iter()
+                .map(|light| {
+                    let default = PointLight::new(light.position, light.ambient, light.diffuse, light.specular);
+                    PointLight {
+                        constant: light.constant.unwrap_or(default.constant),
+                        linear: light.linear.unwrap_or(default.linear),
+                        quadratic: light.quadratic.unwrap_or(default.quadratic),
+                        ..default
+                    }
+                })
+                .collect();
+
+            Ok(LoadedScene {
+                camera,
+                flashlight_on: description.flashlight.as_ref().is_some_and(|flashlight| flashlight.on),
+                background: description.background,
+                programs,
+                lamp,
+                meshes,
+                textures,
+                objects,
+                outline_pass: OutlinePass::new()?,
+                selected: None,
+                selection_outline: Outline::default(),
+                debug_views: DebugViews::new()?,
+                debug_view: DebugView::Shaded,
+                directional_light,
+                point_lights,
+                flashlight_color: description.flashlight.as_ref().map_or(Vec3::ZERO, |flashlight| flashlight.color)
+            })
+        }
+
+        /// Draws the scene seen from `camera` into the bound framebuffer, `time` in seconds drives
+        /// the spinning objects and the material animations. Returns what the objects cost to
+        /// draw, lamps left aside.
+        pub fn draw(&self, camera: &Camera, width: i32, height: i32, time: f32) -> RenderStats {
+            let view = camera.get_view_matrix();
+            let aspect_ratio = width as f32 / height as f32;
+            let projection = camera.get_projection_matrix(aspect_ratio, 0.1, 100.0);
+            let flashlight_color = if self.flashlight_on { self.flashlight_color } else { Vec3::ZERO };
+            let spot_light = SpotLight::flashlight(camera.position, camera.front(), flashlight_color);
+            let dark_light = PointLight::dark();
+            unsafe {
+                gl::Enable(gl::DEPTH_TEST);
+                gl::ClearColor(self.background.x, self.background.y, self.background.z, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            }
+
+            let mut queue = RenderQueue::with_frustum(camera.frustum(aspect_ratio, 0.1, 100.0))
+                .outlines(&self.outline_pass)
+                .debug_view(self.debug_view, &self.debug_views);
+            for (index, object) in self.objects.iter().enumerate() {
+                let model = object.model(time);
+                let outline = if self.selected == Some(index) { Some(self.selection_outline) } else { object.outline };
+                for (mesh, placement) in &object.meshes {
+                    let item = DrawItem::new(&self.meshes[*mesh], &object.material, &self.programs[object.program], model * *placement);
+                    queue.push(match outline {
+                        Some(outline) => item.outlined(outline),
+                        None => item
+                    });
+                }
+            }
+            let stats = queue.render(camera.position, time, |program| {
+                program.set_mat4(c"view", &view);
+                program.set_mat4(c"projection", &projection);
+                self.directional_light.set_uniforms(program, "dirLight", &view);
+                spot_light.set_uniforms(program, "spotLight", &view);
+                for i in 0..MAX_POINT_LIGHTS {
+                    let light = self.point_lights.get(i).unwrap_or(&dark_light);
+                    light.set_uniforms(program, &format!("pointLights[{i}]"), &view);
+                    let c_str = CString::new(format!("lightPositions[{i}]")).unwrap();
+                    program.set_vec3(&c_str, &view.transform_point3(light.position).to_array());
+                }
+            });
+
+            if let Some((lamp, cube)) = &self.lamp {
+                lamp.use_program();
+                lamp.set_mat4(c"view", &view);
+                lamp.set_mat4(c"projection", &projection);
+                for light in &self.point_lights {
+                    lamp.set_vec3(c"lightColorSource", &light.diffuse.to_array());
+                    lamp.set_mat4(c"model", &(Mat4::from_translation(light.position) * Mat4::from_scale(Vec3::splat(0.2))));
+                    cube.draw(lamp);
+                }
+            }
+            stats
+        }
+
+        /// Number of objects, in the order of the file.
+        pub fn object_count(&self) -> usize {
+            self.objects.len()
+        }
+
+        /// Number of point lights, for `DebugView::next`.
+        pub fn point_light_count(&self) -> usize {
+            self.point_lights.len()
+        }
+
+        /// Name given to an object in the file, if any.
+        pub fn object_name(&self, index: usize) -> Option<&str> {
+            self.objects.get(index).and_then(|object| object.name.as_deref())
+        }
+    }
+
+    impl Scene for LoadedScene {
+        fn render(&mut self, time: f32, _delta_time: f32, width: i32, height: i32) {
+            self.draw(&self.camera, width, height, time);
+        }
+    }
+
+    impl Drop for LoadedScene {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteTextures(self.textures.len() as i32, self.textures.as_ptr());
+                for program in &self.programs {
+                    gl::DeleteProgram(program.shader_program_id);
+                }
+                if let Some((program, _)) = &self.lamp {
+                    gl::DeleteProgram(program.shader_program_id);
+                }
+            }
+        }
+    }
+
+    fn build_material(description: &MaterialDescription, textures: &BTreeMap<&str, u32>) -> Material {
+        let texture = |name: &Option<String>| name.as_deref().map(|name| textures[name]);
+        Material {
+            diffuse: texture(&description.diffuse),
+            diffuse_color: description.diffuse_color,
+            specular: texture(&description.specular).map(|id| {
+                if description.specular_is_color { SpecularMap::Color(id) } else { SpecularMap::Intensity(id) }
+            }),
+            specular_color: description.specular_color,
+            shininess: description.shininess,
+            emission: description.emission.as_ref().map(|emission| Emission {
+                map: textures[emission.texture.as_str()],
+                intensity: emission.intensity,
+                scroll: emission.scroll,
+                pulse_frequency: emission.pulse_frequency
+            }),
+            opacity: texture(&description.opacity),
+            alpha: description.alpha,
+            alpha_cutoff: description.alpha_cutoff,
+            blend: description.blend
+        }
+    }
+
+    fn load_texture(texture: &TextureDescription) -> Result<u32, Error> {
+        let is_png = texture.path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+        let result = if texture.srgb {
+            Texture::load_srgb_texture(&texture.path, is_png)
+        } else {
+            Texture::load_texture(&texture.path, is_png)
+        };
+        result.map_err(|e| Error::other(format!("ERROR::SCENE_FILE::TEXTURE_LOAD_FAILED {}\n{e}", texture.path.display())))
+    }
+
+    fn names<T>(map: &BTreeMap<String, T>) -> String {
+        if map.is_empty() {
+            return "none".to_string();
+        }
+        map.keys().map(|name| format!("\"{name}\"")).collect::<Vec<_>>().join(", ")
+    }
+
+    fn white() -> Vec3 {
+        Vec3::ONE
+    }
+
+    fn one() -> f32 {
+        1.0
+    }
+
+    fn yes() -> bool {
+        true
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const MINIMAL: &str = r#"
+            [materials.red]
+            diffuse_color = [1.0, 0.0, 0.0]
+
+            [[objects]]
+            name = "box"
+            mesh = "cube"
+            material = "red"
+        "#;
+
+        fn error(source: &str) -> (&'static str, String) {
+            SceneDescription::parse(source).expect_err("the scene should be rejected")
+        }
+
+        #[test]
+        fn parses_a_minimal_scene_with_defaults() {
+            let scene = SceneDescription::parse(MINIMAL).unwrap();
+            assert_eq!(scene.background, Vec3::splat(0.1));
+            assert!(scene.point_lights.is_empty());
+            assert_eq!(scene.objects.len(), 1);
+            let object = &scene.objects[0];
+            assert_eq!(object.mesh, Some(Shape::Cube));
+            assert_eq!(object.material.as_deref(), Some("red"));
+            assert_eq!(scene.materials["red"].diffuse_color, Vec3::X);
+        }
+
+        #[test]
+        fn rejects_an_unknown_material() {
+            let (kind, message) = error(&MINIMAL.replace("material = \"red\"", "material = \"blue\""));
+            assert_eq!(kind, "INVALID");
+            assert_eq!(message, "object \"box\" uses the unknown material \"blue\", declared materials: \"red\"");
+        }
+
+        #[test]
+        fn rejects_a_vector_of_the_wrong_length() {
+            let (kind, message) = error(&format!("{MINIMAL}position = [1.0, 2.0]\n"));
+            assert_eq!(kind, "PARSE");
+            assert!(message.contains("position"), "{message}");
+            assert!(message.contains("invalid length 2"), "{message}");
+        }
+
+        #[test]
+        fn rejects_too_many_point_lights() {
+            let lights = "[[point_lights]]\nposition = [0.0, 0.0, 0.0]\n".repeat(MAX_POINT_LIGHTS + 1);
+            let (kind, message) = error(&format!("{lights}{MINIMAL}"));
+            assert_eq!(kind, "INVALID");
+            assert_eq!(message, format!("{} point lights, the shaders support at most {MAX_POINT_LIGHTS}", MAX_POINT_LIGHTS + 1));
+        }
+    }
+}
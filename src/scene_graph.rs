@@ -0,0 +1,401 @@
+/// Hierarchy of nodes with local transforms, whose world matrices are cached and only recomputed
+/// when they or one of their ancestors moved
+pub mod scene_graph {
+    use glam::{Mat4, Quat, Vec3};
+
+    /// Handle to a node, only meaningful for the graph that created it. A handle kept after its
+    /// node was removed stays invalid, even once the slot holds another node.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct NodeId {
+        index: usize,
+        generation: u32
+    }
+
+    /// Translation, rotation and scale, applied to a point in reverse order: scale first, then
+    /// rotation, then translation.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Transform {
+        pub translation: Vec3,
+        pub rotation: Quat,
+        pub scale: Vec3
+    }
+
+    /// A node and what it carries, usually what to draw with its world matrix.
+    #[derive(Debug)]
+    pub struct Node<T> {
+        pub name: String,
+        pub data: T,
+        local: Transform,
+        world: Mat4,
+        dirty: bool,
+        parent: Option<NodeId>,
+        children: Vec<NodeId>
+    }
+
+    /// Nodes live in a vector and refer to each other by index, removed nodes leave a hole that
+    /// is reused by the next one added. Each slot counts how many nodes it held, so the ids of
+    /// removed nodes don't reach the new ones.
+    #[derive(Debug)]
+    pub struct SceneGraph<T> {
+        nodes: Vec<Slot<T>>,
+        free: Vec<usize>,
+        roots: Vec<NodeId>
+    }
+
+    #[derive(Debug)]
+    struct Slot<T> {
+        generation: u32,
+        node: Option<Node<T>>
+    }
+
+    impl Transform {
+        pub const IDENTITY: Self = Transform {
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE
+        };
+
+        pub fn from_translation(translation: Vec3) -> Self {
+            Transform { translation, ..Self::IDENTITY }
+        }
+
+        /// Rotation of `angle` degrees around `axis`, which doesn't need to be normalized.
+        pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+            Transform { rotation: Quat::from_axis_angle(axis.normalize(), angle.to_radians()), ..Self::IDENTITY }
+        }
+
+        /// Splits a matrix made of a translation, a rotation and a scale, such as the node
+        /// transforms of model files. Shear, if any, is lost.
+        pub fn from_matrix(matrix: &Mat4) -> Self {
+            let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+            Transform { translation, rotation, scale }
+        }
+
+        pub fn with_translation(self, translation: Vec3) -> Self {
+            Transform { translation, ..self }
+        }
+
+        pub fn with_rotation(self, rotation: Quat) -> Self {
+            Transform { rotation, ..self }
+        }
+
+        pub fn with_scale(self, scale: Vec3) -> Self {
+            Transform { scale, ..self }
+        }
+
+        pub fn matrix(&self) -> Mat4 {
+            Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+        }
+    }
+
+    impl Default for Transform {
+        fn default() -> Self {
+            Self::IDENTITY
+        }
+    }
+
+    impl<T> Node<T> {
+        pub fn local(&self) -> &Transform {
+            &self.local
+        }
+
+        /// World matrix as of the last `SceneGraph::update`.
+        pub fn world(&self) -> &Mat4 {
+            &self.world
+        }
+
+        pub fn parent(&self) -> Option<NodeId> {
+            self.parent
+        }
+
+        pub fn children(&self) -> &[NodeId] {
+            &self.children
+        }
+    }
+
+    impl<T> SceneGraph<T> {
+        pub fn new() -> Self {
+            SceneGraph {
+                nodes: Vec::new(),
+                free: Vec::new(),
+                roots: Vec::new()
+            }
+        }
+
+        /// Adds a node under `parent`, or as a new root.
+        pub fn add(&mut self, parent: Option<NodeId>, name: &str, local: Transform, data: T) -> NodeId {
+            let node = Node {
+                name: name.to_string(),
+                data,
+                local,
+                world: Mat4::IDENTITY,
+                dirty: true,
+                parent,
+                children: Vec::new()
+            };
+            let id = match self.free.pop() {
+                Some(index) => {
+                    let slot = &mut self.nodes[index];
+                    slot.node = Some(node);
+                    NodeId { index, generation: slot.generation }
+                }
+                None => {
+                    self.nodes.push(Slot { generation: 0, node: Some(node) });
+                    NodeId { index: self.nodes.len() - 1, generation: 0 }
+                }
+            };
+            match parent {
+                Some(parent) => self.node_mut(parent).children.push(id),
+                None => self.roots.push(id)
+            }
+            id
+        }
+
+        /// Removes a node and everything below it, returning what the node carried.
+        ///
+        /// Panics if the node was already removed.
+        pub fn remove(&mut self, id: NodeId) -> T {
+            self.detach(id);
+            let node = self.take(id).expect("node was already removed");
+            for child in &node.children {
+                self.remove_subtree(*child);
+            }
+            node.data
+        }
+
+        /// Moves a node, and its children with it, under another parent or to the roots. Its local
+        /// transform is kept, so it moves along with its new parent from now on.
+        ///
+        /// Panics if `parent` is the node itself or one of its descendants.
+        pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) {
+            let mut ancestor = parent;
+            while let Some(current) = ancestor {
+                assert!(current != id, "a node can't become its own descendant");
+                ancestor = self.node(current).parent;
+            }
+            self.detach(id);
+            match parent {
+                Some(parent) => self.node_mut(parent).children.push(id),
+                None => self.roots.push(id)
+            }
+            let node = self.node_mut(id);
+            node.parent = parent;
+            node.dirty = true;
+        }
+
+        /// The node, or None if it was removed.
+        pub fn get(&self, id: NodeId) -> Option<&Node<T>> {
+            self.nodes.get(id.index)
+                .filter(|slot| slot.generation == id.generation)
+                .and_then(|slot| slot.node.as_ref())
+        }
+
+        /// Panics if the node was removed.
+        pub fn node(&self, id: NodeId) -> &Node<T> {
+            self.get(id).expect("node was removed")
+        }
+
+        /// Mutable access to what the node carries. Transforms go through `set_local` so the node
+        /// knows it moved.
+        pub fn data_mut(&mut self, id: NodeId) -> &mut T {
+            &mut self.node_mut(id).data
+        }
+
+        pub fn set_local(&mut self, id: NodeId, local: Transform) {
+            let node = self.node_mut(id);
+            node.local = local;
+            node.dirty = true;
+        }
+
+        /// Changes the local transform in place, e.g. `graph.update_local(arm, |t| t.rotation *= spin)`.
+        pub fn update_local(&mut self, id: NodeId, change: impl FnOnce(&mut Transform)) {
+            let node = self.node_mut(id);
+            change(&mut node.local);
+            node.dirty = true;
+        }
+
+        /// World matrix as of the last `update`.
+        pub fn world(&self, id: NodeId) -> Mat4 {
+            self.node(id).world
+        }
+
+        pub fn roots(&self) -> &[NodeId] {
+            &self.roots
+        }
+
+        /// Number of nodes in the graph.
+        pub fn len(&self) -> usize {
+            self.nodes.len() - self.free.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Finds the first node with the given name, depth first from the roots.
+        pub fn find(&self, name: &str) -> Option<NodeId> {
+            let mut found = None;
+            self.traverse(|id, node| {
+                if found.is_none() && node.name == name {
+                    found = Some(id);
+                }
+            });
+            found
+        }
+
+        /// Recomputes the world matrices of the nodes that moved and of everything below them.
+        /// Returns how many were recomputed.
+        pub fn update(&mut self) -> usize {
+            let mut updated = 0;
+            let mut stack: Vec<(NodeId, Mat4, bool)> = self.roots.iter().rev()
+                .map(|root| (*root, Mat4::IDENTITY, false))
+                .collect();
+            while let Some((id, parent_world, parent_moved)) = stack.pop() {
+                let node = self.node_mut(id);
+                let moved = parent_moved || node.dirty;
+                if moved {
+                    node.world = parent_world * node.local.matrix();
+                    node.dirty = false;
+                    updated += 1;
+                }
+                let world = node.world;
+                stack.extend(node.children.iter().rev().map(|child| (*child, world, moved)));
+            }
+            updated
+        }
+
+        /// Visits every node depth first, parents before their children, in the order they were
+        /// added. World matrices are the ones of the last `update`.
+        pub fn traverse<'a>(&'a self, mut visit: impl FnMut(NodeId, &'a Node<T>)) {
+            let mut stack: Vec<NodeId> = self.roots.iter().rev().copied().collect();
+            while let Some(id) = stack.pop() {
+                let node = self.node(id);
+                visit(id, node);
+                stack.extend(node.children.iter().rev());
+            }
+        }
+
+        fn node_mut(&mut self, id: NodeId) -> &mut Node<T> {
+            self.nodes.get_mut(id.index)
+                .filter(|slot| slot.generation == id.generation)
+                .and_then(|slot| slot.node.as_mut())
+                .expect("node was removed")
+        }
+
+        /// Empties the slot of a node, so its id no longer matches, and frees it for the next one.
+        fn take(&mut self, id: NodeId) -> Option<Node<T>> {
+            let slot = self.nodes.get_mut(id.index).filter(|slot| slot.generation == id.generation)?;
+            let node = slot.node.take()?;
+            slot.generation = slot.generation.wrapping_add(1);
+            self.free.push(id.index);
+            Some(node)
+        }
+
+        /// Takes a node out of its parent's children, or out of the roots.
+        fn detach(&mut self, id: NodeId) {
+            let siblings = match self.node(id).parent {
+                Some(parent) => &mut self.node_mut(parent).children,
+                None => &mut self.roots
+            };
+            siblings.retain(|sibling| *sibling != id);
+        }
+
+        fn remove_subtree(&mut self, id: NodeId) {
+            if let Some(node) = self.take(id) {
+                for child in node.children {
+                    self.remove_subtree(child);
+                }
+            }
+        }
+    }
+
+    impl<T> Default for SceneGraph<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// `arm` and `hand` under `base`, and a separate `lamp` root.
+        fn graph() -> (SceneGraph<u32>, [NodeId; 4]) {
+            let mut graph = SceneGraph::new();
+            let base = graph.add(None, "base", Transform::from_translation(Vec3::X), 0);
+            let arm = graph.add(Some(base), "arm", Transform::from_translation(Vec3::Y), 1);
+            let hand = graph.add(Some(arm), "hand", Transform::IDENTITY.with_scale(Vec3::splat(2.0)), 2);
+            let lamp = graph.add(None, "lamp", Transform::from_translation(Vec3::Z), 3);
+            (graph, [base, arm, hand, lamp])
+        }
+
+        #[test]
+        fn only_moved_subtrees_are_recomputed() {
+            let (mut graph, [base, arm, hand, lamp]) = graph();
+            assert_eq!(graph.update(), 4);
+            assert_eq!(graph.update(), 0);
+            assert_eq!(graph.world(hand).transform_point3(Vec3::X), Vec3::new(3.0, 1.0, 0.0));
+
+            graph.set_local(arm, Transform::from_translation(Vec3::NEG_Y));
+            assert_eq!(graph.update(), 2);
+            assert_eq!(graph.world(hand).transform_point3(Vec3::X), Vec3::new(3.0, -1.0, 0.0));
+            assert_eq!(graph.world(base), Mat4::from_translation(Vec3::X));
+
+            graph.update_local(lamp, |local| local.scale = Vec3::splat(0.5));
+            graph.update_local(base, |local| local.translation = Vec3::ZERO);
+            assert_eq!(graph.update(), 4);
+        }
+
+        #[test]
+        fn removing_a_node_removes_its_subtree_and_its_id() {
+            let (mut graph, [_, arm, hand, lamp]) = graph();
+            assert_eq!(graph.remove(arm), 1);
+            assert_eq!(graph.len(), 2);
+            assert!(graph.get(arm).is_none());
+            assert!(graph.get(hand).is_none());
+            assert!(graph.find("hand").is_none());
+
+            // the freed slots are reused, but the old ids don't reach the new nodes
+            let bulb = graph.add(Some(lamp), "bulb", Transform::IDENTITY, 4);
+            let switch = graph.add(Some(lamp), "switch", Transform::IDENTITY, 5);
+            assert_eq!(graph.len(), 4);
+            assert!(graph.get(arm).is_none());
+            assert!(graph.get(hand).is_none());
+            assert_eq!(graph.node(bulb).data, 4);
+            assert_eq!(graph.node(switch).data, 5);
+            assert_eq!(graph.node(lamp).children(), [bulb, switch]);
+        }
+
+        #[test]
+        fn reparented_nodes_follow_their_new_parent() {
+            let (mut graph, [base, arm, hand, lamp]) = graph();
+            graph.set_parent(arm, Some(lamp));
+            assert_eq!(graph.node(base).children(), []);
+            assert_eq!(graph.node(arm).parent(), Some(lamp));
+            graph.update();
+            assert_eq!(graph.world(hand).transform_point3(Vec3::ZERO), Vec3::new(0.0, 1.0, 1.0));
+
+            graph.set_parent(lamp, None);
+            graph.set_parent(arm, None);
+            assert_eq!(graph.roots(), [base, lamp, arm]);
+        }
+
+        #[test]
+        #[should_panic(expected = "a node can't become its own descendant")]
+        fn a_node_cannot_move_under_its_descendant() {
+            let (mut graph, [base, _, hand, _]) = graph();
+            graph.set_parent(base, Some(hand));
+        }
+
+        #[test]
+        fn find_searches_depth_first_from_the_roots() {
+            let (mut graph, [base, arm, _, lamp]) = graph();
+            let other_arm = graph.add(Some(lamp), "arm", Transform::IDENTITY, 4);
+            assert_eq!(graph.find("arm"), Some(arm));
+            assert_eq!(graph.find("lamp"), Some(lamp));
+            assert_eq!(graph.find("missing"), None);
+            graph.remove(base);
+            assert_eq!(graph.find("arm"), Some(other_arm));
+        }
+    }
+}
@@ -0,0 +1,531 @@
+/// Example scenes with a fixed camera, drawn from nothing but the time they are given so they can
+/// be rendered headless and always produce the same image
+pub mod scenes {
+    use std::ffi::CString;
+    use std::io::Error;
+    use std::path::Path;
+    use std::ptr;
+    use glam::{Mat4, Vec3};
+    use crate::camera::camera::Camera;
+    use crate::headless::headless::Scene;
+    use crate::lights::lights::{DirectionalLight, PointLight, SpotLight};
+    use crate::mesh::mesh::Mesh;
+    use crate::shaders::shaders::ShaderProgram;
+    use crate::shapes::shapes;
+    use crate::textures::textures::Texture;
+
+    /// Names accepted by `by_name`, in the order the chapters introduce them.
+    pub const NAMES: [&str; 6] = [
+        "hello_triangle",
+        "textures",
+        "coordinate_systems",
+        "lighting",
+        "light_casters",
+        "multiple_lights"
+    ];
+
+    /// Creates the scene with the given name, `None` if there is no such scene.
+    pub fn by_name(name: &str) -> Option<Result<Box<dyn Scene>, Error>> {
+        fn boxed<S: Scene + 'static>(scene: Result<S, Error>) -> Option<Result<Box<dyn Scene>, Error>> {
+            Some(scene.map(|scene| Box::new(scene) as Box<dyn Scene>))
+        }
+        match name {
+            "hello_triangle" => boxed(HelloTriangle::new()),
+            "textures" => boxed(Textures::new()),
+            "coordinate_systems" => boxed(CoordinateSystems::new()),
+            "lighting" => boxed(Lighting::new()),
+            "light_casters" => boxed(LightCasters::new()),
+            "multiple_lights" => boxed(MultipleLights::new()),
+            _ => None
+        }
+    }
+
+    /// Positions of the boxes scattered in front of the camera from the coordinate systems chapter
+    /// onwards.
+    const CUBE_POSITIONS: [Vec3; 10] = [
+        Vec3::new( 0.0,  0.0,  0.0),
+        Vec3::new( 2.0,  5.0, -15.0),
+        Vec3::new(-1.5, -2.2, -2.5),
+        Vec3::new(-3.8, -2.0, -12.3),
+        Vec3::new( 2.4, -0.4, -3.5),
+        Vec3::new(-1.7,  3.0, -7.5),
+        Vec3::new( 1.3, -2.0, -2.5),
+        Vec3::new( 1.5,  2.0, -2.5),
+        Vec3::new( 1.5,  0.2, -1.5),
+        Vec3::new(-1.3,  1.0, -1.5)
+    ];
+
+    /// Axis the boxes are rotated around.
+    const CUBE_AXIS: Vec3 = Vec3::new(1.0, 0.3, 0.5);
+
+    /// The orange triangle of `hello_triangle_1`, in normalized device coordinates.
+    pub struct HelloTriangle {
+        program: ShaderProgram,
+        triangle: VertexArray
+    }
+
+    /// The container mixed with a second picture on a quad, as in `textures`.
+    pub struct Textures {
+        program: ShaderProgram,
+        quad: VertexArray,
+        container: u32,
+        picture: u32
+    }
+
+    /// The textured boxes of `coordinate_systems`, every third one spinning with time.
+    pub struct CoordinateSystems {
+        program: ShaderProgram,
+        cube: VertexArray,
+        container: u32,
+        face: u32
+    }
+
+    /// A white cube lit by a lamp changing colour over time, as in `light`.
+    pub struct Lighting {
+        program: ShaderProgram,
+        lamp: ShaderProgram,
+        cube: Mesh,
+        light_position: Vec3
+    }
+
+    /// The boxes of `light_casters` lit only by the spotlight held by the camera.
+    pub struct LightCasters {
+        program: ShaderProgram,
+        cube: Mesh,
+        diffuse: u32,
+        specular: u32,
+        camera: Camera
+    }
+
+    /// The boxes of `multiple_lights` lit by a directional light, four point lights and the
+    /// flashlight, seen from the starting position of the camera.
+    pub struct MultipleLights {
+        program: ShaderProgram,
+        lamp: ShaderProgram,
+        cube: Mesh,
+        diffuse: u32,
+        specular: u32,
+        camera: Camera,
+        directional_light: DirectionalLight,
+        point_lights: [PointLight; 4]
+    }
+
+    /// Interleaved float vertices with an index buffer, for the early chapters whose shaders don't
+    /// take the `Mesh` vertex layout.
+    struct VertexArray {
+        vao: u32,
+        buffers: [u32; 2],
+        count: i32
+    }
+
+    impl HelloTriangle {
+        pub fn new() -> Result<Self, Error> {
+            let program = ShaderProgram::from_files("src/shaders/vertex/simple_vertex.vert", "src/shaders/fragment/simple_fragment.frag")?;
+            let vertices: [f32; 18] = [
+                // positions      // colors
+                -0.5, -0.5, 0.0,  1.0, 0.5, 0.2,
+                 0.5, -0.5, 0.0,  1.0, 0.5, 0.2,
+                 0.0,  0.5, 0.0,  1.0, 0.5, 0.2
+            ];
+            Ok(HelloTriangle {
+                program,
+                triangle: VertexArray::new(&vertices, &[0, 1, 2], &[3, 3])
+            })
+        }
+    }
+
+    impl Scene for HelloTriangle {
+        fn render(&mut self, _time: f32, _delta_time: f32, _width: i32, _height: i32) {
+            unsafe {
+                gl::ClearColor(0.2, 0.3, 0.3, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+            }
+            self.program.use_program();
+            self.triangle.draw();
+        }
+    }
+
+    impl Textures {
+        pub fn new() -> Result<Self, Error> {
+            let program = ShaderProgram::from_files("src/shaders/vertex/texture.vert", "src/shaders/fragment/texture.frag")?;
+            program.use_program();
+            program.set_int(c"texture1", 0);
+            program.set_int(c"texture2", 1);
+            program.set_float(c"visibility", 0.2);
+            let vertices: [f32; 32] = [
+                // positions      // colors        // texture coords
+                 0.5,  0.5, 0.0,  1.0, 0.0, 0.0,  1.0, 1.0,   // top right
+                 0.5, -0.5, 0.0,  0.0, 1.0, 0.0,  1.0, 0.0,   // bottom right
+                -0.5, -0.5, 0.0,  0.0, 0.0, 1.0,  0.0, 0.0,   // bottom left
+                -0.5,  0.5, 0.0,  1.0, 1.0, 0.0,  0.0, 1.0    // top left
+            ];
+            Ok(Textures {
+                program,
+                quad: VertexArray::new(&vertices, &[0, 1, 3, 1, 2, 3], &[3, 3, 2]),
+                container: load_texture("src/textures/container.jpg", false)?,
+                picture: load_texture("src/textures/calamardo.jpg", false)?
+            })
+        }
+    }
+
+    impl Scene for Textures {
+        fn render(&mut self, _time: f32, _delta_time: f32, _width: i32, _height: i32) {
+            unsafe {
+                gl::ClearColor(0.2, 0.3, 0.3, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.container);
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, self.picture);
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+            self.program.use_program();
+            self.quad.draw();
+        }
+    }
+
+    impl Drop for Textures {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteTextures(2, [self.container, self.picture].as_ptr());
+            }
+        }
+    }
+
+    impl CoordinateSystems {
+        pub fn new() -> Result<Self, Error> {
+            let program = ShaderProgram::from_files("src/shaders/vertex/coordinate_systems.vert", "src/shaders/fragment/coordinate_system.frag")?;
+            program.use_program();
+            program.set_int(c"texture1", 0);
+            program.set_int(c"texture2", 1);
+            Ok(CoordinateSystems {
+                program,
+                cube: textured_cube(),
+                container: load_texture("src/textures/container.jpg", false)?,
+                face: load_texture("src/textures/awesomeface.png", true)?
+            })
+        }
+    }
+
+    impl Scene for CoordinateSystems {
+        fn render(&mut self, time: f32, _delta_time: f32, width: i32, height: i32) {
+            let view = Mat4::from_translation(Vec3::new(0.0, 0.0, -3.0));
+            let projection = Mat4::perspective_rh_gl(45.0f32.to_radians(), width as f32 / height as f32, 0.1, 100.0);
+            unsafe {
+                gl::Enable(gl::DEPTH_TEST);
+                gl::ClearColor(0.2, 0.3, 0.3, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.container);
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, self.face);
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+
+            self.program.use_program();
+            self.program.set_mat4(c"view", &view);
+            self.program.set_mat4(c"projection", &projection);
+            for (i, position) in CUBE_POSITIONS.iter().enumerate() {
+                let mut angle = 20.0f32.to_radians() * i as f32;
+                if i % 3 == 0 {
+                    angle = 20.0f32.to_radians() * (0.5 + i as f32) * time;
+                }
+                let model = Mat4::from_translation(*position) * Mat4::from_axis_angle(CUBE_AXIS.normalize(), angle);
+                self.program.set_mat4(c"model", &model);
+                self.cube.draw();
+            }
+        }
+    }
+
+    impl Drop for CoordinateSystems {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteTextures(2, [self.container, self.face].as_ptr());
+            }
+        }
+    }
+
+    impl Lighting {
+        pub fn new() -> Result<Self, Error> {
+            let program = ShaderProgram::from_files("src/shaders/vertex/cube_vertex.vert", "src/shaders/fragment/ambient_lighting.frag")?;
+            let lamp = ShaderProgram::from_files("src/shaders/vertex/light_source.vert", "src/shaders/fragment/light_source.frag")?;
+            Ok(Lighting {
+                program,
+                lamp,
+                cube: shapes::cube(),
+                light_position: Vec3::new(1.2, 1.0, 2.0)
+            })
+        }
+    }
+
+    impl Scene for Lighting {
+        fn render(&mut self, time: f32, _delta_time: f32, width: i32, height: i32) {
+            let camera = Camera::new(Vec3::new(0.0, 1.0, 3.0), Vec3::Y, true);
+            let view = camera.get_view_matrix();
+            let projection = perspective(&camera, width, height);
+            let light_color = Vec3::new((2.0 * time).sin(), (0.7 * time).sin(), (1.3 * time).sin());
+            let diffuse = light_color * 0.5;
+            unsafe {
+                gl::Enable(gl::DEPTH_TEST);
+                gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            }
+
+            let program = &self.program;
+            program.use_program();
+            program.set_vec3(c"lightPos", &self.light_position.to_array());
+            program.set_vec3(c"material.ambient", &[1.0, 1.0, 1.0]);
+            program.set_vec3(c"material.diffuse", &[1.0, 1.0, 1.0]);
+            program.set_vec3(c"material.specular", &[1.0, 1.0, 1.0]);
+            program.set_vec3(c"light.ambient", &(diffuse * 0.2).to_array());
+            program.set_vec3(c"light.diffuse", &diffuse.to_array());
+            program.set_vec3(c"light.specular", &[1.0, 1.0, 1.0]);
+            program.set_mat4(c"model", &Mat4::IDENTITY);
+            program.set_mat4(c"view", &view);
+            program.set_mat4(c"projection", &projection);
+            self.cube.draw(program);
+
+            self.lamp.use_program();
+            self.lamp.set_vec3(c"lightColorSource", &light_color.to_array());
+            self.lamp.set_mat4(c"model", &(Mat4::from_translation(self.light_position) * Mat4::from_scale(Vec3::splat(0.2))));
+            self.lamp.set_mat4(c"view", &view);
+            self.lamp.set_mat4(c"projection", &projection);
+            self.cube.draw(&self.lamp);
+        }
+    }
+
+    impl LightCasters {
+        pub fn new() -> Result<Self, Error> {
+            let program = ShaderProgram::from_files("src/shaders/vertex/specular_diffuse.vert", "src/shaders/fragment/spotlight.frag")?;
+            program.use_program();
+            program.set_int(c"material.diffuse", 0);
+            program.set_int(c"material.specular", 1);
+            Ok(LightCasters {
+                program,
+                cube: shapes::cube(),
+                diffuse: load_texture("src/textures/wooden-container-with-metal-frame.png", true)?,
+                specular: load_texture("src/textures/steel-frame.png", true)?,
+                camera: Camera::new(Vec3::new(0.0, 1.0, 3.0), Vec3::Y, true)
+            })
+        }
+    }
+
+    impl Scene for LightCasters {
+        fn render(&mut self, _time: f32, _delta_time: f32, width: i32, height: i32) {
+            let view = self.camera.get_view_matrix();
+            let projection = perspective(&self.camera, width, height);
+            unsafe {
+                gl::Enable(gl::DEPTH_TEST);
+                gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.diffuse);
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, self.specular);
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+
+            let program = &self.program;
+            program.use_program();
+            program.set_float(c"material.shininess", 32.0);
+            program.set_bool(c"blinn", false);
+            program.set_bool(c"gammaCorrection", false);
+            // the spotlight shader puts the light at the eye, looking down the view direction
+            program.set_float(c"light.cutOff", 12.5f32.to_radians().cos());
+            program.set_float(c"light.outerCutOff", 17.5f32.to_radians().cos());
+            program.set_vec3(c"light.ambient", &[0.2, 0.2, 0.2]);
+            program.set_vec3(c"light.diffuse", &[0.5, 0.5, 0.5]);
+            program.set_vec3(c"light.specular", &[1.0, 1.0, 1.0]);
+            program.set_float(c"light.constant", 1.0);
+            program.set_float(c"light.linear", 0.09);
+            program.set_float(c"light.quadratic", 0.032);
+            program.set_mat4(c"view", &view);
+            program.set_mat4(c"projection", &projection);
+            for (i, position) in CUBE_POSITIONS.iter().enumerate() {
+                let angle = 20.0 * i as f32;
+                let model = Mat4::from_translation(*position) * Mat4::from_axis_angle(CUBE_AXIS.normalize(), angle);
+                program.set_mat4(c"model", &model);
+                self.cube.draw(program);
+            }
+        }
+    }
+
+    impl Drop for LightCasters {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteTextures(2, [self.diffuse, self.specular].as_ptr());
+            }
+        }
+    }
+
+    impl MultipleLights {
+        pub fn new() -> Result<Self, Error> {
+            let program = ShaderProgram::from_files("src/shaders/vertex/lights.vert", "src/shaders/fragment/lights.frag")?;
+            program.use_program();
+            program.set_int(c"material.diffuse", 0);
+            program.set_int(c"material.specular", 1);
+            let lamp = ShaderProgram::from_files("src/shaders/vertex/light_source.vert", "src/shaders/fragment/light_source.frag")?;
+
+            Ok(MultipleLights {
+                program,
+                lamp,
+                cube: shapes::cube(),
+                diffuse: load_texture("src/textures/wooden-container-with-metal-frame.png", true)?,
+                specular: load_texture("src/textures/steel-frame.png", true)?,
+                camera: Camera::new(Vec3::new(0.0, 1.0, 3.0), Vec3::Y, true),
+                directional_light: DirectionalLight {
+                    direction: Vec3::new(-0.2, -1.0, -0.3),
+                    ambient: Vec3::splat(0.2),
+                    diffuse: Vec3::splat(0.5),
+                    specular: Vec3::ONE
+                },
+                point_lights: [
+                    PointLight::new(Vec3::new(0.7, 0.2, 1.0), Vec3::splat(0.1), Vec3::new(1.0, 0.65, 0.0), Vec3::ONE),
+                    PointLight::new(Vec3::new(2.3, -1.3, -4.0), Vec3::splat(0.1), Vec3::new(0.0, 1.0, 0.0), Vec3::ONE),
+                    PointLight::new(Vec3::new(-4.0, 2.0, -9.0), Vec3::splat(0.1), Vec3::new(1.0, 0.0, 0.0), Vec3::ONE),
+                    PointLight::new(Vec3::new(0.0, 0.0, -3.0), Vec3::splat(0.1), Vec3::new(0.0, 0.0, 1.0), Vec3::ONE)
+                ]
+            })
+        }
+    }
+
+    impl Scene for MultipleLights {
+        fn render(&mut self, _time: f32, _delta_time: f32, width: i32, height: i32) {
+            let view = self.camera.get_view_matrix();
+            let projection = perspective(&self.camera, width, height);
+            let spot_light = SpotLight::flashlight(self.camera.position, self.camera.front(), Vec3::ONE);
+            unsafe {
+                gl::Enable(gl::DEPTH_TEST);
+                gl::ClearColor(0.1, 0.1, 0.1, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.diffuse);
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, self.specular);
+            }
+
+            let program = &self.program;
+            program.use_program();
+            program.set_float(c"material.shininess", 32.0);
+            program.set_bool(c"blinn", false);
+            program.set_bool(c"gammaCorrection", false);
+            program.set_mat4(c"view", &view);
+            program.set_mat4(c"projection", &projection);
+            self.directional_light.set_uniforms(program, "dirLight", &view);
+            spot_light.set_uniforms(program, "spotLight", &view);
+            for (i, point_light) in self.point_lights.iter().enumerate() {
+                point_light.set_uniforms(program, &format!("pointLights[{i}]"), &view);
+                let c_str = CString::new(format!("lightPositions[{i}]")).unwrap();
+                program.set_vec3(&c_str, &view.transform_point3(point_light.position).to_array());
+            }
+            for (i, position) in CUBE_POSITIONS.iter().enumerate() {
+                let angle = 20.0 * i as f32;
+                let model = Mat4::from_translation(*position) * Mat4::from_axis_angle(CUBE_AXIS.normalize(), angle);
+                program.set_mat4(c"model", &model);
+                self.cube.draw(program);
+            }
+
+            self.lamp.use_program();
+            self.lamp.set_mat4(c"view", &view);
+            self.lamp.set_mat4(c"projection", &projection);
+            for point_light in &self.point_lights {
+                self.lamp.set_vec3(c"lightColorSource", &point_light.diffuse.to_array());
+                let model = Mat4::from_translation(point_light.position) * Mat4::from_scale(Vec3::splat(0.2));
+                self.lamp.set_mat4(c"model", &model);
+                self.cube.draw(&self.lamp);
+            }
+        }
+    }
+
+    impl Drop for MultipleLights {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteTextures(2, [self.diffuse, self.specular].as_ptr());
+            }
+        }
+    }
+
+    impl VertexArray {
+        /// Uploads `vertices`, made of consecutive attributes of `attributes[i]` floats bound to
+        /// location `i`.
+        fn new(vertices: &[f32], indices: &[u32], attributes: &[i32]) -> Self {
+            let (mut vao, mut buffers) = (0, [0; 2]);
+            let stride = attributes.iter().sum::<i32>() * size_of::<f32>() as i32;
+            unsafe {
+                gl::GenVertexArrays(1, &mut vao);
+                gl::GenBuffers(2, buffers.as_mut_ptr());
+                gl::BindVertexArray(vao);
+
+                gl::BindBuffer(gl::ARRAY_BUFFER, buffers[0]);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    size_of_val(vertices) as isize,
+                    vertices.as_ptr().cast(),
+                    gl::STATIC_DRAW
+                );
+                gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, buffers[1]);
+                gl::BufferData(
+                    gl::ELEMENT_ARRAY_BUFFER,
+                    size_of_val(indices) as isize,
+                    indices.as_ptr().cast(),
+                    gl::STATIC_DRAW
+                );
+
+                let mut offset = 0;
+                for (location, size) in attributes.iter().enumerate() {
+                    gl::VertexAttribPointer(
+                        location as u32,
+                        *size,
+                        gl::FLOAT,
+                        gl::FALSE,
+                        stride,
+                        (offset * size_of::<f32>()) as *const _
+                    );
+                    gl::EnableVertexAttribArray(location as u32);
+                    offset += *size as usize;
+                }
+                gl::BindVertexArray(0);
+            }
+            VertexArray { vao, buffers, count: indices.len() as i32 }
+        }
+
+        fn draw(&self) {
+            unsafe {
+                gl::BindVertexArray(self.vao);
+                gl::DrawElements(gl::TRIANGLES, self.count, gl::UNSIGNED_INT, ptr::null());
+                gl::BindVertexArray(0);
+            }
+        }
+    }
+
+    impl Drop for VertexArray {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteBuffers(2, self.buffers.as_ptr());
+                gl::DeleteVertexArrays(1, &self.vao);
+            }
+        }
+    }
+
+    /// Unit cube with positions and texture coordinates only, the layout of
+    /// `coordinate_systems.vert`.
+    fn textured_cube() -> VertexArray {
+        let (cube_vertices, indices) = shapes::cube_geometry();
+        let vertices: Vec<f32> = cube_vertices.iter()
+            .flat_map(|vertex| {
+                let (position, tex_coord) = (vertex.position(), vertex.tex_coord());
+                [position.x, position.y, position.z, tex_coord.x, tex_coord.y]
+            })
+            .collect();
+        VertexArray::new(&vertices, &indices, &[3, 2])
+    }
+
+    fn load_texture(path: &str, is_png: bool) -> Result<u32, Error> {
+        Texture::load_texture(Path::new(path), is_png)
+            .map_err(|e| Error::other(format!("ERROR::SCENES::TEXTURE_LOAD_FAILED {path}: {e}")))
+    }
+
+    fn perspective(camera: &Camera, width: i32, height: i32) -> Mat4 {
+        Mat4::perspective_rh_gl(camera.zoom.to_radians(), width as f32 / height as f32, 0.1, 100.0)
+    }
+}
@@ -6,10 +6,12 @@ pub mod shaders {
     use std::path::{Path};
     use std::{ptr};
     use gl::types::GLint;
+    use glam::Mat4;
 
     #[derive(Debug)]
     pub enum ShaderType {
         Vertex,
+        Geometry,
         Fragment
     }
 
@@ -52,6 +54,9 @@ pub mod shaders {
                 ShaderType::Vertex => {
                     shader.shader_program_id = shader.create_shader(&shader_type);
                 },
+                ShaderType::Geometry => {
+                    shader.shader_program_id = shader.create_shader(&shader_type);
+                },
                 ShaderType::Fragment => {
                     shader.shader_program_id = shader.create_shader(&shader_type);
                 }
@@ -79,6 +84,9 @@ pub mod shaders {
                 ShaderType::Vertex => {
                     unsafe { gl::CreateShader(gl::VERTEX_SHADER) }
                 },
+                ShaderType::Geometry => {
+                    unsafe { gl::CreateShader(gl::GEOMETRY_SHADER) }
+                },
                 ShaderType::Fragment => {
                     unsafe { gl::CreateShader(gl::FRAGMENT_SHADER) }
                 }
@@ -157,6 +165,26 @@ pub mod shaders {
             Ok(())
         }
 
+        /// Loads, compiles and links a vertex and a fragment shader in one step. Handy for programs
+        /// that live inside the library, where there is no need to keep the shader ids around.
+        pub fn from_files<P: AsRef<Path>>(vertex_path: P, fragment_path: P) -> Result<Self, Error> {
+            let vertex_shader = Shader::load_shader(ShaderType::Vertex, vertex_path)?;
+            let fragment_shader = Shader::load_shader(ShaderType::Fragment, fragment_path)?;
+            let program = ShaderProgram::new();
+            program.build(&[vertex_shader, fragment_shader])?;
+            Ok(program)
+        }
+
+        /// Same as `from_files` with a geometry shader between the vertex and fragment ones.
+        pub fn from_files_with_geometry<P: AsRef<Path>>(vertex_path: P, geometry_path: P, fragment_path: P) -> Result<Self, Error> {
+            let vertex_shader = Shader::load_shader(ShaderType::Vertex, vertex_path)?;
+            let geometry_shader = Shader::load_shader(ShaderType::Geometry, geometry_path)?;
+            let fragment_shader = Shader::load_shader(ShaderType::Fragment, fragment_path)?;
+            let program = ShaderProgram::new();
+            program.build(&[vertex_shader, geometry_shader, fragment_shader])?;
+            Ok(program)
+        }
+
         /// Uses the shader assuming no error was raised during build
         pub fn use_program(&self) {
             unsafe {
@@ -180,6 +208,14 @@ pub mod shaders {
             }
         }
 
+        pub fn set_uint(&self, name: &CStr, value: u32) {
+            unsafe {
+                gl::Uniform1ui(
+                    gl::GetUniformLocation(self.shader_program_id, name.as_ptr()),
+                    value)
+            }
+        }
+
         pub fn get_int(&self, name: &CStr) -> i32 {
             unsafe {
                 let location: i32 = gl::GetUniformLocation(self.shader_program_id, name.as_ptr());
@@ -222,6 +258,33 @@ pub mod shaders {
             }
         }
 
+        pub fn set_vec2(&self, name: &CStr, values: &[f32]) {
+            unsafe {
+                gl::Uniform2f(
+                    gl::GetUniformLocation(self.shader_program_id, name.as_ptr()),
+                    values[0], values[1])
+            }
+        }
+
+        pub fn set_float_array(&self, name: &CStr, values: &[f32]) {
+            unsafe {
+                gl::Uniform1fv(
+                    gl::GetUniformLocation(self.shader_program_id, name.as_ptr()),
+                    values.len() as i32,
+                    values.as_ptr())
+            }
+        }
+
+        pub fn set_mat4(&self, name: &CStr, value: &Mat4) {
+            unsafe {
+                gl::UniformMatrix4fv(
+                    gl::GetUniformLocation(self.shader_program_id, name.as_ptr()),
+                    1,
+                    gl::FALSE,
+                    &value.to_cols_array()[0])
+            }
+        }
+
         fn delete_shader(&self, shader: u32) {
             unsafe { gl::DeleteShader(shader); }
         }
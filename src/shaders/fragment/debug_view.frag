@@ -0,0 +1,29 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec3 FragPos;
+in vec3 Normal;
+in vec2 TexCoords;
+
+// 1: normals, 2: UV checkerboard, 3: depth, see debug_view.rs
+uniform int debugView;
+uniform float checkerCells;
+// view space distance shown as black, nearer is brighter
+uniform float depthRange;
+
+void main()
+{
+    if (debugView == 1) {
+        // view space normals, so facing the camera is blue whatever the direction
+        FragColor = vec4(normalize(Normal) * 0.5 + 0.5, 1.0);
+    } else if (debugView == 2) {
+        vec2 cell = floor(TexCoords * checkerCells);
+        float checker = mod(cell.x + cell.y, 2.0);
+        // tinted by the coordinates so flipped or rotated mappings show too
+        FragColor = vec4(mix(vec3(0.2), vec3(0.9), checker) * 0.6 + vec3(fract(TexCoords), 0.0) * 0.4, 1.0);
+    } else {
+        // the view space distance is linear, unlike gl_FragCoord.z
+        float depth = clamp(-FragPos.z / depthRange, 0.0, 1.0);
+        FragColor = vec4(vec3(1.0 - depth), 1.0);
+    }
+}
@@ -0,0 +1,92 @@
+#version 330 core
+struct DirLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+struct SpotLight {
+    vec3 position;
+    vec3 direction;
+    float cutOff;
+    float outerCutOff;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+};
+
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D gPosition;
+uniform sampler2D gNormal;
+uniform sampler2D gAlbedoSpec;
+uniform sampler2D ssao;
+uniform bool ssaoEnabled;
+
+uniform DirLight dirLight;
+uniform SpotLight spotLight;
+uniform float shininess;
+
+vec3 addDirectionalLight(DirLight light, vec3 norm, vec3 fragPos, vec3 albedo, float specularStrength, float occlusion)
+{
+    vec3 lightDir = normalize(-light.direction);
+    float diff = max(dot(norm, lightDir), 0.0);
+
+    vec3 viewDir = normalize(-fragPos);
+    vec3 reflectDir = reflect(-lightDir, norm);
+    float spec = pow(max(dot(viewDir, reflectDir), 0.0), shininess);
+
+    vec3 ambient = light.ambient * albedo * occlusion;
+    vec3 diffuse = light.diffuse * diff * albedo;
+    vec3 specular = light.specular * spec * specularStrength;
+
+    return (ambient + diffuse + specular);
+}
+
+vec3 addSpotLight(SpotLight light, vec3 norm, vec3 fragPos, vec3 albedo, float specularStrength, float occlusion)
+{
+    vec3 lightDir = normalize(light.position - fragPos);
+
+    float theta = dot(lightDir, normalize(-light.direction));
+    float epsilon = light.cutOff - light.outerCutOff;
+    float intensity = clamp((theta - light.outerCutOff) / epsilon, 0.0, 1.0);
+
+    float distance = length(light.position - fragPos);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * pow(distance, 2));
+
+    float diff = max(dot(norm, lightDir), 0.0);
+
+    vec3 viewDir = normalize(-fragPos);
+    vec3 reflectDir = reflect(-lightDir, norm);
+    float spec = pow(max(dot(viewDir, reflectDir), 0.0), shininess);
+
+    vec3 ambient = light.ambient * albedo * occlusion;
+    vec3 diffuse = light.diffuse * diff * albedo * intensity;
+    vec3 specular = light.specular * spec * specularStrength * intensity;
+
+    return (ambient + diffuse + specular) * attenuation;
+}
+
+void main()
+{
+    vec4 position = texture(gPosition, TexCoords);
+    if (position.w == 0.0) {
+        discard;
+    }
+    vec3 norm = texture(gNormal, TexCoords).rgb;
+    vec4 albedoSpec = texture(gAlbedoSpec, TexCoords);
+    float occlusion = ssaoEnabled ? texture(ssao, TexCoords).r : 1.0;
+
+    vec3 outputColor = addDirectionalLight(dirLight, norm, position.xyz, albedoSpec.rgb, albedoSpec.a, occlusion);
+    outputColor += addSpotLight(spotLight, norm, position.xyz, albedoSpec.rgb, albedoSpec.a, occlusion);
+    FragColor = vec4(outputColor, 1.0);
+}
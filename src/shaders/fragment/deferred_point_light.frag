@@ -0,0 +1,55 @@
+#version 330 core
+struct PointLight {
+    vec3 position;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+};
+
+out vec4 FragColor;
+
+uniform sampler2D gPosition;
+uniform sampler2D gNormal;
+uniform sampler2D gAlbedoSpec;
+uniform sampler2D ssao;
+uniform bool ssaoEnabled;
+
+uniform vec2 screenSize;
+uniform PointLight light;
+uniform float shininess;
+
+// Runs for the pixels covered by the light volume, the results of every light get added together
+// through blending
+void main()
+{
+    vec2 texCoords = gl_FragCoord.xy / screenSize;
+    vec4 position = texture(gPosition, texCoords);
+    if (position.w == 0.0) {
+        discard;
+    }
+    vec3 fragPos = position.xyz;
+    vec3 norm = texture(gNormal, texCoords).rgb;
+    vec4 albedoSpec = texture(gAlbedoSpec, texCoords);
+    float occlusion = ssaoEnabled ? texture(ssao, texCoords).r : 1.0;
+
+    float distance = length(light.position - fragPos);
+    float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * pow(distance, 2));
+
+    vec3 lightDir = normalize(light.position - fragPos);
+    float diff = max(dot(norm, lightDir), 0.0);
+
+    vec3 viewDir = normalize(-fragPos);
+    vec3 reflectDir = reflect(-lightDir, norm);
+    float spec = pow(max(dot(viewDir, reflectDir), 0.0), shininess);
+
+    vec3 ambient = light.ambient * albedoSpec.rgb * occlusion;
+    vec3 diffuse = light.diffuse * diff * albedoSpec.rgb;
+    vec3 specular = light.specular * spec * albedoSpec.a;
+
+    FragColor = vec4((ambient + diffuse + specular) * attenuation, 1.0);
+}
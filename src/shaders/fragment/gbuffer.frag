@@ -0,0 +1,24 @@
+#version 330 core
+struct Material {
+    sampler2D diffuse;
+    sampler2D specular;
+};
+
+layout (location = 0) out vec4 gPosition;
+layout (location = 1) out vec4 gNormal;
+layout (location = 2) out vec4 gAlbedoSpec;
+
+in vec3 FragPos;
+in vec3 Normal;
+in vec2 TexCoords;
+
+uniform Material material;
+
+void main()
+{
+    // alpha of 1 marks the pixel as covered by geometry, the background keeps the clear value of 0
+    gPosition = vec4(FragPos, 1.0);
+    gNormal = vec4(normalize(Normal), 1.0);
+    gAlbedoSpec.rgb = texture(material.diffuse, TexCoords).rgb;
+    gAlbedoSpec.a = texture(material.specular, TexCoords).r;
+}
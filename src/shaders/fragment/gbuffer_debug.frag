@@ -0,0 +1,40 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D gPosition;
+uniform sampler2D gNormal;
+uniform sampler2D gAlbedoSpec;
+uniform sampler2D ssao;
+
+// 1: position, 2: normal, 3: albedo, 4: specular, 5: the first four, one per quarter of the screen,
+// 6: ambient occlusion
+uniform int view;
+
+vec3 attachment(int index, vec2 texCoords)
+{
+    if (index == 1) {
+        // view space positions are unbounded, show them in steps of 10 units
+        return abs(texture(gPosition, texCoords).xyz) / 10.0;
+    } else if (index == 2) {
+        return texture(gNormal, texCoords).xyz * 0.5 + 0.5;
+    } else if (index == 3) {
+        return texture(gAlbedoSpec, texCoords).rgb;
+    } else if (index == 6) {
+        return vec3(texture(ssao, texCoords).r);
+    }
+    return vec3(texture(gAlbedoSpec, texCoords).a);
+}
+
+void main()
+{
+    if (view == 5) {
+        vec2 quadrant = floor(TexCoords * 2.0);
+        // position top left, normal top right, albedo bottom left and specular bottom right
+        int index = int(quadrant.x) + 2 * (1 - int(quadrant.y)) + 1;
+        FragColor = vec4(attachment(index, fract(TexCoords * 2.0)), 1.0);
+    } else {
+        FragColor = vec4(attachment(view, TexCoords), 1.0);
+    }
+}
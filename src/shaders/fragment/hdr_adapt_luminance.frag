@@ -0,0 +1,20 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+// 1x1 texture with the average log luminance of the current frame
+uniform sampler2D averageLuminance;
+// 1x1 texture with the luminance the eye was adapted to on the previous frame
+uniform sampler2D previousLuminance;
+uniform float deltaTime;
+uniform float adaptationSpeed;
+uniform bool reset;
+
+void main()
+{
+    float current = exp(texture(averageLuminance, vec2(0.5)).r);
+    float previous = texture(previousLuminance, vec2(0.5)).r;
+    float adapted = reset ? current : previous + (current - previous) * (1.0 - exp(-deltaTime * adaptationSpeed));
+    FragColor = vec4(adapted, 0.0, 0.0, 1.0);
+}
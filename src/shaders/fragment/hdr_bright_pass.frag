@@ -0,0 +1,21 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+uniform float threshold;
+uniform float knee;
+
+void main()
+{
+    vec3 color = texture(screenTexture, TexCoords).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+
+    // quadratic curve around the threshold so bloom fades in instead of popping
+    float soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    soft = (soft * soft) / (4.0 * knee + 0.0001);
+    float contribution = max(soft, brightness - threshold) / max(brightness, 0.0001);
+
+    FragColor = vec4(color * contribution, 1.0);
+}
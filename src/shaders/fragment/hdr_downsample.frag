@@ -0,0 +1,18 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+// texel size of the texture being sampled
+uniform vec2 texelSize;
+
+void main()
+{
+    vec2 halfTexel = texelSize * 0.5;
+    float sum = texture(screenTexture, TexCoords + vec2(-halfTexel.x, -halfTexel.y)).r;
+    sum += texture(screenTexture, TexCoords + vec2( halfTexel.x, -halfTexel.y)).r;
+    sum += texture(screenTexture, TexCoords + vec2(-halfTexel.x,  halfTexel.y)).r;
+    sum += texture(screenTexture, TexCoords + vec2( halfTexel.x,  halfTexel.y)).r;
+    FragColor = vec4(sum * 0.25, 0.0, 0.0, 1.0);
+}
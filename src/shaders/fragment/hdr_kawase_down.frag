@@ -0,0 +1,19 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+// texel size of the texture being sampled, which is twice the size of the target
+uniform vec2 texelSize;
+
+void main()
+{
+    vec2 halfPixel = texelSize * 0.5;
+    vec3 sum = texture(screenTexture, TexCoords).rgb * 4.0;
+    sum += texture(screenTexture, TexCoords - halfPixel).rgb;
+    sum += texture(screenTexture, TexCoords + halfPixel).rgb;
+    sum += texture(screenTexture, TexCoords + vec2(halfPixel.x, -halfPixel.y)).rgb;
+    sum += texture(screenTexture, TexCoords - vec2(halfPixel.x, -halfPixel.y)).rgb;
+    FragColor = vec4(sum / 8.0, 1.0);
+}
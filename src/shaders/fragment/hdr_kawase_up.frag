@@ -0,0 +1,22 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+// texel size of the texture being sampled, which is half the size of the target
+uniform vec2 texelSize;
+
+void main()
+{
+    vec2 halfPixel = texelSize * 0.5;
+    vec3 sum = texture(screenTexture, TexCoords + vec2(-halfPixel.x * 2.0, 0.0)).rgb;
+    sum += texture(screenTexture, TexCoords + vec2(-halfPixel.x, halfPixel.y)).rgb * 2.0;
+    sum += texture(screenTexture, TexCoords + vec2(0.0, halfPixel.y * 2.0)).rgb;
+    sum += texture(screenTexture, TexCoords + vec2(halfPixel.x, halfPixel.y)).rgb * 2.0;
+    sum += texture(screenTexture, TexCoords + vec2(halfPixel.x * 2.0, 0.0)).rgb;
+    sum += texture(screenTexture, TexCoords + vec2(halfPixel.x, -halfPixel.y)).rgb * 2.0;
+    sum += texture(screenTexture, TexCoords + vec2(0.0, -halfPixel.y * 2.0)).rgb;
+    sum += texture(screenTexture, TexCoords + vec2(-halfPixel.x, -halfPixel.y)).rgb * 2.0;
+    FragColor = vec4(sum / 12.0, 1.0);
+}
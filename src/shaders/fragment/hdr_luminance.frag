@@ -0,0 +1,14 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+
+void main()
+{
+    vec3 color = texture(screenTexture, TexCoords).rgb;
+    float luminance = dot(color, vec3(0.2126, 0.7152, 0.0722));
+    // averaging the logarithm keeps a few very bright pixels from dominating the result
+    FragColor = vec4(log(luminance + 0.0001), 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,62 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D hdrTexture;
+uniform sampler2D bloomTexture;
+uniform sampler2D luminanceTexture;
+
+// 0: clamp, 1: Reinhard, 2: ACES, 3: exposure
+uniform int toneMapping;
+uniform float exposure;
+uniform float gamma;
+
+uniform bool bloom;
+uniform float bloomIntensity;
+
+uniform bool autoExposure;
+uniform float keyValue;
+uniform float minExposure;
+uniform float maxExposure;
+
+// Curve fitted to the ACES reference transform by Krzysztof Narkowicz
+vec3 aces(vec3 color)
+{
+    const float a = 2.51;
+    const float b = 0.03;
+    const float c = 2.43;
+    const float d = 0.59;
+    const float e = 0.14;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), 0.0, 1.0);
+}
+
+void main()
+{
+    vec3 hdrColor = texture(hdrTexture, TexCoords).rgb;
+    if (bloom) {
+        hdrColor += texture(bloomTexture, TexCoords).rgb * bloomIntensity;
+    }
+
+    float currentExposure = exposure;
+    if (autoExposure) {
+        // scale the scene so its average luminance ends up at the key value (middle grey)
+        float averageLuminance = texture(luminanceTexture, vec2(0.5)).r;
+        currentExposure = clamp(keyValue / max(averageLuminance, 0.0001), minExposure, maxExposure);
+    }
+
+    vec3 mapped;
+    if (toneMapping == 1) {
+        vec3 exposed = hdrColor * currentExposure;
+        mapped = exposed / (exposed + vec3(1.0));
+    } else if (toneMapping == 2) {
+        mapped = aces(hdrColor * currentExposure);
+    } else if (toneMapping == 3) {
+        mapped = vec3(1.0) - exp(-hdrColor * currentExposure);
+    } else {
+        mapped = clamp(hdrColor * currentExposure, 0.0, 1.0);
+    }
+
+    mapped = pow(mapped, vec3(1.0 / gamma));
+    FragColor = vec4(mapped, 1.0);
+}
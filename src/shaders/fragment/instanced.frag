@@ -0,0 +1,35 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec3 FragPos;
+in vec3 Normal;
+in vec2 TexCoords;
+in vec4 Color;
+// x scales the highlights
+in vec4 Data;
+
+struct DirLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+uniform DirLight dirLight;
+
+void main()
+{
+    vec3 norm = normalize(Normal);
+    vec3 viewDir = normalize(-FragPos);
+    vec3 lightDir = normalize(-dirLight.direction);
+
+    float diff = max(dot(norm, lightDir), 0.0);
+    vec3 halfwayDir = normalize(lightDir + viewDir);
+    float spec = pow(max(dot(norm, halfwayDir), 0.0), 64.0);
+
+    vec3 ambient = dirLight.ambient * Color.rgb;
+    vec3 diffuse = dirLight.diffuse * diff * Color.rgb;
+    vec3 specular = dirLight.specular * spec * Data.x;
+    FragColor = vec4(ambient + diffuse + specular, Color.a);
+}
@@ -49,6 +49,27 @@ uniform SpotLight spotLight;
 uniform PointLight pointLights[NR_POINT_LIGHTS];
 uniform vec3 lightPositions[NR_POINT_LIGHTS];
 
+// Phong reflects the light around the normal, Blinn-Phong compares the normal with the vector
+// halfway between the light and the view directions
+uniform bool blinn;
+// diffuse maps are expected to be loaded as sRGB, so lighting happens in linear space and the
+// result is encoded back to sRGB here unless a later pass (like tone mapping) does it
+uniform bool gammaCorrection;
+
+const float GAMMA = 2.2;
+
+float specularFactor(vec3 norm, vec3 lightDir, vec3 viewDir)
+{
+    if (blinn) {
+        vec3 halfwayDir = normalize(lightDir + viewDir);
+        // the halfway vector is closer to the normal than the reflection is to the view, the
+        // exponent is raised so highlights keep about the same size as with Phong
+        return pow(max(dot(norm, halfwayDir), 0.0), material.shininess * 4.0);
+    }
+    vec3 reflectDir = reflect(-lightDir, norm);
+    return pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+}
+
 vec3 addDirectionalLight(DirLight light, vec3 norm, vec3 fragPos);
 vec3 addPointLight(PointLight light, vec3 norm, vec3 lightPosition, vec3 fragPos);
 vec3 addSpotLight(SpotLight light, vec3 norm, vec3 fragPos);
@@ -59,8 +80,7 @@ vec3 addDirectionalLight(DirLight light, vec3 norm, vec3 fragPos)
     float diff = max(dot(norm, lightDir), 0.0);
 
     vec3 viewDir = normalize(-fragPos);
-    vec3 reflectDir = reflect(-lightDir, norm);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+    float spec = specularFactor(norm, lightDir, viewDir);
 
     vec3 ambient = light.ambient * vec3(texture(material.diffuse, TexCoords));
     vec3 diffuse = light.diffuse * diff * vec3(texture(material.diffuse, TexCoords));
@@ -78,8 +98,7 @@ vec3 addPointLight(PointLight light, vec3 norm, vec3 lightPosition, vec3 fragPos
     float diff = max(dot(norm, lightDir), 0.0);
 
     vec3 viewDir = normalize(-fragPos);
-    vec3 reflectDir = reflect(-lightDir, norm);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+    float spec = specularFactor(norm, lightDir, viewDir);
 
     vec3 ambient = light.ambient * vec3(texture(material.diffuse, TexCoords));
     vec3 diffuse = light.diffuse * diff * vec3(texture(material.diffuse, TexCoords));
@@ -106,8 +125,7 @@ vec3 addSpotLight(SpotLight light, vec3 norm, vec3 fragPos)
     float diff = max(dot(norm, lightDir), 0.0);
 
     vec3 viewDir = normalize(-fragPos);
-    vec3 reflectDir = reflect(-lightDir, norm);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+    float spec = specularFactor(norm, lightDir, viewDir);
 
     vec3 ambient = light.ambient * vec3(texture(material.diffuse, TexCoords));
     vec3 diffuse = light.diffuse * diff * vec3(texture(material.diffuse, TexCoords));
@@ -133,5 +151,8 @@ void main()
     }
     outputColor += addSpotLight(spotLight, norm, FragPos);
 
+    if (gammaCorrection) {
+        outputColor = pow(outputColor, vec3(1.0 / GAMMA));
+    }
     FragColor = vec4(outputColor, 1.0);
 }
@@ -0,0 +1,171 @@
+#version 330 core
+struct Material {
+    sampler2D diffuse;
+    sampler2D specular;
+    sampler2D emission;
+    sampler2D opacity;
+
+    bool hasDiffuse;
+    vec3 diffuseColor;
+    bool hasSpecular;
+    // colour maps tint the highlights, intensity maps only use their red channel
+    bool specularIsColor;
+    vec3 specularColor;
+    float shininess;
+
+    bool hasEmission;
+    vec2 emissionOffset;
+    float emissionIntensity;
+
+    bool hasOpacity;
+    float alpha;
+    float alphaCutoff;
+    // 0: opaque, 1: cutout, 2: blended, 3: additive
+    int blend;
+};
+
+struct DirLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+struct PointLight {
+    vec3 position;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+};
+
+struct SpotLight {
+    vec3 position;
+    vec3 direction;
+    float cutOff;
+    float outerCutOff;
+
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+
+    float constant;
+    float linear;
+    float quadratic;
+};
+#define NR_POINT_LIGHTS 4
+
+layout (location = 0) out vec4 FragColor;
+// only written to by the weighted blended transparency pass, see oit.rs
+layout (location = 1) out vec4 OitWeight;
+
+in vec3 FragPos;
+in vec3 Normal;
+in vec2 TexCoords;
+
+uniform Material material;
+uniform DirLight dirLight;
+uniform SpotLight spotLight;
+uniform PointLight pointLights[NR_POINT_LIGHTS];
+uniform bool weightedBlended;
+// only the light numbered isolatedLight adds up when set, 0 being the directional light, 1 to
+// NR_POINT_LIGHTS the point lights and the one after the spot light, see debug_view.rs
+uniform bool isolateLight;
+uniform int isolatedLight;
+
+// Blinn-Phong, see lights.frag
+float specularFactor(vec3 norm, vec3 lightDir, vec3 viewDir)
+{
+    vec3 halfwayDir = normalize(lightDir + viewDir);
+    return pow(max(dot(norm, halfwayDir), 0.0), material.shininess * 4.0);
+}
+
+vec3 addLight(vec3 ambientColor, vec3 diffuseColor, vec3 specularColor, vec3 lightDir, vec3 norm, vec3 viewDir, vec3 albedo, vec3 specularMap)
+{
+    float diff = max(dot(norm, lightDir), 0.0);
+    float spec = specularFactor(norm, lightDir, viewDir);
+
+    vec3 ambient = ambientColor * albedo;
+    vec3 diffuse = diffuseColor * diff * albedo;
+    vec3 specular = specularColor * spec * specularMap;
+    return ambient + diffuse + specular;
+}
+
+float attenuation(float constant, float linear, float quadratic, float distance)
+{
+    return 1.0 / (constant + linear * distance + quadratic * pow(distance, 2));
+}
+
+bool lightShown(int index)
+{
+    return !isolateLight || isolatedLight == index;
+}
+
+void main()
+{
+    vec4 diffuseTexel = material.hasDiffuse ? texture(material.diffuse, TexCoords) : vec4(material.diffuseColor, 1.0);
+    float opacity = material.alpha * (material.hasOpacity ? texture(material.opacity, TexCoords).a : diffuseTexel.a);
+    if (material.blend == 0) {
+        opacity = 1.0;
+    }
+    // alpha testing: cut out holes instead of blending, so no sorting is needed
+    if (material.blend == 1) {
+        if (opacity < material.alphaCutoff) {
+            discard;
+        }
+        opacity = 1.0;
+    }
+
+    vec3 albedo = diffuseTexel.rgb;
+    vec3 specularMap = material.specularColor;
+    if (material.hasSpecular) {
+        vec3 texel = texture(material.specular, TexCoords).rgb;
+        specularMap = material.specularIsColor ? texel : vec3(texel.r);
+    }
+
+    vec3 norm = normalize(Normal);
+    vec3 viewDir = normalize(-FragPos);
+
+    vec3 outputColor = vec3(0.0);
+    if (lightShown(0)) {
+        outputColor += addLight(dirLight.ambient, dirLight.diffuse, dirLight.specular, normalize(-dirLight.direction), norm, viewDir, albedo, specularMap);
+    }
+
+    for (int i = 0; i < NR_POINT_LIGHTS; i++) {
+        if (!lightShown(i + 1)) {
+            continue;
+        }
+        PointLight light = pointLights[i];
+        vec3 lightDir = normalize(light.position - FragPos);
+        float att = attenuation(light.constant, light.linear, light.quadratic, length(light.position - FragPos));
+        outputColor += addLight(light.ambient, light.diffuse, light.specular, lightDir, norm, viewDir, albedo, specularMap) * att;
+    }
+
+    if (lightShown(NR_POINT_LIGHTS + 1)) {
+        vec3 lightDir = normalize(spotLight.position - FragPos);
+        float theta = dot(lightDir, normalize(-spotLight.direction));
+        float epsilon = spotLight.cutOff - spotLight.outerCutOff;
+        float intensity = clamp((theta - spotLight.outerCutOff) / epsilon, 0.0, 1.0);
+        float att = attenuation(spotLight.constant, spotLight.linear, spotLight.quadratic, length(spotLight.position - FragPos));
+        outputColor += addLight(spotLight.ambient, spotLight.diffuse * intensity, spotLight.specular * intensity, lightDir, norm, viewDir, albedo, specularMap) * att;
+    }
+
+    if (material.hasEmission && !isolateLight) {
+        outputColor += texture(material.emission, TexCoords + material.emissionOffset).rgb * material.emissionIntensity;
+    }
+
+    if (weightedBlended) {
+        // weight from McGuire and Bavoil, favouring opaque and near fragments, so the nearest
+        // layers dominate the average the way they would hide the others when sorted
+        float weight = clamp(pow(min(1.0, opacity * 10.0) + 0.01, 3.0) * 1e8 * pow(1.0 - gl_FragCoord.z * 0.9, 3.0), 1e-2, 3e3);
+        FragColor = vec4(outputColor * opacity * weight, opacity);
+        OitWeight = vec4(opacity * weight);
+        return;
+    }
+    FragColor = vec4(outputColor, opacity);
+}
@@ -0,0 +1,21 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+// premultiplied weighted colours summed in rgb, product of the transparencies in a
+uniform sampler2D accumulation;
+// sum of the weighted opacities
+uniform sampler2D weights;
+
+void main()
+{
+    vec4 accumulated = texture(accumulation, TexCoords);
+    float revealage = accumulated.a;
+    // nothing blended over this pixel
+    if (revealage >= 0.9999) {
+        discard;
+    }
+    vec3 average = accumulated.rgb / max(texture(weights, TexCoords).r, 1e-5);
+    FragColor = vec4(average, 1.0 - revealage);
+}
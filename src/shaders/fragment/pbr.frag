@@ -0,0 +1,218 @@
+#version 330 core
+// Metallic-roughness material, every map falls back to its scalar value when it is not bound
+struct Material {
+    vec3 albedo;
+    float metallic;
+    float roughness;
+    float ao;
+    vec3 emissive;
+
+    sampler2D albedoMap;
+    sampler2D metallicMap;
+    sampler2D roughnessMap;
+    sampler2D aoMap;
+    sampler2D normalMap;
+    sampler2D emissiveMap;
+
+    bool hasAlbedoMap;
+    bool hasMetallicMap;
+    bool hasRoughnessMap;
+    bool hasAoMap;
+    bool hasNormalMap;
+    bool hasEmissiveMap;
+};
+
+// the diffuse colour of the lights is used as their radiance, their specular colour is ignored
+struct DirLight {
+    vec3 direction;
+
+    vec3 ambient;
+    vec3 diffuse;
+};
+
+struct PointLight {
+    vec3 position;
+
+    vec3 ambient;
+    vec3 diffuse;
+
+    float constant;
+    float linear;
+    float quadratic;
+};
+
+struct SpotLight {
+    vec3 position;
+    vec3 direction;
+    float cutOff;
+    float outerCutOff;
+
+    vec3 ambient;
+    vec3 diffuse;
+
+    float constant;
+    float linear;
+    float quadratic;
+};
+#define MAX_POINT_LIGHTS 16
+
+const float PI = 3.14159265359;
+
+out vec4 FragColor;
+
+in vec3 FragPos;
+in vec3 Normal;
+in vec2 TexCoords;
+
+uniform Material material;
+uniform DirLight dirLight;
+uniform SpotLight spotLight;
+uniform PointLight pointLights[MAX_POINT_LIGHTS];
+uniform int pointLightCount;
+
+// image based lighting, replaces the ambient colour of the lights when enabled
+uniform bool useIbl;
+uniform samplerCube irradianceMap;
+uniform samplerCube prefilterMap;
+uniform sampler2D brdfLUT;
+uniform float prefilterMaxLod;
+// the maps are in world space while lighting happens in view space
+uniform mat4 inverseView;
+
+// GGX/Trowbridge-Reitz normal distribution
+float distributionGGX(vec3 N, vec3 H, float roughness)
+{
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float NdotH = max(dot(N, H), 0.0);
+    float denom = NdotH * NdotH * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denom * denom);
+}
+
+float geometrySchlickGGX(float NdotV, float roughness)
+{
+    float r = roughness + 1.0;
+    float k = (r * r) / 8.0;
+    return NdotV / (NdotV * (1.0 - k) + k);
+}
+
+// Smith's method, masking from the view direction and shadowing from the light direction
+float geometrySmith(vec3 N, vec3 V, vec3 L, float roughness)
+{
+    return geometrySchlickGGX(max(dot(N, V), 0.0), roughness) * geometrySchlickGGX(max(dot(N, L), 0.0), roughness);
+}
+
+vec3 fresnelSchlick(float cosTheta, vec3 F0)
+{
+    return F0 + (1.0 - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+// rough surfaces reflect less of the environment at grazing angles
+vec3 fresnelSchlickRoughness(float cosTheta, vec3 F0, float roughness)
+{
+    return F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+// diffuse irradiance plus the split sum approximation of the specular reflection
+vec3 imageBasedLighting(vec3 N, vec3 V, vec3 albedo, float metallic, float roughness)
+{
+    vec3 F0 = mix(vec3(0.04), albedo, metallic);
+    float NdotV = max(dot(N, V), 0.0);
+    vec3 F = fresnelSchlickRoughness(NdotV, F0, roughness);
+    vec3 kD = (vec3(1.0) - F) * (1.0 - metallic);
+
+    mat3 toWorld = mat3(inverseView);
+    vec3 irradiance = texture(irradianceMap, toWorld * N).rgb;
+    vec3 diffuse = irradiance * albedo;
+
+    vec3 R = toWorld * reflect(-V, N);
+    vec3 prefilteredColor = textureLod(prefilterMap, R, roughness * prefilterMaxLod).rgb;
+    vec2 brdf = texture(brdfLUT, vec2(NdotV, roughness)).rg;
+    vec3 specular = prefilteredColor * (F * brdf.x + brdf.y);
+
+    return kD * diffuse + specular;
+}
+
+// Cook-Torrance BRDF times the incoming radiance for a light coming from L
+vec3 cookTorrance(vec3 N, vec3 V, vec3 L, vec3 radiance, vec3 albedo, float metallic, float roughness)
+{
+    vec3 H = normalize(V + L);
+    // dielectrics reflect about 4% at normal incidence, metals tint the reflection with their albedo
+    vec3 F0 = mix(vec3(0.04), albedo, metallic);
+
+    float NDF = distributionGGX(N, H, roughness);
+    float G = geometrySmith(N, V, L, roughness);
+    vec3 F = fresnelSchlick(max(dot(H, V), 0.0), F0);
+
+    float NdotL = max(dot(N, L), 0.0);
+    vec3 specular = NDF * G * F / (4.0 * max(dot(N, V), 0.0) * NdotL + 0.0001);
+
+    // whatever is not reflected gets refracted and diffused, metals don't diffuse at all
+    vec3 kD = (vec3(1.0) - F) * (1.0 - metallic);
+    return (kD * albedo / PI + specular) * radiance * NdotL;
+}
+
+float attenuation(float constant, float linear, float quadratic, float distance)
+{
+    return 1.0 / (constant + linear * distance + quadratic * pow(distance, 2));
+}
+
+// Normal map without per vertex tangents: the tangent frame is rebuilt from the screen space
+// derivatives of the position and texture coordinates
+vec3 perturbNormal(vec3 N, vec3 fragPos, vec2 texCoords)
+{
+    vec3 tangentNormal = texture(material.normalMap, texCoords).xyz * 2.0 - 1.0;
+
+    vec3 dp1 = dFdx(fragPos);
+    vec3 dp2 = dFdy(fragPos);
+    vec2 duv1 = dFdx(texCoords);
+    vec2 duv2 = dFdy(texCoords);
+
+    vec3 dp2perp = cross(dp2, N);
+    vec3 dp1perp = cross(N, dp1);
+    vec3 T = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 B = dp2perp * duv1.y + dp1perp * duv2.y;
+    float invmax = inversesqrt(max(dot(T, T), dot(B, B)));
+    return normalize(mat3(T * invmax, B * invmax, N) * tangentNormal);
+}
+
+void main()
+{
+    vec3 albedo = material.hasAlbedoMap ? texture(material.albedoMap, TexCoords).rgb : material.albedo;
+    float metallic = material.hasMetallicMap ? texture(material.metallicMap, TexCoords).r : material.metallic;
+    float roughness = material.hasRoughnessMap ? texture(material.roughnessMap, TexCoords).r : material.roughness;
+    float ao = material.hasAoMap ? texture(material.aoMap, TexCoords).r : material.ao;
+    vec3 emissive = material.hasEmissiveMap ? texture(material.emissiveMap, TexCoords).rgb : material.emissive;
+    // a perfectly smooth surface turns the specular highlight into a single point
+    roughness = clamp(roughness, 0.05, 1.0);
+
+    vec3 N = normalize(Normal);
+    if (material.hasNormalMap) {
+        N = perturbNormal(N, FragPos, TexCoords);
+    }
+    vec3 V = normalize(-FragPos);
+
+    vec3 Lo = cookTorrance(N, V, normalize(-dirLight.direction), dirLight.diffuse, albedo, metallic, roughness);
+    vec3 ambient = dirLight.ambient;
+
+    for (int i = 0; i < pointLightCount && i < MAX_POINT_LIGHTS; i++) {
+        PointLight light = pointLights[i];
+        float distance = length(light.position - FragPos);
+        float att = attenuation(light.constant, light.linear, light.quadratic, distance);
+        vec3 L = normalize(light.position - FragPos);
+        Lo += cookTorrance(N, V, L, light.diffuse * att, albedo, metallic, roughness);
+        ambient += light.ambient * att;
+    }
+
+    vec3 L = normalize(spotLight.position - FragPos);
+    float theta = dot(L, normalize(-spotLight.direction));
+    float epsilon = spotLight.cutOff - spotLight.outerCutOff;
+    float intensity = clamp((theta - spotLight.outerCutOff) / epsilon, 0.0, 1.0);
+    float att = attenuation(spotLight.constant, spotLight.linear, spotLight.quadratic, length(spotLight.position - FragPos));
+    Lo += cookTorrance(N, V, L, spotLight.diffuse * intensity * att, albedo, metallic, roughness);
+    ambient += spotLight.ambient * att;
+
+    vec3 color = useIbl ? imageBasedLighting(N, V, albedo, metallic, roughness) : ambient * albedo;
+    color = color * ao + Lo + emissive;
+    FragColor = vec4(color, 1.0);
+}
@@ -25,25 +25,46 @@ in vec2 TexCoords;
 uniform Material material;
 uniform Light light;
 
+// same specular models and output encoding as lights.frag, see the comments there
+uniform bool blinn;
+// nothing loads the diffuse map of this shader as sRGB, so it is decoded here instead
+uniform bool gammaCorrection;
+
+const float GAMMA = 2.2;
+
+float specularFactor(vec3 norm, vec3 lightDir, vec3 viewDir)
+{
+    if (blinn) {
+        vec3 halfwayDir = normalize(lightDir + viewDir);
+        return pow(max(dot(norm, halfwayDir), 0.0), material.shininess * 4.0);
+    }
+    vec3 reflectDir = reflect(-lightDir, norm);
+    return pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+}
+
 void main()
 {
     // attenuation
     float distance = length(LightPos - FragPos);
     float attenuation = 1.0 / (light.constant + light.linear * distance + light.quadratic * pow(distance, 2));
 
+    vec3 diffuseColor = vec3(texture(material.diffuse, TexCoords));
+    if (gammaCorrection) {
+        diffuseColor = pow(diffuseColor, vec3(GAMMA));
+    }
+
     // ambient
-    vec3 ambient = light.ambient * vec3(texture(material.diffuse, TexCoords));
+    vec3 ambient = light.ambient * diffuseColor;
 
     // diffuse
     vec3 norm = normalize(Normal);
     vec3 lightDir = normalize(LightPos - FragPos);
     float diff = max(dot(norm, lightDir), 0.0);
-    vec3 diffuse = light.diffuse * diff * vec3(texture(material.diffuse, TexCoords));
+    vec3 diffuse = light.diffuse * diff * diffuseColor;
 
     // specular
     vec3 viewDir = normalize(-FragPos);
-    vec3 reflectDir = reflect(-lightDir, norm);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+    float spec = specularFactor(norm, lightDir, viewDir);
     vec3 specular = light.specular * spec * vec3(texture(material.specular, TexCoords));
 
     ambient *= attenuation;
@@ -51,5 +72,8 @@ void main()
     specular *= attenuation;
 
     vec3 result = ambient + diffuse + specular;
+    if (gammaCorrection) {
+        result = pow(result, vec3(1.0 / GAMMA));
+    }
     FragColor = vec4(result, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,19 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+uniform float strength;
+
+void main()
+{
+    // channels get split further apart the closer they are to the borders of the screen
+    vec2 direction = TexCoords - vec2(0.5);
+    vec2 offset = direction * strength;
+
+    float red = texture(screenTexture, TexCoords + offset).r;
+    float green = texture(screenTexture, TexCoords).g;
+    float blue = texture(screenTexture, TexCoords - offset).b;
+    FragColor = vec4(red, green, blue, 1.0);
+}
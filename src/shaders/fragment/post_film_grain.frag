@@ -0,0 +1,21 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+uniform float intensity;
+uniform float time;
+
+float random(vec2 coords)
+{
+    return fract(sin(dot(coords, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+void main()
+{
+    vec3 color = vec3(texture(screenTexture, TexCoords));
+    // change the seed every frame so the grain moves like on film
+    float noise = random(TexCoords + fract(time)) - 0.5;
+    FragColor = vec4(color + noise * intensity, 1.0);
+}
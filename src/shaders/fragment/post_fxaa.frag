@@ -0,0 +1,58 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+uniform vec2 texelSize;
+uniform float spanMax;
+uniform float reduceMin;
+uniform float reduceMul;
+
+float luma(vec3 color)
+{
+    return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+// Simplified FXAA from Timothy Lottes, only looks at the 4 diagonal neighbours to find the
+// direction of the edge and blurs along it
+void main()
+{
+    vec3 rgbNW = texture(screenTexture, TexCoords + vec2(-1.0, -1.0) * texelSize).rgb;
+    vec3 rgbNE = texture(screenTexture, TexCoords + vec2( 1.0, -1.0) * texelSize).rgb;
+    vec3 rgbSW = texture(screenTexture, TexCoords + vec2(-1.0,  1.0) * texelSize).rgb;
+    vec3 rgbSE = texture(screenTexture, TexCoords + vec2( 1.0,  1.0) * texelSize).rgb;
+    vec3 rgbM  = texture(screenTexture, TexCoords).rgb;
+
+    float lumaNW = luma(rgbNW);
+    float lumaNE = luma(rgbNE);
+    float lumaSW = luma(rgbSW);
+    float lumaSE = luma(rgbSE);
+    float lumaM  = luma(rgbM);
+
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    vec2 direction;
+    direction.x = -((lumaNW + lumaNE) - (lumaSW + lumaSE));
+    direction.y =  ((lumaNW + lumaSW) - (lumaNE + lumaSE));
+
+    float directionReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * (0.25 * reduceMul), reduceMin);
+    float inverseDirectionMin = 1.0 / (min(abs(direction.x), abs(direction.y)) + directionReduce);
+    direction = clamp(direction * inverseDirectionMin, vec2(-spanMax), vec2(spanMax)) * texelSize;
+
+    vec3 rgbA = 0.5 * (
+        texture(screenTexture, TexCoords + direction * (1.0 / 3.0 - 0.5)).rgb +
+        texture(screenTexture, TexCoords + direction * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 rgbB = rgbA * 0.5 + 0.25 * (
+        texture(screenTexture, TexCoords + direction * -0.5).rgb +
+        texture(screenTexture, TexCoords + direction * 0.5).rgb);
+
+    // the wide sample went past the edge, fall back to the narrow one
+    float lumaB = luma(rgbB);
+    if (lumaB < lumaMin || lumaB > lumaMax) {
+        FragColor = vec4(rgbA, 1.0);
+    } else {
+        FragColor = vec4(rgbB, 1.0);
+    }
+}
@@ -0,0 +1,14 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+
+void main()
+{
+    vec4 color = texture(screenTexture, TexCoords);
+    // weighted average, the eye is more sensitive to green and less to blue
+    float average = 0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b;
+    FragColor = vec4(vec3(average), 1.0);
+}
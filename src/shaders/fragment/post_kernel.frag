@@ -0,0 +1,29 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+uniform vec2 texelSize;
+uniform float kernel[9];
+
+void main()
+{
+    vec2 offsets[9] = vec2[](
+        vec2(-texelSize.x,  texelSize.y), // top-left
+        vec2( 0.0,          texelSize.y), // top-center
+        vec2( texelSize.x,  texelSize.y), // top-right
+        vec2(-texelSize.x,  0.0),         // center-left
+        vec2( 0.0,          0.0),         // center-center
+        vec2( texelSize.x,  0.0),         // center-right
+        vec2(-texelSize.x, -texelSize.y), // bottom-left
+        vec2( 0.0,         -texelSize.y), // bottom-center
+        vec2( texelSize.x, -texelSize.y)  // bottom-right
+    );
+
+    vec3 color = vec3(0.0);
+    for (int i = 0; i < 9; i++) {
+        color += vec3(texture(screenTexture, TexCoords + offsets[i])) * kernel[i];
+    }
+    FragColor = vec4(color, 1.0);
+}
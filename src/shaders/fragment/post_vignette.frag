@@ -0,0 +1,17 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D screenTexture;
+uniform float intensity;
+uniform float radius;
+uniform float softness;
+
+void main()
+{
+    vec3 color = vec3(texture(screenTexture, TexCoords));
+    float distance = length(TexCoords - vec2(0.5));
+    float vignette = smoothstep(radius, radius - softness, distance);
+    FragColor = vec4(color * mix(1.0, vignette, intensity), 1.0);
+}
@@ -0,0 +1,11 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec3 LocalPos;
+
+uniform samplerCube environmentMap;
+
+void main()
+{
+    FragColor = vec4(texture(environmentMap, LocalPos).rgb, 1.0);
+}
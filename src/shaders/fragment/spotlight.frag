@@ -27,6 +27,22 @@ in vec2 TexCoords;
 uniform Material material;
 uniform Light light;
 
+// same specular models and output encoding as lights.frag, see the comments there
+uniform bool blinn;
+uniform bool gammaCorrection;
+
+const float GAMMA = 2.2;
+
+float specularFactor(vec3 norm, vec3 lightDir, vec3 viewDir)
+{
+    if (blinn) {
+        vec3 halfwayDir = normalize(lightDir + viewDir);
+        return pow(max(dot(norm, halfwayDir), 0.0), material.shininess * 4.0);
+    }
+    vec3 reflectDir = reflect(-lightDir, norm);
+    return pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+}
+
 void main()
 {
     vec3 lightDir = normalize(-FragPos);
@@ -49,8 +65,7 @@ void main()
 
     // specular
     vec3 viewDir = normalize(-FragPos);
-    vec3 reflectDir = reflect(-lightDir, norm);
-    float spec = pow(max(dot(viewDir, reflectDir), 0.0), material.shininess);
+    float spec = specularFactor(norm, lightDir, viewDir);
     vec3 specular = light.specular * spec * vec3(texture(material.specular, TexCoords));
 
     diffuse *= intensity;
@@ -61,6 +76,9 @@ void main()
     specular *= attenuation;
 
     vec3 result = ambient + diffuse + specular;
+    if (gammaCorrection) {
+        result = pow(result, vec3(1.0 / GAMMA));
+    }
     FragColor = vec4(result, 1.0);
 //    } else {
 //        FragColor = vec4(light.ambient * vec3(texture(material.diffuse, TexCoords)), 1.0);
@@ -0,0 +1,55 @@
+#version 330 core
+out float FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D gPosition;
+uniform sampler2D gNormal;
+uniform sampler2D texNoise;
+
+uniform vec3 samples[64];
+uniform int kernelSize;
+uniform float radius;
+uniform float bias;
+// the noise texture is 4x4 and gets tiled over the screen
+uniform vec2 noiseScale;
+
+uniform mat4 projection;
+
+void main()
+{
+    vec4 position = texture(gPosition, TexCoords);
+    if (position.w == 0.0) {
+        FragColor = 1.0;
+        return;
+    }
+    vec3 fragPos = position.xyz;
+    vec3 normal = normalize(texture(gNormal, TexCoords).rgb);
+    vec3 randomVec = normalize(texture(texNoise, TexCoords * noiseScale).xyz);
+
+    // TBN that orients the kernel hemisphere around the normal, rotated by the noise vector
+    vec3 tangent = normalize(randomVec - normal * dot(randomVec, normal));
+    vec3 bitangent = cross(normal, tangent);
+    mat3 TBN = mat3(tangent, bitangent, normal);
+
+    float occlusion = 0.0;
+    for (int i = 0; i < kernelSize; i++) {
+        vec3 samplePos = fragPos + TBN * samples[i] * radius;
+
+        // project the sample to find where it lands on the screen
+        vec4 offset = projection * vec4(samplePos, 1.0);
+        offset.xyz /= offset.w;
+        offset.xyz = offset.xyz * 0.5 + 0.5;
+
+        vec4 samplePosition = texture(gPosition, offset.xy);
+        // nothing was drawn there, the background never occludes
+        if (samplePosition.w == 0.0) {
+            continue;
+        }
+        float sampleDepth = samplePosition.z;
+        // geometry far away from the fragment should not darken it
+        float rangeCheck = smoothstep(0.0, 1.0, radius / abs(fragPos.z - sampleDepth));
+        occlusion += (sampleDepth >= samplePos.z + bias ? 1.0 : 0.0) * rangeCheck;
+    }
+    FragColor = 1.0 - (occlusion / kernelSize);
+}
@@ -0,0 +1,35 @@
+#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+layout (location = 2) in vec2 aTexCoords;
+// per instance attributes, see mesh::Instance
+layout (location = 3) in mat4 aModel;
+layout (location = 7) in vec4 aColor;
+layout (location = 8) in vec4 aData;
+
+out vec3 FragPos;
+out vec3 Normal;
+out vec2 TexCoords;
+out vec4 Color;
+out vec4 Data;
+
+uniform mat4 view;
+uniform mat4 projection;
+
+// when drawing one object at a time the instance attributes come from these uniforms instead
+uniform bool instanced;
+uniform mat4 model;
+uniform vec4 color;
+uniform vec4 data;
+
+void main()
+{
+    mat4 instanceModel = instanced ? aModel : model;
+    mat4 viewModel = view * instanceModel;
+    gl_Position = projection * viewModel * vec4(aPos, 1.0);
+    FragPos = vec3(viewModel * vec4(aPos, 1.0));
+    Normal = mat3(transpose(inverse(viewModel))) * aNormal;
+    TexCoords = aTexCoords;
+    Color = instanced ? aColor : color;
+    Data = instanced ? aData : data;
+}
@@ -0,0 +1,16 @@
+#version 330 core
+layout (location = 0) in vec3 aPos;
+
+out vec3 LocalPos;
+
+uniform mat4 view;
+uniform mat4 projection;
+
+void main()
+{
+    LocalPos = aPos;
+    // only the rotation of the camera, the sky stays infinitely far away
+    vec4 position = projection * mat4(mat3(view)) * vec4(aPos, 1.0);
+    // z = w puts it on the far plane, behind everything else
+    gl_Position = position.xyww;
+}
@@ -0,0 +1,18 @@
+#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+
+out VS_OUT {
+    vec3 normal;
+} vs_out;
+
+uniform mat4 model;
+uniform mat4 view;
+
+void main()
+{
+    // the geometry shader grows the normals in view space, then projects both ends
+    mat4 viewModel = view * model;
+    gl_Position = viewModel * vec4(aPos, 1.0);
+    vs_out.normal = normalize(mat3(transpose(inverse(viewModel))) * aNormal);
+}
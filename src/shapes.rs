@@ -0,0 +1,81 @@
+/// Basic shapes built as `Mesh`es, so examples don't need to carry their own vertex arrays
+pub mod shapes {
+    use std::f32::consts::PI;
+    use glam::{Vec2, Vec3};
+    use crate::mesh::mesh::{Mesh, Vertex};
+
+    /// Unit cube centered at the origin, each face with its own normal and texture coordinates.
+    pub fn cube() -> Mesh {
+        let (vertices, indices) = cube_geometry();
+        Mesh::new(vertices, indices, Vec::new())
+    }
+
+    /// Vertices and indices of `cube`, for callers that upload them with their own layout.
+    pub fn cube_geometry() -> (Vec<Vertex>, Vec<u32>) {
+        // (normal, tangent, bitangent) of every face, the tangents follow the texture coordinates
+        let faces: [(Vec3, Vec3, Vec3); 6] = [
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+            (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+            (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+            (Vec3::NEG_Y, Vec3::X, Vec3::Z)
+        ];
+        let corners: [Vec2; 4] = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0)
+        ];
+
+        let mut vertices = Vec::with_capacity(24);
+        let mut indices = Vec::with_capacity(36);
+        for (normal, tangent, bitangent) in faces {
+            let first = vertices.len() as u32;
+            for corner in corners {
+                let position = 0.5 * normal + (corner.x - 0.5) * tangent + (corner.y - 0.5) * bitangent;
+                vertices.push(Vertex::new(position, normal, corner));
+            }
+            indices.extend_from_slice(&[first, first + 1, first + 2, first + 2, first + 3, first]);
+        }
+        (vertices, indices)
+    }
+
+    /// Sphere of radius 1 centered at the origin, split in `sectors` slices around the Y axis and
+    /// `stacks` slices from the north to the south pole.
+    pub fn uv_sphere(sectors: u32, stacks: u32) -> Mesh {
+        let (vertices, indices) = uv_sphere_geometry(sectors, stacks);
+        Mesh::new(vertices, indices, Vec::new())
+    }
+
+    /// Vertices and indices of `uv_sphere`.
+    pub fn uv_sphere_geometry(sectors: u32, stacks: u32) -> (Vec<Vertex>, Vec<u32>) {
+        let mut vertices = Vec::with_capacity(((sectors + 1) * (stacks + 1)) as usize);
+        for stack in 0..=stacks {
+            let v = stack as f32 / stacks as f32;
+            let phi = v * PI;
+            for sector in 0..=sectors {
+                let u = sector as f32 / sectors as f32;
+                let theta = u * 2.0 * PI;
+                let normal = Vec3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+                vertices.push(Vertex::new(normal, normal, Vec2::new(u, 1.0 - v)));
+            }
+        }
+
+        let mut indices = Vec::with_capacity((sectors * stacks * 6) as usize);
+        for stack in 0..stacks {
+            for sector in 0..sectors {
+                let current = stack * (sectors + 1) + sector;
+                let below = current + sectors + 1;
+                // counter clockwise seen from outside the sphere
+                if stack != 0 {
+                    indices.extend_from_slice(&[current, current + 1, below]);
+                }
+                if stack != stacks - 1 {
+                    indices.extend_from_slice(&[current + 1, below + 1, below]);
+                }
+            }
+        }
+        (vertices, indices)
+    }
+}
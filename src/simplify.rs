@@ -0,0 +1,332 @@
+/// Mesh simplification by edge collapses ordered by quadric error, after Garland and Heckbert's
+/// "Surface Simplification Using Quadric Error Metrics"
+pub mod simplify {
+    use std::cmp::Ordering;
+    use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
+    use glam::{DMat3, DVec3};
+    use crate::mesh::mesh::Vertex;
+
+    /// Weight of the planes holding open borders in place, so they only move once the inside of
+    /// the surface can't be simplified further.
+    const BORDER_WEIGHT: f64 = 1000.0;
+    /// Collapses turning the normal of a triangle by more than about 78 degrees are refused, they
+    /// fold the surface over itself.
+    const MIN_NORMAL_DOT: f64 = 0.2;
+
+    /// Symmetric 4x4 matrix summing the squared distances to a set of planes, stored as its upper
+    /// triangle: aa, ab, ac, ad, bb, bc, bd, cc, cd, dd.
+    #[derive(Debug, Clone, Copy, Default)]
+    struct Quadric([f64; 10]);
+
+    /// Candidate collapse of the edge between two vertices into `position`. The versions tell
+    /// whether either vertex changed since the candidate was computed.
+    struct Collapse {
+        cost: f64,
+        kept: usize,
+        removed: usize,
+        versions: (u32, u32),
+        position: DVec3
+    }
+
+    /// Reduces the mesh to at most `target_triangles` triangles, or as close as it gets without
+    /// folding the surface. Vertices sharing a position are collapsed together, so seams in the
+    /// normals or texture coordinates stay closed; every remaining vertex keeps its normal and
+    /// texture coordinates and only moves.
+    pub fn simplify(vertices: &[Vertex], indices: &[u32], target_triangles: usize) -> (Vec<Vertex>, Vec<u32>) {
+        // one point per distinct position
+        let mut point_of_position: HashMap<[u32; 3], usize> = HashMap::new();
+        let mut positions: Vec<DVec3> = Vec::new();
+        let point_of_vertex: Vec<usize> = vertices.iter()
+            .map(|vertex| {
+                let position = vertex.position();
+                *point_of_position.entry(position.to_array().map(f32::to_bits)).or_insert_with(|| {
+                    positions.push(position.as_dvec3());
+                    positions.len() - 1
+                })
+            })
+            .collect();
+
+        let mut triangles: Vec<[usize; 3]> = Vec::new();
+        let mut corners: Vec<[u32; 3]> = Vec::new();
+        for triangle in indices.chunks_exact(3) {
+            let points = [0, 1, 2].map(|i| point_of_vertex[triangle[i] as usize]);
+            if points[0] != points[1] && points[1] != points[2] && points[0] != points[2] {
+                triangles.push(points);
+                corners.push([triangle[0], triangle[1], triangle[2]]);
+            }
+        }
+
+        let mut alive = vec![true; triangles.len()];
+        let mut live_triangles = triangles.len();
+        let mut around: Vec<Vec<usize>> = vec![Vec::new(); positions.len()];
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+        // ordered, like the neighbours below, so equal costs are collapsed in the same order every run
+        let mut edge_uses: BTreeMap<(usize, usize), (u32, usize)> = BTreeMap::new();
+        for (t, triangle) in triangles.iter().enumerate() {
+            let normal = face_normal(&positions, triangle);
+            let plane = Quadric::from_plane(normal, -normal.dot(positions[triangle[0]]), 1.0);
+            for (i, point) in triangle.iter().enumerate() {
+                around[*point].push(t);
+                quadrics[*point] = quadrics[*point].add(&plane);
+                let edge = ordered(*point, triangle[(i + 1) % 3]);
+                edge_uses.entry(edge).or_insert((0, t)).0 += 1;
+            }
+        }
+
+        // an edge used by a single triangle is on a border, a plane through it perpendicular to
+        // the triangle keeps the border from shrinking
+        for ((a, b), (uses, t)) in &edge_uses {
+            if *uses == 1 {
+                let normal = face_normal(&positions, &triangles[*t]);
+                let border_normal = (positions[*b] - positions[*a]).cross(normal).normalize_or_zero();
+                let plane = Quadric::from_plane(border_normal, -border_normal.dot(positions[*a]), BORDER_WEIGHT);
+                quadrics[*a] = quadrics[*a].add(&plane);
+                quadrics[*b] = quadrics[*b].add(&plane);
+            }
+        }
+
+        let mut versions = vec![0u32; positions.len()];
+        let mut heap: BinaryHeap<Collapse> = edge_uses.keys()
+            .map(|(a, b)| collapse(*a, *b, &positions, &quadrics, &versions))
+            .collect();
+
+        while live_triangles > target_triangles {
+            let Some(candidate) = heap.pop() else {
+                break;
+            };
+            let (kept, removed) = (candidate.kept, candidate.removed);
+            if candidate.versions != (versions[kept], versions[removed]) {
+                continue;
+            }
+            if folds(&positions, &triangles, &alive, &around, kept, removed, candidate.position) {
+                continue;
+            }
+
+            // move the kept point, hand it the triangles of the removed one and drop the ones the
+            // edge belonged to
+            positions[kept] = candidate.position;
+            quadrics[kept] = quadrics[kept].add(&quadrics[removed]);
+            for t in std::mem::take(&mut around[removed]) {
+                if !alive[t] {
+                    continue;
+                }
+                if triangles[t].contains(&kept) {
+                    alive[t] = false;
+                    live_triangles -= 1;
+                } else {
+                    for point in triangles[t].iter_mut() {
+                        if *point == removed {
+                            *point = kept;
+                        }
+                    }
+                    around[kept].push(t);
+                }
+            }
+            around[kept].retain(|t| alive[*t]);
+            versions[kept] += 1;
+            versions[removed] += 1;
+
+            let neighbours: BTreeSet<usize> = around[kept].iter()
+                .flat_map(|t| triangles[*t])
+                .filter(|point| *point != kept)
+                .collect();
+            for neighbour in neighbours {
+                heap.push(collapse(kept, neighbour, &positions, &quadrics, &versions));
+            }
+        }
+
+        // keep the vertices still used, at the position of their point
+        let mut remap = vec![u32::MAX; vertices.len()];
+        let mut simplified_vertices = Vec::new();
+        let mut simplified_indices = Vec::with_capacity(live_triangles * 3);
+        for t in (0..triangles.len()).filter(|t| alive[*t]) {
+            for (point, vertex) in triangles[t].iter().zip(corners[t]) {
+                if remap[vertex as usize] == u32::MAX {
+                    remap[vertex as usize] = simplified_vertices.len() as u32;
+                    let original = &vertices[vertex as usize];
+                    simplified_vertices.push(Vertex::new(positions[*point].as_vec3(), original.normal(), original.tex_coord()));
+                }
+                simplified_indices.push(remap[vertex as usize]);
+            }
+        }
+        (simplified_vertices, simplified_indices)
+    }
+
+    impl Quadric {
+        /// Squared distance to the plane `normal.dot(p) + distance = 0`, times `weight`.
+        fn from_plane(normal: DVec3, distance: f64, weight: f64) -> Self {
+            let (a, b, c, d) = (normal.x, normal.y, normal.z, distance);
+            Quadric([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|value| value * weight))
+        }
+
+        fn add(&self, other: &Quadric) -> Self {
+            let mut sum = self.0;
+            for (value, other) in sum.iter_mut().zip(other.0) {
+                *value += other;
+            }
+            Quadric(sum)
+        }
+
+        fn error(&self, p: DVec3) -> f64 {
+            let q = &self.0;
+            let (x, y, z) = (p.x, p.y, p.z);
+            q[0] * x * x + 2.0 * q[1] * x * y + 2.0 * q[2] * x * z + 2.0 * q[3] * x
+                + q[4] * y * y + 2.0 * q[5] * y * z + 2.0 * q[6] * y
+                + q[7] * z * z + 2.0 * q[8] * z
+                + q[9]
+        }
+
+        /// Position where the error is the lowest, when there is a single one.
+        fn minimum(&self) -> Option<DVec3> {
+            let q = &self.0;
+            let matrix = DMat3::from_cols(
+                DVec3::new(q[0], q[1], q[2]),
+                DVec3::new(q[1], q[4], q[5]),
+                DVec3::new(q[2], q[5], q[7])
+            );
+            if matrix.determinant().abs() < 1e-10 {
+                return None;
+            }
+            Some(matrix.inverse() * -DVec3::new(q[3], q[6], q[8]))
+        }
+    }
+
+    impl PartialEq for Collapse {
+        fn eq(&self, other: &Self) -> bool {
+            self.cost == other.cost
+        }
+    }
+
+    impl Eq for Collapse {}
+
+    impl PartialOrd for Collapse {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Collapse {
+        /// Reversed, so the binary heap pops the cheapest collapse first.
+        fn cmp(&self, other: &Self) -> Ordering {
+            other.cost.total_cmp(&self.cost)
+        }
+    }
+
+    /// Cheapest collapse of the edge, to the point minimizing the error of both ends when it lies
+    /// near the edge, otherwise to the best of the ends and the middle.
+    fn collapse(a: usize, b: usize, positions: &[DVec3], quadrics: &[Quadric], versions: &[u32]) -> Collapse {
+        let quadric = quadrics[a].add(&quadrics[b]);
+        let middle = (positions[a] + positions[b]) * 0.5;
+        let length = positions[a].distance(positions[b]);
+        let mut candidates = vec![positions[a], positions[b], middle];
+        if let Some(minimum) = quadric.minimum()
+            && minimum.distance(middle) <= length {
+            candidates.push(minimum);
+        }
+        let (cost, position) = candidates.into_iter()
+            .map(|position| (quadric.error(position), position))
+            .min_by(|x, y| x.0.total_cmp(&y.0))
+            .unwrap();
+        Collapse {
+            cost,
+            kept: a,
+            removed: b,
+            versions: (versions[a], versions[b]),
+            position
+        }
+    }
+
+    /// Whether moving both points to `position` would flip or squash one of the triangles that
+    /// survive the collapse.
+    fn folds(positions: &[DVec3], triangles: &[[usize; 3]], alive: &[bool], around: &[Vec<usize>], kept: usize, removed: usize, position: DVec3) -> bool {
+        around[kept].iter().chain(&around[removed])
+            .filter(|t| alive[**t] && !(triangles[**t].contains(&kept) && triangles[**t].contains(&removed)))
+            .any(|t| {
+                let triangle = triangles[*t];
+                let before = face_normal(positions, &triangle);
+                let moved = triangle.map(|point| if point == kept || point == removed { position } else { positions[point] });
+                let after = (moved[1] - moved[0]).cross(moved[2] - moved[0]).normalize_or_zero();
+                after == DVec3::ZERO || before.dot(after) < MIN_NORMAL_DOT
+            })
+    }
+
+    fn face_normal(positions: &[DVec3], triangle: &[usize; 3]) -> DVec3 {
+        let [a, b, c] = triangle.map(|point| positions[point]);
+        (b - a).cross(c - a).normalize_or_zero()
+    }
+
+    fn ordered(a: usize, b: usize) -> (usize, usize) {
+        if a < b { (a, b) } else { (b, a) }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use glam::{Vec2, Vec3};
+        use super::*;
+        use crate::shapes::shapes;
+
+        fn triangle_count(indices: &[u32]) -> usize {
+            indices.len() / 3
+        }
+
+        /// Flat square of `size` x `size` quads in the XY plane, from (0, 0) to (1, 1).
+        fn grid(size: u32) -> (Vec<Vertex>, Vec<u32>) {
+            let mut vertices = Vec::new();
+            for y in 0..=size {
+                for x in 0..=size {
+                    let uv = Vec2::new(x as f32, y as f32) / size as f32;
+                    vertices.push(Vertex::new(uv.extend(0.0), Vec3::Z, uv));
+                }
+            }
+            let mut indices = Vec::new();
+            for y in 0..size {
+                for x in 0..size {
+                    let corner = y * (size + 1) + x;
+                    let above = corner + size + 1;
+                    indices.extend_from_slice(&[corner, corner + 1, above + 1, above + 1, above, corner]);
+                }
+            }
+            (vertices, indices)
+        }
+
+        #[test]
+        fn reaches_the_target_and_keeps_the_shape_of_a_sphere() {
+            let (vertices, indices) = shapes::uv_sphere_geometry(32, 16);
+            let target = triangle_count(&indices) / 4;
+            let (simplified_vertices, simplified_indices) = simplify(&vertices, &indices, target);
+
+            assert!(triangle_count(&simplified_indices) <= target);
+            assert!(triangle_count(&simplified_indices) > target / 2, "simplified far below the target");
+            assert!(simplified_indices.iter().all(|index| (*index as usize) < simplified_vertices.len()));
+            for vertex in &simplified_vertices {
+                let distance = vertex.position().length();
+                assert!((0.9..=1.05).contains(&distance), "{vertex:?} strayed from the sphere");
+            }
+        }
+
+        #[test]
+        fn flat_surfaces_keep_their_borders() {
+            let (vertices, indices) = grid(8);
+            let (simplified_vertices, simplified_indices) = simplify(&vertices, &indices, 2);
+
+            assert_eq!(triangle_count(&simplified_indices), 2);
+            for vertex in &simplified_vertices {
+                assert!(vertex.position().z.abs() < 1e-6, "{vertex:?} left the plane");
+            }
+            for corner in [Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::new(1.0, 1.0, 0.0)] {
+                assert!(
+                    simplified_vertices.iter().any(|vertex| vertex.position().abs_diff_eq(corner, 1e-6)),
+                    "lost the corner {corner}"
+                );
+            }
+        }
+
+        #[test]
+        fn nothing_changes_below_the_target() {
+            let (vertices, indices) = shapes::cube_geometry();
+            let (simplified_vertices, simplified_indices) = simplify(&vertices, &indices, 100);
+            assert_eq!(simplified_indices.len(), indices.len());
+            assert_eq!(simplified_vertices.len(), vertices.len());
+        }
+    }
+}
@@ -0,0 +1,200 @@
+/// Screen-space ambient occlusion computed from the view space positions and normals of a G-buffer
+pub mod ssao {
+    use std::ffi::CString;
+    use std::io::Error;
+    use glam::{Mat4, Vec3};
+    use rand::Rng;
+    use crate::framebuffer::framebuffer::{ColorFormat, DepthStencil, Framebuffer};
+    use crate::postprocessing::postprocessing::ScreenQuad;
+    use crate::shaders::shaders::ShaderProgram;
+
+    pub struct Ssao {
+        pub enabled: bool,
+        /// Radius of the sampled hemisphere, in view space units.
+        pub radius: f32,
+        /// Depth offset that keeps flat surfaces from occluding themselves.
+        pub bias: f32,
+        pub blur: bool,
+        sample_count: usize,
+        kernel: Vec<Vec3>,
+        kernel_changed: bool,
+        noise_texture: u32,
+        occlusion: Framebuffer,
+        blurred_occlusion: Framebuffer,
+        ssao_program: ShaderProgram,
+        blur_program: ShaderProgram,
+        width: i32,
+        height: i32
+    }
+
+    impl Ssao {
+        /// Upper bound on the number of samples, matching the size of the array in `ssao.frag`.
+        pub const MAX_SAMPLES: usize = 64;
+        const NOISE_SIZE: i32 = 4;
+
+        pub fn new(width: i32, height: i32) -> Result<Self, Error> {
+            let occlusion = Framebuffer::new(width, height, 1, &[ColorFormat::R16F], DepthStencil::None)?;
+            let blurred_occlusion = Framebuffer::new(width, height, 1, &[ColorFormat::R16F], DepthStencil::None)?;
+
+            let ssao_program = ShaderProgram::from_files(
+                "src/shaders/vertex/screen_quad.vert",
+                "src/shaders/fragment/ssao.frag"
+            )?;
+            ssao_program.use_program();
+            ssao_program.set_int(c"gPosition", 0);
+            ssao_program.set_int(c"gNormal", 1);
+            ssao_program.set_int(c"texNoise", 2);
+            let blur_program = ShaderProgram::from_files(
+                "src/shaders/vertex/screen_quad.vert",
+                "src/shaders/fragment/ssao_blur.frag"
+            )?;
+            blur_program.use_program();
+            blur_program.set_int(c"ssaoInput", 0);
+
+            let sample_count = 32;
+            Ok(Ssao {
+                enabled: true,
+                radius: 0.5,
+                bias: 0.025,
+                blur: true,
+                sample_count,
+                kernel: Self::generate_kernel(sample_count),
+                kernel_changed: true,
+                noise_texture: Self::create_noise_texture(),
+                occlusion,
+                blurred_occlusion,
+                ssao_program,
+                blur_program,
+                width,
+                height
+            })
+        }
+
+        pub fn sample_count(&self) -> usize {
+            self.sample_count
+        }
+
+        /// Changes the number of samples per pixel, up to `MAX_SAMPLES`. The kernel gets generated
+        /// again so the samples stay spread over the whole hemisphere.
+        pub fn set_sample_count(&mut self, sample_count: usize) {
+            self.sample_count = sample_count.clamp(1, Self::MAX_SAMPLES);
+            self.kernel = Self::generate_kernel(self.sample_count);
+            self.kernel_changed = true;
+        }
+
+        /// Computes the occlusion of every pixel from the G-buffer position and normal textures,
+        /// both in view space, and blurs it if enabled. Returns the texture holding the occlusion
+        /// factor, 1 meaning fully lit, to be multiplied with the ambient light.
+        pub fn render(&mut self, quad: &ScreenQuad, position_texture: u32, normal_texture: u32, projection: &Mat4) -> u32 {
+            self.occlusion.bind();
+            self.ssao_program.use_program();
+            if self.kernel_changed {
+                for (i, sample) in self.kernel.iter().enumerate() {
+                    let c_str = CString::new(format!("samples[{i}]")).unwrap();
+                    self.ssao_program.set_vec3(&c_str, &sample.to_array());
+                }
+                self.kernel_changed = false;
+            }
+            self.ssao_program.set_int(c"kernelSize", self.sample_count as u32);
+            self.ssao_program.set_float(c"radius", self.radius);
+            self.ssao_program.set_float(c"bias", self.bias);
+            self.ssao_program.set_vec2(c"noiseScale", &[
+                self.width as f32 / Self::NOISE_SIZE as f32,
+                self.height as f32 / Self::NOISE_SIZE as f32
+            ]);
+            self.ssao_program.set_mat4(c"projection", projection);
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, position_texture);
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, normal_texture);
+                gl::ActiveTexture(gl::TEXTURE2);
+                gl::BindTexture(gl::TEXTURE_2D, self.noise_texture);
+            }
+            quad.draw();
+
+            if !self.blur {
+                unsafe { gl::ActiveTexture(gl::TEXTURE0); }
+                return self.occlusion.color_texture(0);
+            }
+            self.blurred_occlusion.bind();
+            self.blur_program.use_program();
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.occlusion.color_texture(0));
+            }
+            quad.draw();
+            self.blurred_occlusion.color_texture(0)
+        }
+
+        /// Recreates the occlusion targets for the new window size, keeping them while the window
+        /// is minimised.
+        pub fn resize(&mut self, width: i32, height: i32) -> Result<(), Error> {
+            if width <= 0 || height <= 0 {
+                return Ok(());
+            }
+            self.occlusion.resize(width, height)?;
+            self.blurred_occlusion.resize(width, height)?;
+            self.width = width;
+            self.height = height;
+            Ok(())
+        }
+
+        /// Random points inside a unit hemisphere oriented along +Z, more of them close to the
+        /// origin since occlusion from nearby geometry matters the most.
+        fn generate_kernel(sample_count: usize) -> Vec<Vec3> {
+            let mut rng = rand::rng();
+            (0..sample_count)
+                .map(|i| {
+                    let sample = Vec3::new(
+                        rng.random_range(-1.0..1.0),
+                        rng.random_range(-1.0..1.0),
+                        rng.random_range(0.0..1.0)
+                    ).normalize_or(Vec3::Z) * rng.random_range(0.0..1.0);
+                    let scale = i as f32 / sample_count as f32;
+                    // lerp(0.1, 1.0, scale^2)
+                    sample * (0.1 + 0.9 * scale * scale)
+                })
+                .collect()
+        }
+
+        /// 4x4 texture of random rotations around the Z axis, tiled over the screen so neighbouring
+        /// pixels use differently oriented kernels.
+        fn create_noise_texture() -> u32 {
+            let mut rng = rand::rng();
+            let noise: Vec<f32> = (0..Self::NOISE_SIZE * Self::NOISE_SIZE)
+                .flat_map(|_| [rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0), 0.0])
+                .collect();
+            let mut texture = 0;
+            unsafe {
+                gl::GenTextures(1, &mut texture);
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+                gl::TexImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    gl::RGB16F as i32,
+                    Self::NOISE_SIZE,
+                    Self::NOISE_SIZE,
+                    0,
+                    gl::RGB,
+                    gl::FLOAT,
+                    noise.as_ptr().cast()
+                );
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+            texture
+        }
+    }
+
+    impl Drop for Ssao {
+        fn drop(&mut self) {
+            unsafe {
+                gl::DeleteTextures(1, &self.noise_texture);
+            }
+        }
+    }
+}
@@ -0,0 +1,439 @@
+/// Text in screen space: a TrueType font rasterised into a glyph atlas, laid out with kerning and
+/// drawn as textured quads, for HUD overlays
+pub mod text {
+    use std::collections::HashMap;
+    use std::io::Error;
+    use std::mem::offset_of;
+    use std::path::Path;
+    use fontdue::{Font, FontSettings};
+    use glam::{Vec2, Vec3, Vec4};
+    use crate::shaders::shaders::ShaderProgram;
+
+    /// Font every example can use, see `src/fonts/LICENSE`.
+    pub const DEFAULT_FONT: &str = "src/fonts/DejaVuSans.ttf";
+
+    /// Width of the atlas, which grows in height when full.
+    const ATLAS_WIDTH: usize = 512;
+    /// Empty pixels around each glyph, so the filtering doesn't bleed the neighbours in.
+    const PADDING: usize = 1;
+    /// Largest font size, leaving room on a row of the atlas for glyphs up to four times as wide.
+    pub const MAX_PX: f32 = (ATLAS_WIDTH / 4) as f32;
+
+    /// Where a glyph is in the atlas and how it sits on the baseline, in pixels.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Glyph {
+        /// Top left corner in the atlas.
+        pub atlas_position: Vec2,
+        pub size: Vec2,
+        /// From the pen position on the baseline to the top left corner of the bitmap, y down.
+        pub offset: Vec2,
+        pub advance: f32
+    }
+
+    /// Glyph placed by `FontAtlas::layout`, in pixels from the top left of the screen.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct PlacedGlyph {
+        pub character: char,
+        pub position: Vec2,
+        pub glyph: Glyph
+    }
+
+    /// A font at one pixel size and the glyphs of it rasterised so far, packed in rows into a
+    /// single channel coverage bitmap. Glyphs are rasterised the first time they are laid out, so
+    /// any character of the font can be drawn, and the atlas doubles in height when a glyph no
+    /// longer fits. Positions in the atlas never move, only `generation` changes.
+    pub struct FontAtlas {
+        font: Font,
+        px: f32,
+        ascent: f32,
+        line_height: f32,
+        pixels: Vec<u8>,
+        height: usize,
+        glyphs: HashMap<char, Glyph>,
+        /// Top, height and filled width of the row glyphs are added to
+        row: (usize, usize, usize),
+        generation: u32
+    }
+
+    /// Draws the text queued over a frame in one draw call, with the atlas as texture.
+    pub struct TextRenderer {
+        atlas: FontAtlas,
+        vertices: Vec<TextVertex>,
+        program: ShaderProgram,
+        vao: u32,
+        vbo: u32,
+        texture: u32,
+        /// Generation of the atlas last uploaded to the texture
+        uploaded: Option<u32>
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    struct TextVertex {
+        position: Vec2,
+        tex_coords: Vec2,
+        color: Vec4
+    }
+
+    impl FontAtlas {
+        /// Loads a TrueType or OpenType font for text `px` pixels high, at most `MAX_PX`, with the
+        /// printable ASCII characters already in the atlas.
+        pub fn new<P: AsRef<Path>>(path: P, px: f32) -> Result<Self, Error> {
+            let path = path.as_ref();
+            if !(px > 0.0 && px <= MAX_PX) {
+                return Err(Error::other(format!("ERROR::TEXT::FONT_SIZE {}\n{px} pixels, sizes go up to {MAX_PX}", path.display())));
+            }
+            let data = std::fs::read(path)
+                .map_err(|e| Error::other(format!("ERROR::TEXT::READ_FONT {}\n{e}", path.display())))?;
+            Self::from_bytes(&data, px)
+                .map_err(|e| Error::other(format!("ERROR::TEXT::PARSE_FONT {}\n{e}", path.display())))
+        }
+
+        fn from_bytes(data: &[u8], px: f32) -> Result<Self, &'static str> {
+            let font = Font::from_bytes(data, FontSettings { scale: px, ..FontSettings::default() })?;
+            let line_metrics = font.horizontal_line_metrics(px).ok_or("no horizontal line metrics")?;
+            let mut atlas = FontAtlas {
+                font,
+                px,
+                ascent: line_metrics.ascent,
+                line_height: line_metrics.new_line_size.ceil(),
+                pixels: vec![0; ATLAS_WIDTH * 128],
+                height: 128,
+                glyphs: HashMap::new(),
+                row: (0, 0, 0),
+                generation: 0
+            };
+            for character in ' '..='~' {
+                atlas.glyph(character);
+            }
+            Ok(atlas)
+        }
+
+        pub fn px(&self) -> f32 {
+            self.px
+        }
+
+        /// Distance between the baselines of two lines.
+        pub fn line_height(&self) -> f32 {
+            self.line_height
+        }
+
+        pub fn width(&self) -> usize {
+            ATLAS_WIDTH
+        }
+
+        pub fn height(&self) -> usize {
+            self.height
+        }
+
+        /// Coverage of every pixel of the atlas, row by row from the top.
+        pub fn pixels(&self) -> &[u8] {
+            &self.pixels
+        }
+
+        /// Changes whenever glyphs are added, to know when the copy on the GPU is out of date.
+        pub fn generation(&self) -> u32 {
+            self.generation
+        }
+
+        /// The glyph of `character`, rasterised into the atlas if it isn't there yet. Characters
+        /// the font hasn't got use its missing glyph box.
+        pub fn glyph(&mut self, character: char) -> Glyph {
+            if let Some(glyph) = self.glyphs.get(&character) {
+                return *glyph;
+            }
+            let (metrics, coverage) = self.font.rasterize(character, self.px);
+            let (mut width, mut height) = (metrics.width, metrics.height);
+            if width + PADDING * 2 > ATLAS_WIDTH {
+                // a glyph far wider than the font size, which no row can hold, only moves the pen
+                (width, height) = (0, 0);
+            }
+            let (x, y) = self.allocate(width, height);
+            for row in 0..height {
+                let start = (y + row) * ATLAS_WIDTH + x;
+                self.pixels[start..start + width].copy_from_slice(&coverage[row * width..(row + 1) * width]);
+            }
+            let glyph = Glyph {
+                atlas_position: Vec2::new(x as f32, y as f32),
+                size: Vec2::new(width as f32, height as f32),
+                // fontdue measures from the bottom left with y up
+                offset: Vec2::new(metrics.xmin as f32, -(metrics.ymin as f32 + height as f32)),
+                advance: metrics.advance_width
+            };
+            self.glyphs.insert(character, glyph);
+            self.generation += 1;
+            glyph
+        }
+
+        /// Places the glyphs of `text` with its top left corner at `position`. Each line starts
+        /// back at the left, `line_height` lower, and glyphs are moved closer or apart by the
+        /// kerning of the font with the one before. Pen positions are rounded to whole pixels so
+        /// the glyphs stay as sharp as they were rasterised.
+        pub fn layout(&mut self, text: &str, position: Vec2) -> Vec<PlacedGlyph> {
+            let mut placed = Vec::with_capacity(text.len());
+            let mut pen = Vec2::new(position.x, position.y + self.ascent);
+            let mut previous: Option<char> = None;
+            for character in text.chars() {
+                if character == '\n' {
+                    pen = Vec2::new(position.x, pen.y + self.line_height);
+                    previous = None;
+                    continue;
+                }
+                if let Some(previous) = previous {
+                    pen.x += self.font.horizontal_kern(previous, character, self.px).unwrap_or(0.0);
+                }
+                let glyph = self.glyph(character);
+                if glyph.size.x > 0.0 && glyph.size.y > 0.0 {
+                    placed.push(PlacedGlyph { character, position: pen.round() + glyph.offset, glyph });
+                }
+                pen.x += glyph.advance;
+                previous = Some(character);
+            }
+            placed
+        }
+
+        /// Width of the longest line and height of all the lines of `text`, in pixels.
+        pub fn measure(&mut self, text: &str) -> Vec2 {
+            let mut width: f32 = 0.0;
+            for line in text.split('\n') {
+                let mut line_width = 0.0;
+                let mut previous: Option<char> = None;
+                for character in line.chars() {
+                    if let Some(previous) = previous {
+                        line_width += self.font.horizontal_kern(previous, character, self.px).unwrap_or(0.0);
+                    }
+                    line_width += self.glyph(character).advance;
+                    previous = Some(character);
+                }
+                width = width.max(line_width);
+            }
+            Vec2::new(width, text.split('\n').count() as f32 * self.line_height)
+        }
+
+        /// Free spot for a glyph, on the current row, on a new row below it, or below everything
+        /// once the atlas has grown.
+        fn allocate(&mut self, width: usize, height: usize) -> (usize, usize) {
+            let (padded_width, padded_height) = (width + PADDING * 2, height + PADDING * 2);
+            let (mut top, mut row_height, mut filled) = self.row;
+            if filled + padded_width > ATLAS_WIDTH {
+                top += row_height;
+                row_height = 0;
+                filled = 0;
+            }
+            while top + padded_height > self.height {
+                self.height *= 2;
+                // rows are whole, so growing only appends empty rows at the bottom
+                self.pixels.resize(ATLAS_WIDTH * self.height, 0);
+            }
+            self.row = (top, row_height.max(padded_height), filled + padded_width);
+            (filled + PADDING, top + PADDING)
+        }
+    }
+
+    impl TextRenderer {
+        pub fn new<P: AsRef<Path>>(font_path: P, px: f32) -> Result<Self, Error> {
+            let atlas = FontAtlas::new(font_path, px)?;
+            let program = ShaderProgram::from_files(
+                "src/shaders/vertex/text.vert",
+                "src/shaders/fragment/text.frag"
+            )?;
+            program.use_program();
+            program.set_int(c"atlas", 0);
+
+            let (mut vao, mut vbo, mut texture) = (0, 0, 0);
+            unsafe {
+                gl::GenVertexArrays(1, &mut vao);
+                gl::GenBuffers(1, &mut vbo);
+                gl::BindVertexArray(vao);
+                gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+                let stride = size_of::<TextVertex>() as i32;
+                for (location, size, offset) in [
+                    (0, 2, offset_of!(TextVertex, position)),
+                    (1, 2, offset_of!(TextVertex, tex_coords)),
+                    (2, 4, offset_of!(TextVertex, color))
+                ] {
+                    gl::EnableVertexAttribArray(location);
+                    gl::VertexAttribPointer(location, size, gl::FLOAT, gl::FALSE, stride, offset as *const _);
+                }
+                gl::BindVertexArray(0);
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+                gl::GenTextures(1, &mut texture);
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+            Ok(TextRenderer { atlas, vertices: Vec::new(), program, vao, vbo, texture, uploaded: None })
+        }
+
+        pub fn atlas(&mut self) -> &mut FontAtlas {
+            &mut self.atlas
+        }
+
+        /// Adds `text` to the next `flush`, its top left corner `position` pixels from the top left
+        /// of the window.
+        pub fn queue(&mut self, text: &str, position: Vec2, color: Vec3) {
+            let color = color.extend(1.0);
+            for placed in self.atlas.layout(text, position) {
+                let (min, max) = (placed.position, placed.position + placed.glyph.size);
+                let (uv_min, uv_max) = (placed.glyph.atlas_position, placed.glyph.atlas_position + placed.glyph.size);
+                let corner = |x: bool, y: bool| TextVertex {
+                    position: Vec2::new(if x { max.x } else { min.x }, if y { max.y } else { min.y }),
+                    tex_coords: Vec2::new(if x { uv_max.x } else { uv_min.x }, if y { uv_max.y } else { uv_min.y }),
+                    color
+                };
+                self.vertices.extend([
+                    corner(false, false), corner(false, true), corner(true, true),
+                    corner(false, false), corner(true, true), corner(true, false)
+                ]);
+            }
+        }
+
+        /// Same as `queue` with a dark copy one pixel down and right underneath, readable over
+        /// any background.
+        pub fn queue_with_shadow(&mut self, text: &str, position: Vec2, color: Vec3) {
+            self.queue(text, position + Vec2::ONE, Vec3::ZERO);
+            self.queue(text, position, color);
+        }
+
+        /// Draws everything queued over the bound framebuffer of `width` by `height` pixels and
+        /// empties the queue. Blending, the blend function, depth testing and the unpack alignment
+        /// are left as they were found.
+        pub fn flush(&mut self, width: i32, height: i32) {
+            if self.vertices.is_empty() {
+                return;
+            }
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.texture);
+                if self.uploaded != Some(self.atlas.generation()) {
+                    // rows of single bytes aren't 4 byte aligned
+                    let mut alignment = 4;
+                    gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut alignment);
+                    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+                    gl::TexImage2D(
+                        gl::TEXTURE_2D,
+                        0,
+                        gl::R8 as i32,
+                        self.atlas.width() as i32,
+                        self.atlas.height() as i32,
+                        0,
+                        gl::RED,
+                        gl::UNSIGNED_BYTE,
+                        self.atlas.pixels().as_ptr().cast()
+                    );
+                    gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+                    self.uploaded = Some(self.atlas.generation());
+                }
+
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    size_of_val(self.vertices.as_slice()) as isize,
+                    self.vertices.as_ptr().cast(),
+                    gl::STREAM_DRAW
+                );
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+                let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+                let blend = gl::IsEnabled(gl::BLEND) == gl::TRUE;
+                let mut blend_func = [0; 4];
+                for (value, parameter) in blend_func.iter_mut().zip([gl::BLEND_SRC_RGB, gl::BLEND_DST_RGB, gl::BLEND_SRC_ALPHA, gl::BLEND_DST_ALPHA]) {
+                    gl::GetIntegerv(parameter, value);
+                }
+                gl::Disable(gl::DEPTH_TEST);
+                gl::Enable(gl::BLEND);
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+
+                self.program.use_program();
+                self.program.set_vec2(c"screenSize", &[width as f32, height as f32]);
+                self.program.set_vec2(c"atlasSize", &[self.atlas.width() as f32, self.atlas.height() as f32]);
+                gl::BindVertexArray(self.vao);
+                gl::DrawArrays(gl::TRIANGLES, 0, self.vertices.len() as i32);
+                gl::BindVertexArray(0);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+
+                if depth_test {
+                    gl::Enable(gl::DEPTH_TEST);
+                }
+                if !blend {
+                    gl::Disable(gl::BLEND);
+                }
+                let [src_rgb, dst_rgb, src_alpha, dst_alpha] = blend_func.map(|factor| factor as u32);
+                gl::BlendFuncSeparate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+            }
+            self.vertices.clear();
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn atlas() -> FontAtlas {
+            FontAtlas::new(DEFAULT_FONT, 20.0).unwrap()
+        }
+
+        #[test]
+        fn kerning_pulls_pairs_together() {
+            let mut atlas = atlas();
+            let apart = atlas.measure("A").x + atlas.measure("V").x;
+            assert!(atlas.measure("AV").x < apart);
+            // pairs the font doesn't kern just add up
+            assert_eq!(atlas.measure("HH").x, atlas.measure("H").x * 2.0);
+        }
+
+        #[test]
+        fn lines_start_at_the_left_one_line_lower() {
+            let mut atlas = atlas();
+            let origin = Vec2::new(10.0, 20.0);
+            let placed = atlas.layout("Hi\nHo", origin);
+            let (first, second) = (placed[0], placed[2]);
+            assert_eq!(second.character, 'H');
+            assert_eq!(first.position.x, second.position.x);
+            assert_eq!(second.position.y - first.position.y, atlas.line_height());
+            // capitals reach up to about the top of the line
+            assert!(first.position.y >= origin.y && first.position.y < origin.y + atlas.px() * 0.5);
+            assert_eq!(atlas.measure("Hi\nHo").y, atlas.line_height() * 2.0);
+            // spaces take room without a quad
+            assert_eq!(atlas.layout("a b", Vec2::ZERO).len(), 2);
+        }
+
+        #[test]
+        fn font_sizes_too_large_for_the_atlas_are_refused() {
+            let error = FontAtlas::new(DEFAULT_FONT, MAX_PX * 2.0).err().unwrap();
+            assert!(error.to_string().starts_with("ERROR::TEXT::FONT_SIZE"));
+            assert!(FontAtlas::new(DEFAULT_FONT, 0.0).is_err());
+            assert!(FontAtlas::new(DEFAULT_FONT, MAX_PX).is_ok());
+        }
+
+        #[test]
+        fn glyphs_are_added_without_overlapping_and_the_atlas_grows() {
+            let mut atlas = FontAtlas::new(DEFAULT_FONT, 48.0).unwrap();
+            let generation = atlas.generation();
+            let (width, height) = (atlas.width(), atlas.height());
+            let glyphs: Vec<Glyph> = ('À'..='ÿ').map(|character| atlas.glyph(character)).collect();
+            assert!(atlas.generation() > generation);
+            assert!(atlas.height() > height);
+            assert_eq!(atlas.pixels().len(), width * atlas.height());
+            // asking again rasterises nothing
+            let generation = atlas.generation();
+            assert_eq!(atlas.glyph('é'), glyphs['é' as usize - 'À' as usize]);
+            assert_eq!(atlas.generation(), generation);
+
+            let rectangle = |glyph: &Glyph| (glyph.atlas_position, glyph.atlas_position + glyph.size);
+            for (i, a) in glyphs.iter().enumerate() {
+                let (min, max) = rectangle(a);
+                assert!(max.x <= width as f32 && max.y <= atlas.height() as f32);
+                for b in &glyphs[i + 1..] {
+                    let (other_min, other_max) = rectangle(b);
+                    let overlap = min.x < other_max.x && other_min.x < max.x && min.y < other_max.y && other_min.y < max.y;
+                    assert!(!overlap);
+                }
+            }
+        }
+    }
+}
@@ -4,12 +4,24 @@ pub mod textures {
 
     pub struct Texture {
         texture_id: u32,
-        is_png: bool
+        is_png: bool,
+        srgb: bool
     }
 
     impl Texture {
         pub fn load_texture<P: AsRef<Path>>(texture_path: P, is_png: bool) -> Result<u32, ImageError> {
-            let texture = Self::create_texture(is_png);
+            Self::load(texture_path, is_png, false)
+        }
+
+        /// Loads a colour texture stored in sRGB, like most diffuse maps, so sampling it returns
+        /// linear values that can be lit correctly. Data textures such as specular maps are
+        /// already linear and go through `load_texture`.
+        pub fn load_srgb_texture<P: AsRef<Path>>(texture_path: P, is_png: bool) -> Result<u32, ImageError> {
+            Self::load(texture_path, is_png, true)
+        }
+
+        fn load<P: AsRef<Path>>(texture_path: P, is_png: bool, srgb: bool) -> Result<u32, ImageError> {
+            let texture = Self::create_texture(is_png, srgb);
             texture.configure_texture();
             let img = image::open(texture_path)?.flipv();
 
@@ -31,11 +43,19 @@ pub mod textures {
                 } else {
                     gl::RGB
                 };
+            // the alpha channel is kept for opacity maps
+            let internal_format =
+                match (self.is_png, self.srgb) {
+                    (true, true) => gl::SRGB_ALPHA,
+                    (true, false) => gl::RGBA,
+                    (false, true) => gl::SRGB,
+                    (false, false) => gl::RGB
+                };
             unsafe {
                 gl::TexImage2D(
                     gl::TEXTURE_2D,
                     0,
-                    gl::RGB as i32,
+                    internal_format as i32,
                     img.width() as i32,
                     img.height() as i32,
                     0,
@@ -56,12 +76,12 @@ pub mod textures {
             }
         }
 
-        fn create_texture(is_png: bool) -> Self {
+        fn create_texture(is_png: bool, srgb: bool) -> Self {
             let mut texture_id = 0;
             unsafe {
                 gl::GenTextures(1, &mut texture_id);
             }
-            Texture { texture_id, is_png }
+            Texture { texture_id, is_png, srgb }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,137 @@
+// Golden image tests: every scene in `scenes::NAMES` is rendered headless at a fixed size and time
+// and compared with its reference in `tests/golden`. Failures leave the rendered image and a diff
+// next to each other in `target/golden`. The test needs EGL, so it is ignored by a plain
+// `cargo test` and run on its own:
+//
+//     LIBGL_ALWAYS_SOFTWARE=1 cargo test --test golden -- --ignored
+//
+// After an intended visual change, render new references with `BLESS=1` and commit them. Once
+// asked for, the test fails when no headless OpenGL context can be created instead of passing
+// without comparing anything.
+use std::path::{Path, PathBuf};
+use image::{Rgba, RgbaImage};
+use opengl_book_examples::headless::headless::{HeadlessContext, HeadlessRenderer};
+use opengl_book_examples::scenes::scenes;
+
+const REFERENCE_DIR: &str = "tests/golden";
+const OUTPUT_DIR: &str = "target/golden";
+
+const WIDTH: i32 = 320;
+const HEIGHT: i32 = 240;
+/// Three frames half a second apart, so the last one is drawn at exactly one second.
+const FRAMES: u32 = 3;
+const TIME_STEP: f32 = 0.5;
+
+/// Largest perceived colour difference between two pixels still considered equal, from 0 (exact)
+/// to 1 (black against white). Absorbs rounding differences between drivers.
+const PIXEL_THRESHOLD: f32 = 0.1;
+/// Fraction of the pixels allowed to differ, mostly edges rasterized slightly differently.
+const MAX_DIFFERENT_PIXELS: f32 = 0.005;
+
+#[test]
+#[ignore = "needs a headless OpenGL context, run with --ignored"]
+fn golden_images() {
+    let bless = std::env::var_os("BLESS").is_some();
+    let _context = match HeadlessContext::new() {
+        Ok(context) => context,
+        Err(e) => panic!("No headless OpenGL context for the golden image tests: {e}")
+    };
+    let renderer = HeadlessRenderer::new(WIDTH, HEIGHT, 1).unwrap();
+
+    let mut failures = Vec::new();
+    for name in scenes::NAMES {
+        let mut scene = scenes::by_name(name).unwrap().unwrap();
+        let actual = renderer.render(scene.as_mut(), FRAMES, TIME_STEP).unwrap();
+        let reference_path = Path::new(REFERENCE_DIR).join(format!("{name}.png"));
+
+        if bless {
+            std::fs::create_dir_all(REFERENCE_DIR).unwrap();
+            actual.save(&reference_path).unwrap();
+            println!("Blessed {}", reference_path.display());
+            continue;
+        }
+        if let Err(message) = check(name, &reference_path, &actual) {
+            failures.push(message);
+        }
+    }
+    assert!(failures.is_empty(), "{} scene(s) differ from their references:\n{}", failures.len(), failures.join("\n"));
+}
+
+/// Compares `actual` with the reference, writing both images and their diff to `OUTPUT_DIR` when
+/// they differ.
+fn check(name: &str, reference_path: &Path, actual: &RgbaImage) -> Result<(), String> {
+    let reference = match image::open(reference_path) {
+        Ok(reference) => reference.to_rgba8(),
+        Err(e) => return Err(format!("{name}: cannot read {}, run with BLESS=1 to create it: {e}", reference_path.display()))
+    };
+    if reference.dimensions() != actual.dimensions() {
+        return Err(format!(
+            "{name}: reference is {:?} but the scene was rendered at {:?}",
+            reference.dimensions(),
+            actual.dimensions()
+        ));
+    }
+
+    let (different, diff) = compare(&reference, actual);
+    let allowed = (MAX_DIFFERENT_PIXELS * (actual.width() * actual.height()) as f32) as u32;
+    if different <= allowed {
+        return Ok(());
+    }
+
+    let actual_path = output_path(name, "actual");
+    let diff_path = output_path(name, "diff");
+    std::fs::create_dir_all(OUTPUT_DIR).map_err(|e| e.to_string())?;
+    actual.save(&actual_path).map_err(|e| e.to_string())?;
+    diff.save(&diff_path).map_err(|e| e.to_string())?;
+    Err(format!(
+        "{name}: {different} pixels differ, at most {allowed} allowed, see {} and {}",
+        actual_path.display(),
+        diff_path.display()
+    ))
+}
+
+fn output_path(name: &str, suffix: &str) -> PathBuf {
+    Path::new(OUTPUT_DIR).join(format!("{name}.{suffix}.png"))
+}
+
+/// Counts the pixels whose perceived difference is above `PIXEL_THRESHOLD`, and returns an image
+/// showing them in red over a faded grey copy of the reference.
+fn compare(reference: &RgbaImage, actual: &RgbaImage) -> (u32, RgbaImage) {
+    // YIQ distance of two fully different pixels, black against white
+    const MAX_DELTA: f32 = 35215.0;
+    let threshold = MAX_DELTA * PIXEL_THRESHOLD * PIXEL_THRESHOLD;
+
+    let mut different = 0;
+    let mut diff = RgbaImage::new(reference.width(), reference.height());
+    for ((expected, actual), out) in reference.pixels().zip(actual.pixels()).zip(diff.pixels_mut()) {
+        if color_delta(expected, actual) > threshold {
+            different += 1;
+            *out = Rgba([255, 0, 0, 255]);
+        } else {
+            let (y, _, _) = yiq(expected);
+            let faded = (255.0 - 0.1 * (255.0 - y)) as u8;
+            *out = Rgba([faded, faded, faded, 255]);
+        }
+    }
+    (different, diff)
+}
+
+/// Squared distance in YIQ space, weighted so it follows how differently the colours are perceived
+/// (Kotsarenko and Ramos, "Measuring perceived color difference using YIQ NTSC transmission color
+/// space in mobile applications"). Both pixels are blended over white first.
+fn color_delta(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+    let (y1, i1, q1) = yiq(a);
+    let (y2, i2, q2) = yiq(b);
+    let (y, i, q) = (y1 - y2, i1 - i2, q1 - q2);
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+fn yiq(pixel: &Rgba<u8>) -> (f32, f32, f32) {
+    let alpha = pixel[3] as f32 / 255.0;
+    let [r, g, b] = [0, 1, 2].map(|channel| 255.0 + (pixel[channel] as f32 - 255.0) * alpha);
+    (
+        0.2988953 * r + 0.5866225 * g + 0.1144822 * b,
+        0.595978 * r - 0.2741761 * g - 0.3218019 * b,
+        0.2114702 * r - 0.5226171 * g + 0.3111469 * b
+    )
+}